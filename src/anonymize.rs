@@ -0,0 +1,201 @@
+//
+// ===================== АНОНИМИЗАЦИЯ (--anonymize) =====================
+//
+// Имена и id участников заменяются псевдонимами User1, User2, ... до
+// того, как сообщение попадёт в лог и статистику, так что везде они
+// одинаковые. Псевдоним привязан к from_id (а если id нет — к имени) и
+// выдаётся по порядку первого появления.
+//
+// Заменяются: from/from_id, actor/actor_id, members, реакции,
+// forwarded_from от людей, упоминания в разметке. Чаты получают имена
+// Chat1, Chat2, ... Из обычного текста убираются только те упоминания,
+// что есть в разметке: имя, набранное руками, останется как есть.
+//
+
+use ahash::AHashMap;
+use simd_json::BorrowedValue;
+
+use std::borrow::Cow;
+use simd_json::borrowed::Object;
+
+use crate::{get_i64_field, get_str_field};
+
+#[derive(Default)]
+pub struct Anonymizer {
+    // from_id -> номер
+    by_id: AHashMap<String, usize>,
+    // имя или "@username" -> номер
+    by_name: AHashMap<String, usize>,
+    people: usize,
+    chats: AHashMap<String, String>,
+}
+
+fn pseudonym(n: usize) -> String {
+    format!("User{n}")
+}
+
+/// "user123" -> "user7": префикс типа (user/channel) остаётся.
+fn pseudo_id(id: &str, n: usize) -> String {
+    let prefix = id.trim_end_matches(|c: char| c.is_ascii_digit());
+    format!("{prefix}{n}")
+}
+
+fn set_str(obj: &mut Object, key: &str, value: String) {
+    obj.insert(key.to_string().into(), BorrowedValue::from(value));
+}
+
+impl Anonymizer {
+    /// Номер участника; связывает имя и id, если известны оба.
+    fn person(&mut self, name: Option<&str>, id: Option<&str>) -> usize {
+        let known = id
+            .and_then(|id| self.by_id.get(id))
+            .or_else(|| name.and_then(|n| self.by_name.get(n)))
+            .copied();
+        let n = known.unwrap_or_else(|| {
+            self.people += 1;
+            self.people
+        });
+        if let Some(id) = id {
+            self.by_id.entry(id.to_string()).or_insert(n);
+        }
+        if let Some(name) = name {
+            self.by_name.entry(name.to_string()).or_insert(n);
+        }
+        n
+    }
+
+    /// Псевдоним чата; для одного и того же id — один и тот же.
+    pub fn chat(&mut self, id: &str, name: &str) -> String {
+        let key = if id.is_empty() { name } else { id };
+        let next = self.chats.len() + 1;
+        self.chats.entry(key.to_string()).or_insert_with(|| format!("Chat{next}")).clone()
+    }
+
+    /// Пара полей "имя"/"id" объекта: заменяются те, что есть строкой.
+    fn rewrite_pair(&mut self, obj: &mut Object, name_key: &str, id_key: &str) {
+        let name = get_str_field(obj, name_key).map(str::to_string);
+        let id = get_str_field(obj, id_key).map(str::to_string);
+        if name.is_none() && id.is_none() {
+            return;
+        }
+        let n = self.person(name.as_deref(), id.as_deref());
+        if name.is_some() {
+            set_str(obj, name_key, pseudonym(n));
+        }
+        if let Some(id) = id {
+            set_str(obj, id_key, pseudo_id(&id, n));
+        }
+    }
+
+    /// Упоминания в разметке; в `replaced` — (было, стало) для простого текста.
+    fn rewrite_entities(&mut self, v: &mut BorrowedValue, replaced: &mut Vec<(String, String)>) {
+        let BorrowedValue::Array(items) = v else {
+            return;
+        };
+        for item in items.iter_mut() {
+            let BorrowedValue::Object(ent) = item else {
+                continue;
+            };
+            match get_str_field(ent, "type") {
+                Some("mention") => {
+                    let Some(text) = get_str_field(ent, "text") else {
+                        continue;
+                    };
+                    let old = text.to_string();
+                    let n = self.person(Some(&old.to_lowercase()), None);
+                    let new = format!("@{}", pseudonym(n));
+                    set_str(ent, "text", new.clone());
+                    replaced.push((old, new));
+                }
+                Some("mention_name") => {
+                    let id = get_i64_field(ent, "user_id").map(|id| format!("user{id}"));
+                    let text = get_str_field(ent, "text").map(str::to_string);
+                    let n = self.person(text.as_deref(), id.as_deref());
+                    set_str(ent, "text", pseudonym(n));
+                    if id.is_some() {
+                        ent.insert("user_id".into(), BorrowedValue::from(n as u64));
+                    }
+                    if let Some(old) = text {
+                        replaced.push((old, pseudonym(n)));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Заменяет в сообщении всё, что указывает на участников.
+    pub fn rewrite(&mut self, msg_obj: &mut Object) {
+        self.rewrite_pair(msg_obj, "from", "from_id");
+        self.rewrite_pair(msg_obj, "actor", "actor_id");
+
+        // пересылки от каналов оставляем: это публичные источники
+        let forwarded_id = get_str_field(msg_obj, "forwarded_from_id");
+        if !forwarded_id.is_some_and(|id| id.starts_with("channel")) {
+            self.rewrite_pair(msg_obj, "forwarded_from", "forwarded_from_id");
+        }
+
+        if let Some(BorrowedValue::Array(members)) = msg_obj.get_mut("members") {
+            let names: Vec<Option<String>> = members
+                .iter()
+                .map(|m| match m {
+                    BorrowedValue::String(s) => Some(s.to_string()),
+                    _ => None,
+                })
+                .collect();
+            for (m, name) in members.iter_mut().zip(names) {
+                if let Some(name) = name {
+                    let n = self.person(Some(&name), None);
+                    *m = BorrowedValue::from(pseudonym(n));
+                }
+            }
+        }
+
+        if let Some(BorrowedValue::Array(reactions)) = msg_obj.get_mut("reactions") {
+            for r in reactions.iter_mut() {
+                let BorrowedValue::Object(r) = r else {
+                    continue;
+                };
+                if let Some(BorrowedValue::Array(recent)) = r.get_mut("recent") {
+                    for who in recent.iter_mut() {
+                        if let BorrowedValue::Object(who) = who {
+                            self.rewrite_pair(who, "from", "from_id");
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut replaced = Vec::new();
+        for key in ["text", "text_entities"] {
+            if let Some(v) = msg_obj.get_mut(key) {
+                self.rewrite_entities(v, &mut replaced);
+            }
+        }
+        // в "text" то же упоминание может стоять и простой строкой
+        if !replaced.is_empty() {
+            let replace_all = |s: &mut Cow<str>| {
+                for (old, new) in &replaced {
+                    if s.contains(old.as_str()) {
+                        *s = s.replace(old.as_str(), new).into();
+                    }
+                }
+            };
+            match msg_obj.get_mut("text") {
+                Some(BorrowedValue::String(s)) => replace_all(s),
+                Some(BorrowedValue::Array(parts)) => {
+                    for part in parts.iter_mut() {
+                        if let BorrowedValue::String(s) = part {
+                            replace_all(s);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // присланный контакт — чужие имя и телефон
+        msg_obj.remove("contact_information");
+        msg_obj.remove("contact_vcard");
+    }
+}
@@ -0,0 +1,122 @@
+//
+// ===================== ДОПИСЫВАНИЕ ЛОГА (--append) =====================
+//
+// Рядом с логом лежит chat.txt.state: id чата и последнего записанного
+// сообщения. Следующий запуск с --append дописывает в лог только то, что
+// новее, а не пересоздаёт его. Статистика прошлых запусков — в
+// chat.txt.stats (формат кэша): новые сообщения досчитываются к ней, и
+// итог всегда по всему логу. Начать заново — удалить лог, .state и .stats.
+//
+// Файл состояния от другого чата — ошибка: дописать чужой лог легко, а
+// разобрать потом трудно.
+//
+
+use simd_json::borrowed::Object;
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::stats::Stats;
+use crate::{cache, get_i64_field};
+
+pub struct AppendState {
+    path: String,
+    chat_id: String,
+    // id последнего сообщения в логе с прошлого запуска
+    after: Option<i64>,
+    last: Option<i64>,
+    pub appended: usize,
+}
+
+impl AppendState {
+    /// Состояние для лога `log_path`; нет файла — лог пишется с начала.
+    pub fn load(log_path: &str, chat_id: &str) -> io::Result<Self> {
+        let path = format!("{log_path}.state");
+        let after = match fs::read_to_string(&path) {
+            Ok(s) => {
+                let (saved_chat, last) = s.trim_end().rsplit_once(' ').unwrap_or(("", ""));
+                if saved_chat != chat_id {
+                    return Err(io::Error::other(format!(
+                        "{path} — от другого чата ({saved_chat}); уберите его или --append"
+                    )));
+                }
+                let last = last
+                    .parse()
+                    .map_err(|_| io::Error::other(format!("{path}: битый файл состояния")))?;
+                Some(last)
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e),
+        };
+        Ok(Self { path, chat_id: chat_id.to_string(), after, last: after, appended: 0 })
+    }
+
+    /// Лог уже есть и дописывается.
+    pub fn resumes(&self) -> bool {
+        self.after.is_some()
+    }
+
+    /// Сообщения ещё нет ни в логе, ни в сохранённой статистике.
+    pub fn is_new(&self, msg_obj: &Object) -> bool {
+        match get_i64_field(msg_obj, "id") {
+            Some(id) => self.after.is_none_or(|after| id > after),
+            None => self.after.is_none(),
+        }
+    }
+
+    /// Сообщение новее записанного в прошлый раз (и запоминается как записанное).
+    pub fn fresh(&mut self, msg_obj: &Object) -> bool {
+        if !self.is_new(msg_obj) {
+            return false;
+        }
+        let Some(id) = get_i64_field(msg_obj, "id") else {
+            return true;
+        };
+        self.last = Some(self.last.map_or(id, |last| last.max(id)));
+        self.appended += 1;
+        true
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        match self.last {
+            Some(last) => fs::write(&self.path, format!("{} {last}\n", self.chat_id)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Статистика прошлых запусков: <лог>.stats рядом с <лог>.state.
+pub fn stats_path(output: &str) -> PathBuf {
+    PathBuf::from(format!("{output}.stats"))
+}
+
+/// Сохранённая статистика; None — первый запуск. `key` — настройки подсчёта:
+/// с другими досчитывать нельзя, итог смешал бы несравнимое.
+pub fn load_stats(path: &Path, key: u64) -> io::Result<Option<Stats>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let fail = |why: &str| {
+        io::Error::other(format!(
+            "{}: {why}; удалите лог, .state и .stats, чтобы начать заново",
+            path.display()
+        ))
+    };
+    let mut stats = match cache::load::<Stats>(path, key) {
+        Ok(Some(stats)) => stats,
+        Ok(None) => {
+            return Err(fail("статистика посчитана с другими настройками или версией tgjsps"));
+        }
+        Err(e) => return Err(fail(&e)),
+    };
+    // это было про файлы прошлого запуска
+    stats.split_files = None;
+    stats.appended = None;
+    stats.merged = None;
+    Ok(Some(stats))
+}
+
+pub fn save_stats(path: &Path, key: u64, stats: &Stats) -> io::Result<()> {
+    cache::save(path, key, stats)
+}
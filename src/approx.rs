@@ -0,0 +1,51 @@
+//
+// ===================== ПРИБЛИЗИТЕЛЬНЫЙ ПОДСЧЁТ (--approx) =====================
+//
+// На очень болтливых экспортах память съедают словари слов и текстов для
+// спама: word_freq, word_freq_per_author, spam_map. С --approx N каждый
+// такой словарь держит не больше 2×N счётчиков, а дойдя до предела,
+// оставляет N самых больших. Частые слова и повторы переживают отсев,
+// редкие выпадают, и память не растёт с размером чата.
+//
+// Это не Space-Saving и не Count-Min: выпавший ключ, встретившись снова,
+// начинает счёт с 1, а погрешность нигде не копится. Поэтому счётчики —
+// только нижние оценки без оценки погрешности (так и подписано в выводе),
+// зато повтор не появится в спаме на пустом месте.
+//
+
+use ahash::AHashMap;
+
+/// Отсеять лишнее, если словарь дорос до 2×`capacity`.
+pub fn trim(map: &mut AHashMap<String, usize>, capacity: Option<usize>) {
+    if let Some(capacity) = capacity
+        && map.len() >= capacity.saturating_mul(2)
+    {
+        keep_largest(map, capacity);
+    }
+}
+
+/// Оставить `capacity` самых больших счётчиков (при равенстве — какие попадутся).
+fn keep_largest(map: &mut AHashMap<String, usize>, capacity: usize) {
+    if map.len() <= capacity {
+        return;
+    }
+    if capacity == 0 {
+        map.clear();
+        return;
+    }
+    let mut counts: Vec<usize> = map.values().copied().collect();
+    let (_, &mut threshold, _) = counts.select_nth_unstable_by(capacity - 1, |a, b| b.cmp(a));
+    let above = counts.iter().filter(|&&c| c > threshold).count();
+    let mut ties = capacity - above;
+    map.retain(|_, &mut c| {
+        if c > threshold {
+            return true;
+        }
+        if c == threshold && ties > 0 {
+            ties -= 1;
+            return true;
+        }
+        false
+    });
+    map.shrink_to(capacity * 2);
+}
@@ -0,0 +1,110 @@
+//
+// ===================== ВЫГРУЗКА В ARROW IPC =====================
+//
+// Колонки с типами (дата — timestamp, id — int64) в файле Arrow IPC
+// (он же Feather v2): pandas.read_feather / polars.read_ipc читают его
+// без повторного разбора JSON. Пишем батчами, так что память не растёт
+// и в потоковом режиме.
+//
+
+use arrow_array::RecordBatch;
+use arrow_array::builder::{Int64Builder, StringBuilder, TimestampSecondBuilder};
+use arrow_ipc::writer::FileWriter;
+use arrow_schema::{ArrowError, DataType, Field, Schema, SchemaRef, TimeUnit};
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::sync::Arc;
+
+use crate::MessageRecord;
+
+const BATCH_ROWS: usize = 64 * 1024;
+
+pub struct ArrowSink {
+    writer: FileWriter<BufWriter<File>>,
+    schema: SchemaRef,
+    rows: usize,
+
+    chat: StringBuilder,
+    id: Int64Builder,
+    date: TimestampSecondBuilder,
+    author: StringBuilder,
+    from_id: StringBuilder,
+    text: StringBuilder,
+    media_type: StringBuilder,
+    reply_to: Int64Builder,
+}
+
+impl ArrowSink {
+    pub fn create(path: &str) -> Result<Self, ArrowError> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("chat", DataType::Utf8, false),
+            Field::new("id", DataType::Int64, false),
+            Field::new("date", DataType::Timestamp(TimeUnit::Second, None), true),
+            Field::new("author", DataType::Utf8, false),
+            Field::new("from_id", DataType::Utf8, false),
+            Field::new("text", DataType::Utf8, false),
+            Field::new("media_type", DataType::Utf8, true),
+            Field::new("reply_to", DataType::Int64, true),
+        ]));
+        let file = BufWriter::new(File::create(path)?);
+        let writer = FileWriter::try_new(file, &schema)?;
+        Ok(Self {
+            writer,
+            schema,
+            rows: 0,
+            chat: StringBuilder::new(),
+            id: Int64Builder::new(),
+            date: TimestampSecondBuilder::new(),
+            author: StringBuilder::new(),
+            from_id: StringBuilder::new(),
+            text: StringBuilder::new(),
+            media_type: StringBuilder::new(),
+            reply_to: Int64Builder::new(),
+        })
+    }
+
+    pub fn insert(&mut self, chat: &str, rec: &MessageRecord) -> Result<(), ArrowError> {
+        self.chat.append_value(chat);
+        self.id.append_value(rec.id);
+        self.date.append_option(rec.datetime.map(|dt| dt.and_utc().timestamp()));
+        self.author.append_value(rec.author);
+        self.from_id.append_value(rec.from_id);
+        self.text.append_value(&rec.text);
+        self.media_type.append_option(rec.media_type);
+        self.reply_to.append_option(rec.reply_to);
+
+        self.rows += 1;
+        if self.rows >= BATCH_ROWS {
+            self.flush_batch()?;
+        }
+        Ok(())
+    }
+
+    fn flush_batch(&mut self) -> Result<(), ArrowError> {
+        if self.rows == 0 {
+            return Ok(());
+        }
+        let batch = RecordBatch::try_new(
+            self.schema.clone(),
+            vec![
+                Arc::new(self.chat.finish()),
+                Arc::new(self.id.finish()),
+                Arc::new(self.date.finish()),
+                Arc::new(self.author.finish()),
+                Arc::new(self.from_id.finish()),
+                Arc::new(self.text.finish()),
+                Arc::new(self.media_type.finish()),
+                Arc::new(self.reply_to.finish()),
+            ],
+        )?;
+        self.writer.write(&batch)?;
+        self.rows = 0;
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> Result<(), ArrowError> {
+        self.flush_batch()?;
+        self.writer.finish()
+    }
+}
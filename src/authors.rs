@@ -0,0 +1,148 @@
+//
+// ===================== ИМЕНА АВТОРОВ ПО FROM_ID =====================
+//
+// Люди меняют имя, и статистика по "from" делит одного человека на
+// несколько. Поэтому перед основным проходом собираем для каждого
+// from_id его самое свежее имя, а при разборе подставляем его вместо
+// "from" (и "actor", и имён в реакциях). Двум разным id с одинаковым
+// именем дописывается id: "Саша (user123)".
+//
+// Старое поведение — ключ по имени как есть — включается --by-name.
+//
+
+use ahash::AHashMap;
+use simd_json::BorrowedValue;
+use simd_json::borrowed::Object;
+
+use crate::get_str_field;
+
+#[derive(Default)]
+pub struct AuthorNames {
+    // from_id -> (дата, имя) последнего сообщения; даты ISO сравниваются строкой
+    latest: AHashMap<String, (String, String)>,
+    // from_id -> имя для статистики и лога
+    resolved: AHashMap<String, String>,
+}
+
+impl AuthorNames {
+    fn note(&mut self, obj: &Object, name_key: &str, id_key: &str, date: &str) {
+        let (Some(name), Some(id)) = (get_str_field(obj, name_key), get_str_field(obj, id_key))
+        else {
+            return;
+        };
+        match self.latest.get_mut(id) {
+            Some(entry) if entry.0.as_str() > date => {}
+            Some(entry) => *entry = (date.to_string(), name.to_string()),
+            None => {
+                self.latest.insert(id.to_string(), (date.to_string(), name.to_string()));
+            }
+        }
+    }
+
+    /// Первый проход: запоминает имена автора, actor-а и реакций.
+    pub fn observe(&mut self, msg_obj: &Object) {
+        let date = get_str_field(msg_obj, "date").unwrap_or("");
+        self.note(msg_obj, "from", "from_id", date);
+        self.note(msg_obj, "actor", "actor_id", date);
+        if let Some(BorrowedValue::Array(reactions)) = msg_obj.get("reactions") {
+            for r in reactions.iter() {
+                if let BorrowedValue::Object(r) = r
+                    && let Some(BorrowedValue::Array(recent)) = r.get("recent")
+                {
+                    for who in recent.iter() {
+                        if let BorrowedValue::Object(who) = who {
+                            let when = get_str_field(who, "date").unwrap_or(date);
+                            self.note(who, "from", "from_id", when);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// После первого прохода: итоговое имя каждого id.
+    pub fn finish(&mut self) {
+        let mut ids_by_name: AHashMap<&str, usize> = AHashMap::new();
+        for (_, name) in self.latest.values() {
+            *ids_by_name.entry(name.as_str()).or_insert(0) += 1;
+        }
+        self.resolved = self
+            .latest
+            .iter()
+            .map(|(id, (_, name))| {
+                let shown = if ids_by_name[name.as_str()] > 1 {
+                    format!("{name} ({id})")
+                } else {
+                    name.clone()
+                };
+                (id.clone(), shown)
+            })
+            .collect();
+    }
+
+    fn differs(&self, obj: &Object, name_key: &str, id_key: &str) -> bool {
+        let Some(id) = get_str_field(obj, id_key) else {
+            return false;
+        };
+        match (self.resolved.get(id), get_str_field(obj, name_key)) {
+            (Some(shown), Some(name)) => shown != name,
+            _ => false,
+        }
+    }
+
+    fn rename(&self, obj: &mut Object, name_key: &str, id_key: &str) {
+        if self.differs(obj, name_key, id_key)
+            && let Some(shown) = get_str_field(obj, id_key).and_then(|id| self.resolved.get(id))
+        {
+            obj.insert(name_key.to_string().into(), BorrowedValue::from(shown.clone()));
+        }
+    }
+
+    fn reactions_differ(&self, msg_obj: &Object) -> bool {
+        let Some(BorrowedValue::Array(reactions)) = msg_obj.get("reactions") else {
+            return false;
+        };
+        for r in reactions.iter() {
+            if let BorrowedValue::Object(r) = r
+                && let Some(BorrowedValue::Array(recent)) = r.get("recent")
+            {
+                for who in recent.iter() {
+                    if let BorrowedValue::Object(who) = who
+                        && self.differs(who, "from", "from_id")
+                    {
+                        return true;
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    /// Копия сообщения с подставленными именами; None — менять нечего.
+    pub fn rewrite<'v>(&self, msg_obj: &Object<'v>) -> Option<Object<'v>> {
+        if !self.differs(msg_obj, "from", "from_id")
+            && !self.differs(msg_obj, "actor", "actor_id")
+            && !self.reactions_differ(msg_obj)
+        {
+            return None;
+        }
+
+        let mut obj = msg_obj.clone();
+        self.rename(&mut obj, "from", "from_id");
+        self.rename(&mut obj, "actor", "actor_id");
+        if let Some(BorrowedValue::Array(reactions)) = obj.get_mut("reactions") {
+            for r in reactions.iter_mut() {
+                if let BorrowedValue::Object(r) = r
+                    && let Some(BorrowedValue::Array(recent)) = r.get_mut("recent")
+                {
+                    for who in recent.iter_mut() {
+                        if let BorrowedValue::Object(who) = who {
+                            self.rename(who, "from", "from_id");
+                        }
+                    }
+                }
+            }
+        }
+        Some(obj)
+    }
+}
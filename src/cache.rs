@@ -0,0 +1,342 @@
+//
+// ===================== КЭШ СТАТИСТИКИ (--cache) =====================
+//
+// Разбор большого экспорта долгий, а чтобы сменить --stat-format или
+// построить отчёт, достаточно уже посчитанной статистики. С --cache она
+// сохраняется в .tgjsps-cache рядом с входом; следующий запуск с тем же
+// входом (хеш содержимого), теми же файлами медиа (опись --media-dir) и
+// теми же настройками подсчёта берёт её оттуда и экспорт не разбирает —
+// лог тогда не пересоздаётся.
+//
+// Формат свой: поля по порядку, числа little-endian, у строк и коллекций
+// длина впереди. Каждый тип статистики описывает свои поля одной строкой
+// codec!(Тип { поле, ... }) в своём модуле; забытое поле не соберётся.
+// При изменении состава полей поднимается FORMAT_VERSION.
+//
+
+use ahash::{AHashMap, AHashSet};
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime};
+use siphasher::sip::SipHasher13;
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::hash::{Hash, Hasher};
+use std::io::{self, BufReader, Read};
+use std::path::Path;
+
+pub const CACHE_FILE: &str = ".tgjsps-cache";
+const MAGIC: &[u8; 8] = b"TGJSPSC\0";
+const FORMAT_VERSION: u32 = 5;
+
+pub trait Codec: Sized {
+    fn encode(&self, out: &mut Vec<u8>);
+    fn decode(r: &mut Reader) -> Result<Self, String>;
+}
+
+pub struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], String> {
+        let end = self.pos.checked_add(n).filter(|&end| end <= self.data.len());
+        let end = end.ok_or("кэш оборван")?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    /// Длина коллекции; не больше оставшихся байт, чтобы битый файл не
+    /// заставил выделить гигабайты.
+    fn len(&mut self) -> Result<usize, String> {
+        let n = usize::decode(self)?;
+        if n > self.data.len() - self.pos {
+            return Err("кэш повреждён".into());
+        }
+        Ok(n)
+    }
+}
+
+/// Поля структуры по порядку: codec!(Тип { a, b, c }).
+macro_rules! codec {
+    ($ty:ident { $($field:ident),* $(,)? }) => {
+        impl $crate::cache::Codec for $ty {
+            fn encode(&self, out: &mut Vec<u8>) {
+                $( $crate::cache::Codec::encode(&self.$field, out); )*
+            }
+            fn decode(r: &mut $crate::cache::Reader) -> Result<Self, String> {
+                Ok(Self { $( $field: $crate::cache::Codec::decode(r)?, )* })
+            }
+        }
+    };
+}
+pub(crate) use codec;
+
+macro_rules! int_codec {
+    ($($t:ty),*) => {$(
+        impl Codec for $t {
+            fn encode(&self, out: &mut Vec<u8>) {
+                out.extend_from_slice(&self.to_le_bytes());
+            }
+            fn decode(r: &mut Reader) -> Result<Self, String> {
+                let bytes = r.take(size_of::<$t>())?;
+                Ok(<$t>::from_le_bytes(bytes.try_into().expect("размер числа")))
+            }
+        }
+    )*};
+}
+int_codec!(u8, u32, u64, i32, i64, f64);
+
+impl Codec for usize {
+    fn encode(&self, out: &mut Vec<u8>) {
+        (*self as u64).encode(out);
+    }
+    fn decode(r: &mut Reader) -> Result<Self, String> {
+        usize::try_from(u64::decode(r)?).map_err(|_| "кэш повреждён".to_string())
+    }
+}
+
+impl Codec for bool {
+    fn encode(&self, out: &mut Vec<u8>) {
+        (*self as u8).encode(out);
+    }
+    fn decode(r: &mut Reader) -> Result<Self, String> {
+        Ok(u8::decode(r)? != 0)
+    }
+}
+
+impl Codec for String {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.len().encode(out);
+        out.extend_from_slice(self.as_bytes());
+    }
+    fn decode(r: &mut Reader) -> Result<Self, String> {
+        let n = r.len()?;
+        String::from_utf8(r.take(n)?.to_vec()).map_err(|_| "кэш повреждён".to_string())
+    }
+}
+
+// метки вида "видео" или "edit_group_title" хранятся как &'static str;
+// из кэша их немного, и утечка на время запуска дешевле интернирования
+impl Codec for &'static str {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.to_string().encode(out);
+    }
+    fn decode(r: &mut Reader) -> Result<Self, String> {
+        Ok(String::decode(r)?.leak())
+    }
+}
+
+impl<T: Codec> Codec for Option<T> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            Some(v) => {
+                true.encode(out);
+                v.encode(out);
+            }
+            None => false.encode(out),
+        }
+    }
+    fn decode(r: &mut Reader) -> Result<Self, String> {
+        Ok(if bool::decode(r)? { Some(T::decode(r)?) } else { None })
+    }
+}
+
+impl<T: Codec> Codec for Vec<T> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.len().encode(out);
+        for v in self {
+            v.encode(out);
+        }
+    }
+    fn decode(r: &mut Reader) -> Result<Self, String> {
+        let n = r.len()?;
+        (0..n).map(|_| T::decode(r)).collect()
+    }
+}
+
+impl<T: Codec, const N: usize> Codec for [T; N] {
+    fn encode(&self, out: &mut Vec<u8>) {
+        for v in self {
+            v.encode(out);
+        }
+    }
+    fn decode(r: &mut Reader) -> Result<Self, String> {
+        let items: Vec<T> = (0..N).map(|_| T::decode(r)).collect::<Result<_, _>>()?;
+        items.try_into().map_err(|_| "кэш повреждён".to_string())
+    }
+}
+
+impl<A: Codec, B: Codec> Codec for (A, B) {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.0.encode(out);
+        self.1.encode(out);
+    }
+    fn decode(r: &mut Reader) -> Result<Self, String> {
+        Ok((A::decode(r)?, B::decode(r)?))
+    }
+}
+
+impl<K: Codec + Eq + Hash, V: Codec> Codec for AHashMap<K, V> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.len().encode(out);
+        for (k, v) in self {
+            k.encode(out);
+            v.encode(out);
+        }
+    }
+    fn decode(r: &mut Reader) -> Result<Self, String> {
+        let n = r.len()?;
+        (0..n).map(|_| <(K, V)>::decode(r)).collect()
+    }
+}
+
+impl<K: Codec + Eq + Hash> Codec for AHashSet<K> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.len().encode(out);
+        for k in self {
+            k.encode(out);
+        }
+    }
+    fn decode(r: &mut Reader) -> Result<Self, String> {
+        let n = r.len()?;
+        (0..n).map(|_| K::decode(r)).collect()
+    }
+}
+
+impl<K: Codec + Ord, V: Codec> Codec for BTreeMap<K, V> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.len().encode(out);
+        for (k, v) in self {
+            k.encode(out);
+            v.encode(out);
+        }
+    }
+    fn decode(r: &mut Reader) -> Result<Self, String> {
+        let n = r.len()?;
+        (0..n).map(|_| <(K, V)>::decode(r)).collect()
+    }
+}
+
+impl Codec for NaiveDate {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.num_days_from_ce().encode(out);
+    }
+    fn decode(r: &mut Reader) -> Result<Self, String> {
+        NaiveDate::from_num_days_from_ce_opt(i32::decode(r)?).ok_or("кэш повреждён".into())
+    }
+}
+
+impl Codec for NaiveDateTime {
+    fn encode(&self, out: &mut Vec<u8>) {
+        let utc = self.and_utc();
+        utc.timestamp().encode(out);
+        utc.timestamp_subsec_nanos().encode(out);
+    }
+    fn decode(r: &mut Reader) -> Result<Self, String> {
+        let (secs, nanos) = (i64::decode(r)?, u32::decode(r)?);
+        DateTime::from_timestamp(secs, nanos).map(|dt| dt.naive_utc()).ok_or("кэш повреждён".into())
+    }
+}
+
+/// Ключ кэша: SipHash-1-3 с постоянным ключом — одинаков между запусками и
+/// сборками (у ahash зерно случайное, а алгоритм зависит от процессора).
+#[derive(Clone, Copy)]
+pub struct KeyHasher(SipHasher13);
+
+impl KeyHasher {
+    pub fn new() -> Self {
+        KeyHasher(SipHasher13::new_with_keys(SIP_KEY.0, SIP_KEY.1))
+    }
+
+    pub fn write(&mut self, bytes: &[u8]) {
+        self.0.write(bytes);
+    }
+
+    /// Содержимое файла целиком, кусками.
+    pub fn write_file(&mut self, path: &str) -> io::Result<()> {
+        let mut r = BufReader::with_capacity(1 << 20, File::open(path)?);
+        let mut buf = vec![0u8; 1 << 20];
+        loop {
+            match r.read(&mut buf)? {
+                0 => return Ok(()),
+                n => self.write(&buf[..n]),
+            }
+        }
+    }
+
+    /// Опись медиа экспорта: пути, размеры и время изменения файлов во всех
+    /// подпапках (не содержимое). Файлы в самой папке не в счёт: там
+    /// result.json, этот кэш и, бывает, логи. Папки нет — опись пустая.
+    pub fn write_media_dir(&mut self, dir: &Path) -> io::Result<()> {
+        match sorted_entries(dir) {
+            Ok(entries) => {
+                for entry in entries.iter().filter(|e| e.path().is_dir()) {
+                    self.write_tree(&entry.path())?;
+                }
+                Ok(())
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn write_tree(&mut self, dir: &Path) -> io::Result<()> {
+        for entry in sorted_entries(dir)? {
+            let meta = entry.metadata()?;
+            let path = entry.path();
+            self.write(path.to_string_lossy().as_bytes());
+            if meta.is_dir() {
+                self.write_tree(&path)?;
+                continue;
+            }
+            let mtime = meta.modified()?.duration_since(std::time::UNIX_EPOCH).unwrap_or_default();
+            self.write(&meta.len().to_le_bytes());
+            self.write(&mtime.as_nanos().to_le_bytes());
+        }
+        // конец папки: «a/b» и «a» + «b» не совпадут
+        self.write(&[0xff]);
+        Ok(())
+    }
+
+    pub fn finish(self) -> u64 {
+        self.0.finish()
+    }
+}
+
+fn sorted_entries(dir: &Path) -> io::Result<Vec<std::fs::DirEntry>> {
+    let mut entries: Vec<_> = std::fs::read_dir(dir)?.collect::<Result<_, _>>()?;
+    entries.sort_by_key(|e| e.file_name());
+    Ok(entries)
+}
+
+// постоянный ключ SipHash: сменить — значит сбросить все кэши
+const SIP_KEY: (u64, u64) = (0x7467_6a73_7073_2d63, 0x6163_6865_2d6b_6579);
+
+/// Статистика из кэша, если ключ совпал; None — кэша нет или он от
+/// другого входа, Err — файл испорчен.
+pub fn load<T: Codec>(path: &Path, key: u64) -> Result<Option<T>, String> {
+    let data = match std::fs::read(path) {
+        Ok(data) => data,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.to_string()),
+    };
+    let mut r = Reader { data: &data, pos: 0 };
+    if r.take(MAGIC.len()).ok() != Some(&MAGIC[..])
+        || u32::decode(&mut r)? != FORMAT_VERSION
+        || u64::decode(&mut r)? != key
+    {
+        return Ok(None);
+    }
+    T::decode(&mut r).map(Some)
+}
+
+pub fn save<T: Codec>(path: &Path, key: u64, value: &T) -> io::Result<()> {
+    let mut out = Vec::with_capacity(1 << 16);
+    out.extend_from_slice(MAGIC);
+    FORMAT_VERSION.encode(&mut out);
+    key.encode(&mut out);
+    value.encode(&mut out);
+    std::fs::write(path, out)
+}
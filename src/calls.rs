@@ -0,0 +1,75 @@
+//
+// ===================== ЗВОНКИ =====================
+//
+// Служебные phone_call / group_call: "duration_seconds" и (у личных
+// звонков) "discard_reason": missed, busy, hangup, disconnect.
+// Звонок без длительности считаем несостоявшимся.
+//
+
+use ahash::AHashMap;
+use simd_json::borrowed::Object;
+
+use std::io::{self, Write};
+
+use crate::durations::{duration_seconds, format_duration};
+use crate::{get_str_field, sorted_by_count};
+
+#[derive(Default)]
+pub struct CallStats {
+    pub total: usize,
+    pub group_calls: usize,
+    pub answered: usize,
+    pub missed: usize,
+    pub total_seconds: u64,
+    // причина завершения -> сколько
+    pub by_reason: AHashMap<String, usize>,
+    // кто звонил -> сколько
+    pub per_author: AHashMap<String, usize>,
+}
+
+crate::cache::codec!(CallStats {
+    total, group_calls, answered, missed, total_seconds, by_reason, per_author,
+});
+
+impl CallStats {
+    /// `action` — уже известный phone_call или group_call.
+    pub fn observe(&mut self, action: &str, actor: &str, msg_obj: &Object) {
+        self.total += 1;
+        if action == "group_call" {
+            self.group_calls += 1;
+        }
+        *self.per_author.entry(actor.to_string()).or_insert(0) += 1;
+
+        if let Some(reason) = get_str_field(msg_obj, "discard_reason") {
+            *self.by_reason.entry(reason.to_string()).or_insert(0) += 1;
+        }
+
+        match duration_seconds(msg_obj) {
+            Some(secs) if secs > 0 => {
+                self.answered += 1;
+                self.total_seconds += secs;
+            }
+            _ => self.missed += 1,
+        }
+    }
+
+    pub fn write_text<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(w, "Звонки: {} (групповых: {})", self.total, self.group_calls)?;
+        writeln!(w, "  состоялось: {}", self.answered)?;
+        writeln!(w, "  пропущено / отклонено: {}", self.missed)?;
+        writeln!(w, "  общая длительность: {}", format_duration(self.total_seconds))?;
+        let avg = self.total_seconds.checked_div(self.answered as u64).unwrap_or(0);
+        writeln!(w, "  средняя длительность: {}", format_duration(avg))?;
+        if !self.by_reason.is_empty() {
+            writeln!(w, "  причины завершения:")?;
+            for (reason, count) in sorted_by_count(&self.by_reason) {
+                writeln!(w, "  - {}: {}", reason, count)?;
+            }
+        }
+        writeln!(w, "  кто звонил:")?;
+        for (name, count) in sorted_by_count(&self.per_author) {
+            writeln!(w, "  - {}: {}", name, count)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,67 @@
+//
+// ===================== ГРАФИКИ ФАЙЛАМИ (--charts) =====================
+//
+// Графики из HTML-отчёта — отдельными SVG-файлами, чтобы вставить их в
+// презентацию или документ. Растровых PNG нет: для них нужна своя
+// отрисовка шрифтов, а SVG и так открывается в браузере и офисных пакетах
+// и переводится в PNG любым конвертером.
+//
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::report::{
+    Chart, author_items, esc, svg_hbars, svg_heatmap, svg_timeline, svg_vbars,
+};
+use crate::{Stats, WEEKDAYS_SHORT};
+
+const PAD: usize = 16;
+const TITLE_H: usize = 28;
+
+const STYLE: &str = "text { font-family: sans-serif; font-size: 12px; fill: #333; } \
+                     text.title { font-size: 16px; font-weight: bold; } \
+                     rect.bar { fill: #4a90d9; }";
+
+/// Самостоятельный SVG: белый фон, заголовок, поля.
+fn standalone(title: &str, chart: &Chart) -> String {
+    let width = chart.width + 2 * PAD;
+    let height = chart.height + TITLE_H + 2 * PAD;
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+         viewBox=\"0 0 {width} {height}\"><style>{STYLE}</style>\
+         <rect width=\"{width}\" height=\"{height}\" fill=\"#fff\"/>\
+         <text class=\"title\" x=\"{PAD}\" y=\"{}\">{}</text>\
+         <g transform=\"translate({PAD} {})\">{}</g></svg>\n",
+        PAD + 18,
+        esc(title),
+        PAD + TITLE_H,
+        chart.body
+    )
+}
+
+/// Записать графики в `dir`; возвращает, сколько файлов записано.
+pub fn write_charts(dir: &Path, stats: &Stats) -> io::Result<usize> {
+    fs::create_dir_all(dir)?;
+    let hour_labels: Vec<String> = (0..24).map(|h| format!("{h:02}")).collect();
+    let weekday_labels: Vec<String> = WEEKDAYS_SHORT.map(String::from).to_vec();
+    let mut charts = vec![
+        ("authors.svg", "Доли участников", svg_hbars(&author_items(stats), stats.total_messages)),
+        ("hours.svg", "Активность по часам", svg_vbars(&hour_labels, &stats.hour_hist)),
+        (
+            "weekdays.svg",
+            "Активность по дням недели",
+            svg_vbars(&weekday_labels, &stats.weekday_hist),
+        ),
+        ("heatmap.svg", "День недели × час", svg_heatmap(&stats.week_hour)),
+    ];
+    let months = stats.timeline.months();
+    if !months.is_empty() {
+        charts.push(("activity.svg", "Сообщения по месяцам", svg_timeline(&months)));
+    }
+    for (file, title, chart) in &charts {
+        fs::write(dir.join(file), standalone(&format!("{} — {title}", stats.chat_name), chart))?;
+    }
+    Ok(charts.len())
+}
@@ -0,0 +1,1122 @@
+use clap::parser::ValueSource;
+use clap::{CommandFactory, FromArgMatches, Parser};
+use simd_json::prelude::*;
+
+use chrono::NaiveDateTime;
+use chrono_tz::Tz;
+
+use crate::context::SearchContext;
+use crate::log_template::Field;
+use crate::output::{
+    DEFAULT_DATE_FORMAT, LogStyle, OutputFormat, StatFormat, write_stats_as, write_stats_to_files,
+};
+use crate::parser::{STDIO, discover_export, expand_glob, read_input, run_dom_root, run_input};
+use crate::replies::MessageIndex;
+use crate::stats::{Processor, SpamConfig, Stats, TOP_SPAMMERS, TOP_WORDS};
+use crate::table::Term;
+use crate::timings::{Timings, timed};
+use crate::{
+    Options, append, arrow_out, cache, charts, config, counters, diff, extract, filter, forwards,
+    graph, html_chat, links, log_out, log_template, logger, manifest, merge, pins, redact, report,
+    script, sqlite, tui, watch,
+};
+
+use log::{error, info, warn};
+
+use std::io::{self, BufWriter, IsTerminal, Write};
+use std::path::PathBuf;
+use std::process::exit;
+use std::time::Instant;
+
+//
+// ===================== CLI =====================
+//
+
+// Без подкоманды — лог и статистика сразу (как convert + stats). Подкоманды
+// convert, stats, search и report — те же флаги, только с другим набором
+// выходов; merge и diff — отдельные инструменты со своими аргументами.
+
+#[derive(Parser, Debug)]
+#[command(
+    author = "ты",
+    version,
+    about = "Telegram JSON -> текстовый лог + статистика",
+    long_about = None,
+    args_conflicts_with_subcommands = true
+)]
+struct App {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    cli: Cli,
+}
+
+#[derive(clap::Args, Debug)]
+struct Cli {
+    /// Входной JSON (экспорт из Telegram); можно .gz или .zip с result.json внутри,
+    /// папку экспорта (тогда она же --media-dir) или "-" — читать из stdin.
+    /// Несколько -i (или шаблон вида 'exports/*.json') склеиваются в один
+    /// экспорт без повторов по id сообщения
+    #[arg(short = 'i', long = "input", value_name = "INPUT", default_value = "result.json")]
+    input: Vec<String>,
+
+    /// Выходной текстовый лог чата; "-" — в stdout (статистика тогда в stderr)
+    #[arg(short = 'o', long = "output", default_value = "chat.txt")]
+    output: String,
+
+    /// Формат лога чата
+    #[arg(long = "output-format", value_enum, default_value_t = OutputFormat::Text)]
+    output_format: OutputFormat,
+
+    /// Отмечать в логе ответы: "↳ ответ <автор>: ..."
+    #[arg(long = "replies")]
+    replies: bool,
+
+    /// К отметке ответа добавлять цитату из первых N символов (включает --replies)
+    #[arg(long = "reply-quote", value_name = "N")]
+    reply_quote: Option<usize>,
+
+    /// Писать в текстовый лог служебные события: "*** <кто> вступает по ссылке ***"
+    #[arg(long = "service")]
+    service: bool,
+
+    /// Начинать каждую строку лога с даты и времени сообщения
+    #[arg(long = "with-date", conflicts_with = "format")]
+    with_date: bool,
+
+    /// Шаблон строки лога, например "{date} {name} ({from_id}): {text}".
+    /// Поля: id, date, name (author), from_id, media, text, reply
+    #[arg(long = "format", value_name = "TEMPLATE")]
+    format: Option<String>,
+
+    /// Часовой пояс для дат и гистограмм активности (например, Europe/Berlin);
+    /// берётся из date_unixtime, без него даты — как в экспорте
+    #[arg(long = "timezone", value_name = "TZ")]
+    timezone: Option<Tz>,
+
+    /// Формат даты для --with-date и {date} (strftime)
+    #[arg(
+        long = "date-format",
+        value_name = "FMT",
+        default_value = DEFAULT_DATE_FORMAT,
+        value_parser = parse_date_format
+    )]
+    date_format: String,
+
+    /// Расширенная статистика (топ слов, активность, спамеры); -vv — ещё и
+    /// отладка в stderr: пропущенные сообщения и незнакомые поля
+    #[arg(short = 'v', long = "verbose", action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// Не печатать ничего, кроме ошибок и самой статистики
+    #[arg(short = 'q', long = "quiet")]
+    quiet: bool,
+
+    /// Без цвета в таблицах статистики в терминале (как и NO_COLOR=1)
+    #[arg(long = "no-color")]
+    no_color: bool,
+
+    /// Дополнительные стоп-слова для топа слов: файл, по слову на строку
+    #[arg(long = "stopwords", value_name = "FILE")]
+    stopwords: Option<PathBuf>,
+
+    /// Не отбрасывать встроенные стоп-слова (русские и английские)
+    #[arg(long = "no-stopwords")]
+    no_stopwords: bool,
+
+    /// Сводить формы слова к основе в топе слов (русский и английский):
+    /// "сообщение/сообщения/сообщений" -> "сообщен"
+    #[arg(long = "stem")]
+    stem: bool,
+
+    /// Сколько слов показывать в топе слов
+    #[arg(long = "top-words", value_name = "N", default_value_t = TOP_WORDS)]
+    top_words: usize,
+
+    /// Писать статистику в файл (stat.txt / stat.json / stat_*.csv) вместо консоли
+    #[arg(long = "txt")]
+    stat_txt: bool,
+
+    /// Формат статистики
+    #[arg(long = "stat-format", value_enum, default_value_t = StatFormat::Text)]
+    stat_format: StatFormat,
+
+    /// Самодостаточный HTML-отчёт с графиками (включает подсчёт как при -v)
+    #[arg(long = "report", value_name = "FILE")]
+    report: Option<String>,
+
+    /// Графики в папку отдельными SVG: лента по месяцам, часы, дни недели,
+    /// участники (включает подсчёт как при -v)
+    #[arg(long = "charts", value_name = "DIR")]
+    charts: Option<PathBuf>,
+
+    /// Граф "кто кому отвечает" с весами рёбер: Graphviz DOT или GraphML
+    /// (по расширению .graphml) — включает подсчёт как при -v
+    #[arg(long = "graph", value_name = "FILE")]
+    graph: Option<String>,
+
+    /// Добавить в --graph рёбра упоминаний
+    #[arg(long = "graph-mentions", requires = "graph")]
+    graph_mentions: bool,
+
+    /// Отдельный граф упоминаний "кто кого упоминает" (DOT или GraphML)
+    #[arg(long = "mention-graph", value_name = "FILE")]
+    mention_graph: Option<String>,
+
+    /// Записать историю закреплённых сообщений в отдельный файл
+    /// (без значения — pins.txt)
+    #[arg(
+        long = "pins",
+        value_name = "FILE",
+        num_args = 0..=1,
+        default_missing_value = "pins.txt"
+    )]
+    pins: Option<String>,
+
+    /// Выгрузить сообщения и статистику в базу SQLite (файл перезаписывается)
+    #[arg(long = "sqlite", value_name = "FILE")]
+    sqlite: Option<String>,
+
+    /// Выгрузить сообщения в Arrow IPC / Feather (типизированные колонки
+    /// для pandas / polars)
+    #[arg(long = "arrow", value_name = "FILE")]
+    arrow: Option<String>,
+
+    /// Папка экспорта с медиафайлами: посчитать, сколько места они занимают
+    #[arg(long = "media-dir", value_name = "DIR")]
+    media_dir: Option<PathBuf>,
+
+    /// Манифест всех файлов медиа (CSV или JSON — по расширению) с отметкой,
+    /// есть ли файл в папке экспорта (--media-dir или папка входного файла)
+    #[arg(long = "media-manifest", value_name = "FILE")]
+    media_manifest: Option<String>,
+
+    /// Скопировать файлы медиа в DIR/<ГГГГ-ММ>/<автор>/ с датой в имени
+    #[arg(long = "extract-media", value_name = "DIR")]
+    extract_media: Option<PathBuf>,
+
+    /// Статическая HTML-версия переписки с фото, голосовыми и видео:
+    /// по странице на месяц в папке DIR (медиа — из папки экспорта)
+    #[arg(long = "html-chat", value_name = "DIR")]
+    html_chat: Option<PathBuf>,
+
+    /// Записать все ссылки из сообщений (без повторов, с автором и датой)
+    #[arg(long = "links", value_name = "FILE")]
+    links: Option<String>,
+
+    /// Потоковый разбор: сообщения читаются по одному, память не растёт
+    /// с размером экспорта (включается сам для файлов от 512 МиБ)
+    #[arg(long = "streaming")]
+    streaming: bool,
+
+    /// Для экспорта всего аккаунта: обработать только чат с этим именем
+    /// или id (по умолчанию — все чаты, каждый в свой chat_<имя>.txt)
+    #[arg(long = "chat")]
+    chat: Option<String>,
+
+    /// Учитывать сообщения не раньше этого момента
+    /// (ГГГГ-ММ-ДД или ГГГГ-ММ-ДДTЧЧ:ММ[:СС])
+    #[arg(long = "since", value_parser = filter::parse_since)]
+    since: Option<NaiveDateTime>,
+
+    /// Учитывать сообщения не позже этого момента (дата включается целиком)
+    #[arg(long = "until", value_parser = filter::parse_until)]
+    until: Option<NaiveDateTime>,
+
+    /// Оставить только этого автора (имя или from_id, можно несколько раз)
+    #[arg(long = "only-author", value_name = "AUTHOR")]
+    only_author: Vec<String>,
+
+    /// Исключить автора (имя или from_id, можно несколько раз)
+    #[arg(long = "exclude-author", value_name = "AUTHOR")]
+    exclude_author: Vec<String>,
+
+    /// Не учитывать ботов: авторов с именем на "bot" и перечисленных в --bot
+    #[arg(long = "no-bots")]
+    no_bots: bool,
+
+    /// Считать автора ботом (имя или from_id, можно несколько раз; включает --no-bots)
+    #[arg(long = "bot", value_name = "AUTHOR")]
+    bot: Vec<String>,
+
+    /// Оставить только сообщения, в тексте которых есть PATTERN
+    /// (подстрока без учёта регистра; совпадения считаются по авторам)
+    #[arg(long = "grep", value_name = "PATTERN")]
+    grep: Option<String>,
+
+    /// Считать PATTERN из --grep регулярным выражением
+    #[arg(long = "regex")]
+    regex: bool,
+
+    /// Считать совпадения со словарём по авторам (слова, корень*, /regex/;
+    /// можно несколько раз — каждый файл отдельным разделом)
+    #[arg(long = "wordlist", value_name = "FILE")]
+    wordlist: Vec<PathBuf>,
+
+    /// Считать совпадения с регулярным выражением по авторам под именем NAME
+    /// (можно несколько раз; в файле настроек — раздел [counters])
+    #[arg(long = "counter", value_name = "NAME=REGEX", value_parser = counters::parse_counter)]
+    counter: Vec<(String, String)>,
+
+    /// Тональность сообщений по словарю: среднее по авторам и по месяцам
+    #[arg(long = "sentiment")]
+    sentiment: bool,
+
+    /// Свой словарь тональности вместо встроенного (строки "слово оценка")
+    #[arg(long = "lexicon", value_name = "FILE", requires = "sentiment")]
+    lexicon: Option<PathBuf>,
+
+    /// Свой подсчёт: исполняемый скрипт получает на stdin сообщения по JSON
+    /// в строке, его stdout — раздел в конце статистики (можно несколько раз)
+    #[arg(long = "script", value_name = "FILE")]
+    script: Vec<PathBuf>,
+
+    /// Свой фильтр: скрипт получает сообщения так же, как --script, и на
+    /// каждое отвечает строкой "1" (оставить) или "0" (отбросить). Встроенного
+    /// rhai/Lua нет — фильтры и счётчики пишутся внешними программами
+    #[arg(long = "filter-script", value_name = "FILE")]
+    filter_script: Option<PathBuf>,
+
+    /// Доля пересылок среди сообщений автора, с которой он попадает в спамеры
+    #[arg(
+        long = "forward-spam-ratio",
+        value_name = "RATIO",
+        default_value_t = forwards::DEFAULT_SPAM_RATIO,
+        value_parser = parse_ratio
+    )]
+    forward_spam_ratio: f64,
+
+    /// Спам: тексты короче N символов не считаются
+    #[arg(long = "spam-min-chars", value_name = "N", default_value_t = 5)]
+    spam_min_chars: usize,
+
+    /// Спам: текст считается повтором, если отправлен не меньше N раз
+    #[arg(
+        long = "spam-min-repeats",
+        value_name = "N",
+        default_value_t = 2,
+        value_parser = parse_min_repeats
+    )]
+    spam_min_repeats: usize,
+
+    /// Спам: сколько авторов показывать
+    #[arg(long = "spam-top", value_name = "N", default_value_t = TOP_SPAMMERS)]
+    spam_top: usize,
+
+    /// Спам: печатать сами повторяющиеся тексты каждого автора
+    #[arg(long = "spam-details")]
+    spam_details: bool,
+
+    /// Заменить имена и id участников псевдонимами User1, User2, ... (и в логе,
+    /// и в статистике), а названия чатов — на Chat1, Chat2, ...
+    /// Фильтры по авторам тогда принимают псевдонимы
+    #[arg(long = "anonymize")]
+    anonymize: bool,
+
+    /// Маскировать в логе личные данные: phones, emails, links (через запятую)
+    #[arg(long = "redact", value_name = "KINDS", value_delimiter = ',')]
+    redact: Vec<redact::RedactKind>,
+
+    /// Считать авторов по имени как есть, а не по from_id с последним именем
+    #[arg(long = "by-name")]
+    by_name: bool,
+
+    /// Разбить лог на файлы по месяцам, годам (chat_2023-01.txt, ...) или авторам
+    #[arg(long = "split-by", value_name = "PERIOD")]
+    split_by: Option<log_out::SplitBy>,
+
+    /// Продолжать лог в chat.part2.txt, ... когда файл дорос до размера
+    /// (50M, 512K, 2G; без суффикса — байты)
+    #[arg(long = "max-output-size", value_name = "SIZE", value_parser = parse_size)]
+    max_output_size: Option<u64>,
+
+    /// Дописывать в существующий лог только сообщения новее прошлого запуска
+    /// (id последнего хранится в <лог>.state)
+    #[arg(long = "append")]
+    append: bool,
+
+    /// Сжимать лог gzip-ом (к имени добавится .gz); -o *.gz сжимается и так
+    #[arg(long = "compress")]
+    compress: bool,
+
+    /// Хранить посчитанную статистику в .tgjsps-cache рядом с входом: пока вход
+    /// и настройки подсчёта те же, экспорт не разбирается заново (и лог не
+    /// пересоздаётся) — удобно менять --stat-format или строить отчёт
+    #[arg(long = "cache")]
+    cache: bool,
+
+    /// Считать слова, спам, длины и стиль для -v в N потоках (0 — по числу
+    /// ядер); лог пишется по порядку. С --streaming — в один поток
+    #[arg(long = "threads", value_name = "N", default_value_t = 1)]
+    threads: usize,
+
+    /// Приблизительный топ слов и спамеров в ограниченной памяти: в каждом
+    /// словаре слов и текстов держать не больше 2×N счётчиков (по умолчанию
+    /// N = 10000), при переполнении оставлять N самых больших. Редкие слова
+    /// выпадают, а числа — нижние оценки без оценки погрешности: отсеянное
+    /// слово считается заново с 1. Богатство словаря тоже приблизительное
+    #[arg(
+        long = "approx",
+        value_name = "N",
+        num_args = 0..=1,
+        default_missing_value = "10000",
+        value_parser = parse_approx
+    )]
+    approx: Option<usize>,
+
+    /// Показать время по этапам: чтение, разбор JSON, обработка сообщений,
+    /// сводка статистики, запись выходов — и сообщений в секунду
+    #[arg(long = "timings")]
+    timings: bool,
+
+    /// Следить за входом (файлом или папкой экспорта) и словарями и
+    /// обрабатывать заново при каждом изменении
+    #[arg(long = "watch")]
+    watch: bool,
+
+    /// Файл настроек (по умолчанию — tgjsps.toml в текущей папке, если есть):
+    /// ключи — имена флагов, флаги из командной строки важнее
+    #[arg(long = "config", value_name = "FILE")]
+    config: Option<PathBuf>,
+
+    /// Профиль из файла настроек: раздел [profile.<NAME>] поверх общих ключей
+    #[arg(long = "profile", value_name = "NAME")]
+    profile: Option<String>,
+
+    // выставляются подкомандами: convert не выводит статистику,
+    // stats и report не пишут лог, search -C печатает соседей
+    #[arg(skip)]
+    no_stats: bool,
+    #[arg(skip)]
+    no_log: bool,
+    #[arg(skip)]
+    context: Option<usize>,
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum Command {
+    /// Только лог чата, без статистики
+    Convert(Cli),
+    /// Только статистика, лог не пишется
+    Stats(Cli),
+    /// Сообщения с PATTERN — в stdout (или в -o), статистика с числом
+    /// совпадений по авторам — в stderr
+    Search(SearchArgs),
+    /// Только HTML-отчёт: ни лога, ни статистики в консоли
+    Report(ReportArgs),
+    /// Склеить несколько экспортов в один JSON без повторов по id сообщения
+    Merge(merge::MergeArgs),
+    /// Сравнить два экспорта: новые, изменённые и удалённые сообщения
+    Diff(diff::DiffArgs),
+    /// Листать переписку в терминале: фильтры по автору и датам, поиск,
+    /// статистика по отобранному
+    Tui(tui::TuiArgs),
+}
+
+#[derive(clap::Args, Debug)]
+struct SearchArgs {
+    /// Подстрока без учёта регистра (с --regex — регулярное выражение)
+    pattern: String,
+
+    /// Печатать и N сообщений до и после каждого найденного
+    #[arg(short = 'C', long = "context", value_name = "N")]
+    context: Option<usize>,
+
+    #[command(flatten)]
+    cli: Cli,
+}
+
+#[derive(clap::Args, Debug)]
+struct ReportArgs {
+    /// Куда записать отчёт
+    #[arg(value_name = "FILE")]
+    file: String,
+
+    #[command(flatten)]
+    cli: Cli,
+}
+
+fn parse_date_format(s: &str) -> Result<String, String> {
+    chrono::format::StrftimeItems::new(s)
+        .parse()
+        .map(|_| s.to_string())
+        .map_err(|_| format!("некорректный формат даты «{s}»"))
+}
+
+fn parse_ratio(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(r) if (0.0..=1.0).contains(&r) => Ok(r),
+        _ => Err(format!("доля должна быть числом от 0 до 1, а не «{s}»")),
+    }
+}
+
+fn parse_size(s: &str) -> Result<u64, String> {
+    let t = s.trim();
+    let (num, mult) = match t.char_indices().last() {
+        Some((i, 'K' | 'k')) => (&t[..i], 1u64 << 10),
+        Some((i, 'M' | 'm')) => (&t[..i], 1 << 20),
+        Some((i, 'G' | 'g')) => (&t[..i], 1 << 30),
+        _ => (t, 1),
+    };
+    match num.parse::<u64>().ok().and_then(|n| n.checked_mul(mult)) {
+        Some(size) if size > 0 => Ok(size),
+        _ => Err(format!("размер — число с суффиксом K, M или G (50M), а не «{s}»")),
+    }
+}
+
+fn parse_min_repeats(s: &str) -> Result<usize, String> {
+    match s.parse::<usize>() {
+        Ok(n) if n >= 2 => Ok(n),
+        _ => Err(format!("повтор — это хотя бы 2 одинаковых сообщения, а не «{s}»")),
+    }
+}
+
+fn parse_approx(s: &str) -> Result<usize, String> {
+    match s.parse::<usize>() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(format!("нужно число счётчиков больше нуля, а не «{s}»")),
+    }
+}
+
+//
+// ===================== MAIN =====================
+//
+
+pub fn main() {
+    let matches = match with_config(std::env::args().collect()) {
+        Ok(matches) => matches,
+        Err(e) => {
+            logger::init(false, 0);
+            error!("Фатальная ошибка: {e}");
+            exit(1);
+        }
+    };
+    let app = App::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    let mut cli = match app.command {
+        None => app.cli,
+        Some(Command::Convert(cli)) => Cli { no_stats: true, ..cli },
+        Some(Command::Stats(cli)) => Cli { no_log: true, ..cli },
+        Some(Command::Search(SearchArgs { pattern, context, cli })) => {
+            // без -o найденное печатается, а не пишется в chat.txt
+            let search = matches.subcommand_matches("search");
+            let explicit = search.and_then(|m| m.value_source("output"))
+                == Some(ValueSource::CommandLine);
+            let output = if explicit { cli.output } else { STDIO.to_string() };
+            // у найденного видно, когда это было (если нет своего --format)
+            let with_date = cli.with_date || cli.format.is_none();
+            Cli { grep: Some(pattern), output, with_date, context, ..cli }
+        }
+        Some(Command::Report(ReportArgs { file, cli })) => {
+            Cli { report: Some(file), no_log: true, no_stats: true, ..cli }
+        }
+        Some(Command::Merge(args)) => run_tool(|| merge::run(&args)),
+        Some(Command::Diff(args)) => run_tool(|| diff::run(&args)),
+        Some(Command::Tui(args)) => run_tool(|| tui::run(&args)),
+    };
+    logger::init(cli.quiet, cli.verbose);
+    if cli.regex && cli.grep.is_none() {
+        error!("Фатальная ошибка: --regex относится к --grep, а он не задан");
+        exit(1);
+    }
+    if cli.watch {
+        watch_loop(cli);
+    }
+    if let Err(e) = resolve_inputs(&mut cli).map_err(|e| e.into()).and_then(|_| process(&cli)) {
+        error!("Фатальная ошибка: {e}");
+        exit(1);
+    }
+}
+
+/// Разобрать командную строку, дополнив её флагами из файла настроек.
+/// Флаги файла встают сразу после подкоманды (у convert, stats, ... свои
+/// флаги), а заданные в командной строке из файла не берутся.
+fn with_config(mut argv: Vec<String>) -> Result<clap::ArgMatches, String> {
+    let matches = App::command().get_matches_from(&argv);
+    let (cmd, given, at) = match matches.subcommand() {
+        None => (App::command(), &matches, 1),
+        Some((name @ ("convert" | "stats" | "search" | "report"), sub)) => {
+            let cmd = App::command().find_subcommand(name).cloned().expect("подкоманда есть");
+            (cmd, sub, 2)
+        }
+        Some(_) => return Ok(matches),
+    };
+    let profile = given.get_one::<String>("profile");
+    let path = match given.get_one::<PathBuf>("config") {
+        Some(path) => path.clone(),
+        None if std::path::Path::new(config::DEFAULT_CONFIG).is_file() => {
+            PathBuf::from(config::DEFAULT_CONFIG)
+        }
+        None if profile.is_some() => {
+            return Err(format!("--profile: нет файла настроек {}", config::DEFAULT_CONFIG));
+        }
+        None => return Ok(matches),
+    };
+    let extra = config::Config::load(&path)?.args(profile.map(String::as_str), &cmd, given)?;
+    if extra.is_empty() {
+        return Ok(matches);
+    }
+    argv.splice(at..at, extra);
+    Ok(App::command().get_matches_from(argv))
+}
+
+/// merge и diff: свои аргументы, без общего прохода по экспорту.
+fn run_tool(f: impl FnOnce() -> Result<(), Box<dyn std::error::Error>>) -> ! {
+    logger::init(false, 0);
+    if let Err(e) = f() {
+        error!("Фатальная ошибка: {e}");
+        exit(1);
+    }
+    exit(0);
+}
+
+/// --watch: обработка заново при каждом изменении входа; ошибки не
+/// прерывают наблюдение — экспорт мог быть дописан не до конца.
+fn watch_loop(mut cli: Cli) -> ! {
+    if cli.input.iter().any(|p| p == STDIO) {
+        error!("Фатальная ошибка: --watch следит за файлами — со stdin он не работает");
+        exit(1);
+    }
+    let patterns = cli.input.clone();
+    let media_dir = cli.media_dir.clone();
+    loop {
+        let seen = watch::stamp(&watched_files(&patterns, &cli));
+        cli.input = patterns.clone();
+        cli.media_dir = media_dir.clone();
+        let done = resolve_inputs(&mut cli).map_err(|e| e.into()).and_then(|_| process(&cli));
+        if let Err(e) = done {
+            error!("Ошибка: {e}");
+        }
+        info!("Жду изменений во входе (Ctrl+C — выход)...");
+        watch::wait_for_change(|| watch::stamp(&watched_files(&patterns, &cli)), &seen);
+    }
+}
+
+/// Файлы, за которыми следит --watch: входы (шаблоны и папки экспорта
+/// раскрываются каждый раз — появится новый экспорт) и словари.
+fn watched_files(patterns: &[String], cli: &Cli) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for pattern in patterns {
+        for input in expand_glob(pattern).unwrap_or_else(|_| vec![pattern.clone()]) {
+            match discover_export(&input) {
+                Ok(Some(dir)) => files.push(dir.join("result.json")),
+                _ => files.push(PathBuf::from(input)),
+            }
+        }
+    }
+    let dicts = cli.stopwords.iter().chain(&cli.lexicon).chain(&cli.wordlist);
+    files.extend(dicts.chain(&cli.script).chain(&cli.filter_script).cloned());
+    files
+}
+
+/// Один проход: разбор (или кэш), статистика и сообщения о записанных файлах.
+fn process(cli: &Cli) -> Result<(), Box<dyn std::error::Error>> {
+    let start = Instant::now();
+
+    let (stats, outputs, mut timings) = run_cached(cli)?;
+
+    // служебные строки — в stderr (logger.rs); с -o - в stdout идёт лог,
+    // и статистика тоже уходит в stderr
+    let log_to_stdout = cli.output == STDIO && !cli.no_log;
+
+    let stats_start = Instant::now();
+    if cli.no_stats {
+        // tgjsps convert, report
+    } else if cli.stat_txt {
+        match write_stats_to_files(&stats, cli.stat_format, cli.verbose > 0) {
+            Ok(paths) => info!("Статистика записана в {}", paths.join(", ")),
+            Err(e) => error!("Ошибка записи статистики: {e}"),
+        }
+    } else {
+        let (mut handle, tty): (BufWriter<Box<dyn Write>>, bool) = if log_to_stdout {
+            (BufWriter::new(Box::new(io::stderr().lock())), io::stderr().is_terminal())
+        } else {
+            (BufWriter::new(Box::new(io::stdout().lock())), io::stdout().is_terminal())
+        };
+        let term = Term::new(tty, cli.no_color);
+        let verbose = cli.verbose > 0;
+        if let Err(e) = write_stats_as(&mut handle, &stats, cli.stat_format, verbose, term)
+            .and_then(|_| handle.flush())
+        {
+            error!("Ошибка вывода статистики: {e}");
+        }
+    }
+    timings.aggregate += stats_start.elapsed();
+    let outputs_start = Instant::now();
+
+    if let Some(path) = &cli.report {
+        match report::write_report(path, &stats) {
+            Ok(()) => info!("HTML-отчёт записан в {path}"),
+            Err(e) => error!("Ошибка записи отчёта {path}: {e}"),
+        }
+    }
+
+    if let Some(dir) = &cli.charts {
+        match charts::write_charts(dir, &stats) {
+            Ok(n) => info!("Графики записаны в {}: {n} SVG", dir.display()),
+            Err(e) => error!("Ошибка записи графиков в {}: {e}", dir.display()),
+        }
+    }
+
+    if let Some(path) = &cli.graph {
+        match graph::write_graph(path, &stats, true, cli.graph_mentions) {
+            Ok((nodes, edges)) => {
+                info!("Граф ответов записан в {path}: вершин {nodes}, рёбер {edges}")
+            }
+            Err(e) => error!("Ошибка записи графа {path}: {e}"),
+        }
+    }
+
+    if let Some(path) = &cli.mention_graph {
+        match graph::write_graph(path, &stats, false, true) {
+            Ok((nodes, edges)) => info!(
+                "Граф упоминаний записан в {path}: вершин {nodes}, рёбер {edges}"
+            ),
+            Err(e) => error!("Ошибка записи графа {path}: {e}"),
+        }
+    }
+
+    if let Some(path) = &cli.media_manifest
+        && let Some((files, missing)) = stats.manifest_counts
+    {
+        info!(
+            "Манифест медиа записан в {path}: файлов {files}, нет в экспорте {missing}"
+        );
+    }
+
+    if let Some(dir) = &cli.extract_media
+        && let Some((copied, skipped)) = stats.extract_counts
+    {
+        info!(
+            "Медиа скопированы в {}: файлов {copied}, нет в экспорте {skipped}",
+            dir.display()
+        );
+    }
+
+    if let Some(dir) = &cli.html_chat
+        && let Some(pages) = stats.html_pages
+    {
+        info!(
+            "HTML-версия переписки записана в {}/index.html ({pages} стр.)",
+            dir.display()
+        );
+    }
+
+    if let Some(path) = &cli.links {
+        info!("Ссылки записаны в {path}");
+    }
+
+    if let Some(path) = &cli.pins {
+        match pins::write_pins_file(path, &stats.pins) {
+            Ok(()) => info!("Закреплённые сообщения записаны в {path}"),
+            Err(e) => error!("Ошибка записи {path}: {e}"),
+        }
+    }
+
+    timings.output += outputs_start.elapsed();
+
+    if let Some(appended) = stats.appended {
+        info!("Дописано в лог новых сообщений: {appended}");
+    }
+
+    if let Some((inputs, duplicates)) = stats.merged {
+        info!("Склеено экспортов: {inputs}, повторов по id отброшено: {duplicates}");
+    }
+
+    if cli.no_log {
+        // tgjsps stats, report
+    } else if let Some(files) = stats.split_files
+        && let Some(split) = cli.split_by
+    {
+        let (ext, part) = (cli.output_format.extension(), split.label());
+        info!("Лог разбит на {files} файлов: <имя>_<{part}>.{ext}");
+    } else if let Some(files) = stats.split_files {
+        let ext = cli.output_format.extension();
+        info!("Лог разбит на {files} файлов: <имя>.partN.{ext}");
+    } else if log_to_stdout {
+        info!("История чата выведена в stdout");
+    } else if outputs.is_empty() {
+        let path = cache_path(cli);
+        info!("Статистика взята из {}, лог не пересоздавался", path.display());
+    } else if outputs.len() == 1 {
+        info!("История чата записана в {}", outputs[0]);
+    } else {
+        let ext = cli.output_format.extension();
+        info!("Истории {} чатов записаны в chat_<имя>.{ext}", outputs.len());
+    }
+
+    let dur = start.elapsed();
+    if cli.timings {
+        info!("{}", timings.report(dur));
+    }
+    info!(
+        "Время обработки: {} нс (~{} мс)",
+        dur.as_nanos(),
+        dur.as_millis()
+    );
+    Ok(())
+}
+
+/// Раскрывает шаблоны в -i и находит result.json в папках экспорта.
+fn resolve_inputs(cli: &mut Cli) -> Result<(), String> {
+    let mut inputs = Vec::new();
+    for pattern in &cli.input {
+        inputs.extend(expand_glob(pattern)?);
+    }
+    if inputs.len() > 1 && inputs.iter().any(|p| p == STDIO) {
+        return Err("stdin (-i -) нельзя склеивать с другими входами".into());
+    }
+    for input in &mut inputs {
+        if let Some(dir) = discover_export(input)? {
+            *input = dir.join("result.json").to_string_lossy().into_owned();
+            // медиа — из первой папки экспорта
+            cli.media_dir.get_or_insert(dir);
+        }
+    }
+    cli.input = inputs;
+    Ok(())
+}
+
+/// Папка экспорта: --media-dir или та, где лежит входной JSON.
+fn export_dir(cli: &Cli) -> PathBuf {
+    match &cli.media_dir {
+        Some(dir) => dir.clone(),
+        None => std::path::Path::new(&cli.input[0])
+            .parent()
+            .map(PathBuf::from)
+            .unwrap_or_default(),
+    }
+}
+
+/// Отчёту и графикам нужны часы и слова, графу — ответы: считаем их, даже
+/// если -v не задан.
+fn collects_verbose(cli: &Cli) -> bool {
+    cli.verbose > 0
+        || cli.report.is_some()
+        || cli.charts.is_some()
+        || cli.graph.is_some()
+        || cli.mention_graph.is_some()
+}
+
+/// .tgjsps-cache в папке первого входа.
+fn cache_path(cli: &Cli) -> PathBuf {
+    let dir = std::path::Path::new(&cli.input[0]).parent().unwrap_or(std::path::Path::new(""));
+    dir.join(cache::CACHE_FILE)
+}
+
+/// Ключ кэша: содержимое входов, словарей и --filter-script плюс всё, что
+/// меняет подсчёт.
+/// Формат вывода, лог и пути выходных файлов на статистику не влияют.
+fn cache_key(cli: &Cli) -> io::Result<u64> {
+    let mut h = cache::KeyHasher::new();
+    for path in &cli.input {
+        h.write_file(path)?;
+    }
+    // --media-dir: размеры файлов берутся с диска, а папка меняется сама по себе
+    if let Some(dir) = &cli.media_dir {
+        h.write_media_dir(dir)?;
+    }
+    write_settings_key(&mut h, cli)?;
+    Ok(h.finish())
+}
+
+/// Настройки подсчёта без самого входа: для --append, где вход каждый раз новый.
+fn settings_key(cli: &Cli) -> io::Result<u64> {
+    let mut h = cache::KeyHasher::new();
+    write_settings_key(&mut h, cli)?;
+    Ok(h.finish())
+}
+
+fn write_settings_key(h: &mut cache::KeyHasher, cli: &Cli) -> io::Result<()> {
+    let dicts = cli.stopwords.iter().chain(&cli.lexicon).chain(&cli.wordlist);
+    for path in dicts.chain(&cli.filter_script).filter_map(|p| p.to_str()) {
+        h.write_file(path)?;
+    }
+    let filters = (
+        &cli.chat,
+        cli.since,
+        cli.until,
+        &cli.only_author,
+        &cli.exclude_author,
+        cli.no_bots,
+        &cli.bot,
+        &cli.grep,
+        cli.regex,
+    );
+    let spam = (cli.spam_min_chars, cli.spam_min_repeats, cli.spam_top, cli.spam_details);
+    let top = cli.top_words;
+    let counting = (
+        collects_verbose(cli),
+        cli.pins.is_some(),
+        cli.timezone,
+        cli.no_stopwords,
+        cli.stem,
+        cli.approx,
+        &cli.media_dir,
+        &cli.wordlist,
+        cli.sentiment,
+        cli.forward_spam_ratio,
+        cli.anonymize,
+        cli.by_name,
+    );
+    let counters = &cli.counter;
+    h.write(format!("{filters:?} {spam:?} {top:?} {counting:?} {counters:?}").as_bytes());
+    Ok(())
+}
+
+/// --cache: статистика из кэша, если вход и настройки подсчёта не менялись;
+/// иначе обычный разбор, и результат кладётся в кэш. Пустой список логов
+/// означает, что экспорт не разбирался.
+fn run_cached(cli: &Cli) -> Result<Run, Box<dyn std::error::Error>> {
+    if !cli.cache {
+        return run(cli);
+    }
+    if cli.input.iter().any(|p| p == STDIO) {
+        return Err("--cache сверяет вход по содержимому файла — со stdin он не работает".into());
+    }
+    let path = cache_path(cli);
+    let key = cache_key(cli)?;
+    // выгрузкам по сообщениям, скриптам и логу в stdout нужен сам экспорт
+    let needs_messages = (cli.output == STDIO && !cli.no_log)
+        || !cli.script.is_empty()
+        || cli.append
+        || cli.sqlite.is_some()
+        || cli.arrow.is_some()
+        || cli.links.is_some()
+        || cli.media_manifest.is_some()
+        || cli.extract_media.is_some()
+        || cli.html_chat.is_some();
+    if !needs_messages {
+        let mut timings = Timings::default();
+        match timed(&mut timings.read, || cache::load::<Stats>(&path, key)) {
+            Ok(Some(mut stats)) => {
+                // это было про файлы прошлого запуска
+                stats.split_files = None;
+                stats.appended = None;
+                return Ok((stats, Vec::new(), timings));
+            }
+            Ok(None) => {}
+            Err(e) => warn!("Кэш {} не прочитан ({e}), считаю заново", path.display()),
+        }
+    }
+
+    let (stats, outputs, mut timings) = run(cli)?;
+    if let Err(e) = timed(&mut timings.output, || cache::save(&path, key, &stats)) {
+        warn!("Не удалось записать кэш {}: {e}", path.display());
+    }
+    Ok((stats, outputs, timings))
+}
+
+/// Статистика, записанные логи и время по этапам.
+type Run = (Stats, Vec<String>, Timings);
+
+/// Настройки подсчёта из флагов; выходы CLI добавляет сам.
+fn options(cli: &Cli) -> Options {
+    Options {
+        verbose: collects_verbose(cli),
+        top_words: cli.top_words,
+        approx: cli.approx,
+        spam: SpamConfig {
+            min_chars: cli.spam_min_chars,
+            min_repeats: cli.spam_min_repeats,
+            top: cli.spam_top,
+            details: cli.spam_details,
+        },
+        stopwords: !cli.no_stopwords,
+        stopwords_file: cli.stopwords.clone(),
+        stem: cli.stem,
+        timezone: cli.timezone,
+        since: cli.since,
+        until: cli.until,
+        only_authors: cli.only_author.clone(),
+        exclude_authors: cli.exclude_author.clone(),
+        no_bots: cli.no_bots,
+        bots: cli.bot.clone(),
+        grep: cli.grep.clone(),
+        regex: cli.regex,
+        wordlists: cli.wordlist.clone(),
+        counters: cli.counter.clone(),
+        sentiment: cli.sentiment,
+        lexicon: cli.lexicon.clone(),
+        forward_spam_ratio: cli.forward_spam_ratio,
+        anonymize: cli.anonymize,
+        by_name: cli.by_name,
+        pins: cli.verbose > 0 || cli.report.is_some() || cli.pins.is_some(),
+        threads: cli.threads,
+        chat: cli.chat.clone(),
+        media_dir: cli.media_dir.clone(),
+    }
+}
+
+fn run(cli: &Cli) -> Result<Run, Box<dyn std::error::Error>> {
+    let counting = Processor::new(&options(cli))?;
+    if cli.output == STDIO
+        && !cli.no_log
+        && (cli.split_by.is_some() || cli.max_output_size.is_some() || cli.compress || cli.append)
+    {
+        return Err("С -o - лог идёт в stdout: --split-by, --max-output-size, --compress и \
+                    --append недоступны (сжать можно через | gzip)"
+            .into());
+    }
+    if cli.append
+        && !cli.no_log
+        && (cli.sqlite.is_some()
+            || cli.arrow.is_some()
+            || cli.links.is_some()
+            || cli.media_manifest.is_some()
+            || cli.extract_media.is_some()
+            || cli.html_chat.is_some()
+            || !cli.script.is_empty())
+    {
+        return Err("--append разбирает только новые сообщения: --sqlite, --arrow, --links, \
+                    --media-manifest, --extract-media, --html-chat и --script выгружают весь \
+                    экспорт — запустите их без --append"
+            .into());
+    }
+    if cli.context.is_some() && cli.output_format != OutputFormat::Text {
+        return Err("-C печатает соседние сообщения строками лога — только с текстовым \
+                    --output-format"
+            .into());
+    }
+    if cli.output.ends_with(".zst") {
+        return Err("Сжатие zstd не поддерживается — используйте .gz или --compress".into());
+    }
+    let template = match &cli.format {
+        Some(t) => Some(log_template::parse_template(t)?),
+        None => None,
+    };
+
+    let mut proc = Processor {
+        write_log: !cli.no_log,
+        split_by: cli.split_by,
+        max_output_size: cli.max_output_size,
+        compress: cli.compress,
+        append: (cli.append && !cli.no_log).then(Vec::new),
+        output_path: cli.output.clone(),
+        sqlite: match &cli.sqlite {
+            Some(path) => Some(sqlite::SqliteSink::create(path)?),
+            None => None,
+        },
+        arrow: match &cli.arrow {
+            Some(path) => Some(arrow_out::ArrowSink::create(path)?),
+            None => None,
+        },
+        links_out: match &cli.links {
+            Some(path) => Some(links::LinkDump::create(path)?),
+            None => None,
+        },
+        manifest: match &cli.media_manifest {
+            Some(path) => Some(manifest::MediaManifest::create(path, export_dir(cli))?),
+            None => None,
+        },
+        extractor: match &cli.extract_media {
+            Some(dir) => Some(extract::MediaExtractor::new(export_dir(cli), dir)?),
+            None => None,
+        },
+        html_chat: match &cli.html_chat {
+            Some(dir) => Some(html_chat::HtmlChat::create(dir, &export_dir(cli))?),
+            None => None,
+        },
+        context: cli.context.map(SearchContext::new),
+        output_format: cli.output_format,
+        log_style: LogStyle {
+            with_date: cli.with_date,
+            date_format: cli.date_format.clone(),
+            service: cli.service && cli.output_format == OutputFormat::Text,
+            replies: (cli.output_format == OutputFormat::Text
+                && (cli.replies
+                    || cli.reply_quote.is_some()
+                    || template
+                        .as_deref()
+                        .is_some_and(|t| log_template::uses_field(t, Field::Reply))))
+            .then(|| MessageIndex::new(cli.reply_quote.unwrap_or(0))),
+            redact: (!cli.redact.is_empty()).then(|| redact::Redactor::new(&cli.redact)),
+            template,
+        },
+        ..counting
+    };
+    // --append: счёт продолжается с того, что насчитано в прошлые разы
+    let saved_stats = match proc.append {
+        Some(_) => Some((append::stats_path(&cli.output), settings_key(cli)?)),
+        None => None,
+    };
+    if let Some((path, key)) = &saved_stats
+        && let Some(stats) = append::load_stats(path, *key)?
+    {
+        proc.stats = stats;
+        proc.append_resumed = true;
+    }
+    if let Some(path) = &cli.filter_script {
+        proc.filter_script = Some(script::ScriptFilter::spawn(path, cli.timezone)?);
+    }
+    for path in &cli.script {
+        let script = script::ScriptMetric::spawn(path, cli.timezone)?;
+        proc.stats.metrics.0.push(Box::new(script));
+    }
+
+    let start = Instant::now();
+    if let [input] = cli.input.as_slice() {
+        run_input(input, cli.streaming, &mut proc)?;
+    } else {
+        // склейка сортирует сообщения — без DOM всех входов не обойтись
+        if cli.streaming {
+            return Err("--streaming читает один вход: несколько -i склеиваются в памяти".into());
+        }
+        // строки склеенного DOM заимствованы из буферов: они живут до конца
+        let t = &mut proc.timings;
+        let mut bufs: Vec<Vec<u8>> = timed(&mut t.read, || {
+            cli.input.iter().map(|i| read_input(i)).collect::<Result<_, _>>()
+        })?;
+        let mut merger = merge::Merger::default();
+        for (input, buf) in cli.input.iter().zip(bufs.iter_mut()) {
+            let root = timed(&mut t.parse, || simd_json::to_borrowed_value(buf))
+                .map_err(|e| format!("Ошибка парсинга JSON в {input}: {e}"))?;
+            merger.add(root)?;
+        }
+        proc.stats.merged = Some((merger.inputs, merger.duplicates));
+        run_dom_root(merger.finish(), &mut proc)?;
+    }
+
+    proc.finish()?;
+    let elapsed = start.elapsed();
+
+    // обработка — всё, что не ушло на чтение, разбор, сводку и запись
+    let t = &mut proc.timings;
+    t.process = elapsed.saturating_sub(t.read + t.parse + t.read_parse + t.aggregate + t.output);
+    let outputs_start = Instant::now();
+
+    if let Some(db) = proc.sqlite.take() {
+        db.finish(&proc.stats, proc.verbose)?;
+    }
+    if let Some(arrow) = proc.arrow.take() {
+        arrow.finish()?;
+    }
+    if let Some(dump) = proc.links_out.take() {
+        dump.finish()?;
+    }
+    if let Some(manifest) = proc.manifest.take() {
+        proc.stats.manifest_counts = Some(manifest.finish()?);
+    }
+    if let Some(ex) = proc.extractor.take() {
+        proc.stats.extract_counts = Some((ex.copied, ex.skipped));
+    }
+    if let Some(html) = proc.html_chat.take() {
+        proc.stats.html_pages = Some(html.finish()?);
+    }
+    if proc.split_by.is_some() || proc.split_files.len() > proc.outputs.len() {
+        proc.stats.split_files = Some(proc.split_files.len());
+    }
+    // состояние — только после того, как всё записано
+    if let Some(states) = proc.append.take() {
+        for state in &states {
+            state.save()?;
+        }
+        if let Some((path, key)) = &saved_stats {
+            append::save_stats(path, *key, &proc.stats)?;
+        }
+        proc.stats.appended = Some(states.iter().map(|s| s.appended).sum());
+    }
+    proc.timings.output += outputs_start.elapsed();
+
+    Ok((proc.stats, proc.outputs, proc.timings))
+}
@@ -0,0 +1,88 @@
+//
+// ===================== КОМАНДЫ БОТАМ =====================
+//
+// Команда — сущность bot_command, а если разметки нет — первое слово
+// сообщения, начинающегося с "/". "/start@SomeBot" считается как "/start",
+// регистр не важен.
+//
+
+use ahash::AHashMap;
+
+use std::io::{self, Write};
+
+use crate::model::{EntityKind, Message, Text};
+use crate::{Metric, add_counts, top_by_count};
+
+const TOP_COMMANDS: usize = 20;
+
+#[derive(Default)]
+pub struct CommandStats {
+    pub total: usize,
+    // команда -> сколько раз вызвана
+    pub by_command: AHashMap<String, usize>,
+    // кто -> сколько команд отправил
+    pub per_author: AHashMap<String, usize>,
+}
+
+crate::cache::codec!(CommandStats { total, by_command, per_author });
+
+fn normalize(cmd: &str) -> Option<String> {
+    let cmd = cmd.split('@').next().unwrap_or(cmd);
+    if cmd.len() < 2 || !cmd.starts_with('/') {
+        return None;
+    }
+    Some(cmd.to_lowercase())
+}
+
+impl CommandStats {
+    /// Сложить счётчики команд: всего, по командам и по авторам.
+    pub fn merge(&mut self, other: CommandStats) {
+        self.total += other.total;
+        add_counts(&mut self.by_command, other.by_command);
+        add_counts(&mut self.per_author, other.per_author);
+    }
+}
+
+impl Metric for CommandStats {
+    fn observe(&mut self, msg: &Message) {
+        let mut found: Vec<String> = msg
+            .entities()
+            .filter(|e| e.kind == EntityKind::BotCommand)
+            .filter_map(|e| normalize(e.text))
+            .collect();
+        if found.is_empty()
+            && let Text::Plain(text) = &msg.text
+            && let Some(first) = text.split_whitespace().next()
+            && first.starts_with('/')
+            // "/" с цифрами и т.п. — не команда
+            && first[1..].chars().next().is_some_and(|c| c.is_alphabetic())
+            && let Some(cmd) = normalize(first)
+        {
+            found.push(cmd);
+        }
+        if found.is_empty() {
+            return;
+        }
+        self.total += found.len();
+        *self.per_author.entry(msg.author().into_owned()).or_insert(0) += found.len();
+        for cmd in found {
+            *self.by_command.entry(cmd).or_insert(0) += 1;
+        }
+    }
+
+    fn report(&self, w: &mut dyn Write) -> io::Result<()> {
+        writeln!(w, "Команды ботам: {}", self.total)?;
+        if self.total == 0 {
+            return Ok(());
+        }
+        writeln!(w, "  популярные:")?;
+        for (cmd, count) in top_by_count(&self.by_command, TOP_COMMANDS) {
+            writeln!(w, "  - {}: {}", cmd, count)?;
+        }
+        writeln!(w, "  чаще всех вызывают ботов:")?;
+        for (name, count) in top_by_count(&self.per_author, TOP_COMMANDS) {
+            writeln!(w, "  - {}: {}", name, count)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,311 @@
+//
+// ===================== ФАЙЛ НАСТРОЕК (--config, --profile) =====================
+//
+// Настройки, которые надоело повторять в командной строке, лежат в
+// tgjsps.toml в текущей папке (или в файле из --config). Ключи — длинные
+// имена флагов:
+//
+//   stem = true
+//   top-words = 30
+//   timezone = "Europe/Moscow"
+//
+//   [profile.family]
+//   input = "exports/family/result.json"
+//   output = "family.txt"
+//   exclude-author = ["HelperBot"]
+//
+//   [profile.work]
+//   input = "exports/work"
+//   no-bots = true
+//   since = "2024-01-01"
+//
+// Ключи вне разделов действуют всегда, раздел профиля — только с
+// --profile <имя> и поверх них. Флаг из командной строки важнее файла.
+//
+// Раздел [counters] — именованные регулярные выражения для --counter:
+//
+//   [counters]
+//   memes = "(?i)kek|lol"
+//
+// Понимается подмножество TOML, которого хватает для флагов: строки в
+// двойных и одинарных кавычках, числа, true/false и массивы строк.
+//
+
+use ahash::AHashMap;
+use clap::ArgMatches;
+use clap::parser::ValueSource;
+
+use std::path::Path;
+
+/// Файл, который читается без --config, если он есть.
+pub const DEFAULT_CONFIG: &str = "tgjsps.toml";
+
+#[derive(Debug)]
+enum Value {
+    Bool(bool),
+    // строка или число — как есть, разберёт clap
+    One(String),
+    List(Vec<String>),
+}
+
+type Section = Vec<(String, Value)>;
+
+#[derive(Default)]
+pub struct Config {
+    name: String,
+    defaults: Section,
+    profiles: AHashMap<String, Section>,
+    // [counters]: имя -> регулярное выражение
+    counters: Vec<(String, String)>,
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Config, String> {
+        let name = path.display().to_string();
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Не удалось прочитать настройки {name}: {e}"))?;
+        parse(&text, &name)
+    }
+
+    /// Аргументы командной строки из файла: ключи вне разделов, затем
+    /// профиль. Флаги, заданные в `given`, пропускаются.
+    pub fn args(
+        &self,
+        profile: Option<&str>,
+        cmd: &clap::Command,
+        given: &ArgMatches,
+    ) -> Result<Vec<String>, String> {
+        let mut merged: Vec<(&str, &Value)> =
+            self.defaults.iter().map(|(k, v)| (k.as_str(), v)).collect();
+        if let Some(profile) = profile {
+            let Some(section) = self.profiles.get(profile) else {
+                let mut known: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+                known.sort_unstable();
+                return Err(format!(
+                    "В {} нет профиля «{profile}» (есть: {})",
+                    self.name,
+                    if known.is_empty() { "никаких".to_string() } else { known.join(", ") }
+                ));
+            };
+            for (key, value) in section {
+                match merged.iter_mut().find(|(k, _)| k == key) {
+                    Some(slot) => slot.1 = value,
+                    None => merged.push((key, value)),
+                }
+            }
+        }
+
+        let mut args = Vec::new();
+        for (key, value) in merged {
+            let arg = cmd
+                .get_arguments()
+                .find(|a| a.get_long() == Some(key) && !matches!(key, "config" | "profile"))
+                .ok_or_else(|| format!("{}: неизвестный параметр «{key}»", self.name))?;
+            if given.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine) {
+                continue;
+            }
+            let takes_value = arg.get_action().takes_values();
+            match (value, takes_value) {
+                (Value::Bool(true), false) => args.push(format!("--{key}")),
+                (Value::Bool(false), false) => {}
+                // -v: verbose = 2 — как -vv
+                (Value::One(n), false) if matches!(arg.get_action(), clap::ArgAction::Count) => {
+                    let n: usize = n.parse().map_err(|_| {
+                        format!("{}: «{key}» — число повторов флага, а не «{n}»", self.name)
+                    })?;
+                    args.extend(std::iter::repeat_n(format!("--{key}"), n));
+                }
+                (_, false) => {
+                    return Err(format!("{}: «{key}» — флаг, нужно true или false", self.name));
+                }
+                (Value::Bool(b), true) => args.push(format!("--{key}={b}")),
+                (Value::One(v), true) => args.push(format!("--{key}={v}")),
+                (Value::List(items), true) => {
+                    args.extend(items.iter().map(|v| format!("--{key}={v}")));
+                }
+            }
+        }
+        let counter = cmd.get_arguments().find(|a| a.get_long() == Some("counter"));
+        if let Some(arg) = counter
+            && given.value_source(arg.get_id().as_str()) != Some(ValueSource::CommandLine)
+        {
+            args.extend(self.counters.iter().map(|(name, re)| format!("--counter={name}={re}")));
+        }
+        Ok(args)
+    }
+}
+
+/// Куда идут ключи: в общие, в профиль или в [counters].
+enum Target {
+    Defaults,
+    Profile(String),
+    Counters,
+}
+
+fn parse(text: &str, name: &str) -> Result<Config, String> {
+    let mut config = Config { name: name.to_string(), ..Config::default() };
+    let mut current = Target::Defaults;
+    let mut lines = text.lines().enumerate();
+    while let Some((i, raw)) = lines.next() {
+        let err = |msg: String| format!("{name}:{}: {msg}", i + 1);
+        let line = strip_comment(raw).trim().to_string();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(header) = line.strip_prefix('[') {
+            let header = header.strip_suffix(']').ok_or_else(|| err("нет «]»".into()))?.trim();
+            if header == "counters" {
+                current = Target::Counters;
+                continue;
+            }
+            let profile = header
+                .strip_prefix("profile.")
+                .map(|p| unquote(p.trim()))
+                .filter(|p| !p.is_empty())
+                .ok_or_else(|| {
+                    err(format!("раздел [{header}] — ожидается [profile.<имя>] или [counters]"))
+                })?;
+            if config.profiles.contains_key(&profile) {
+                return Err(err(format!("профиль «{profile}» уже был")));
+            }
+            config.profiles.insert(profile.clone(), Section::new());
+            current = Target::Profile(profile);
+            continue;
+        }
+        let (key, rest) =
+            line.split_once('=').ok_or_else(|| err("ожидается ключ = значение".into()))?;
+        let key = unquote(key.trim());
+        let mut rest = rest.trim().to_string();
+        // массив может занимать несколько строк
+        if rest.starts_with('[') {
+            while !closes_array(&rest) {
+                let (_, more) = lines.next().ok_or_else(|| err("массив не закрыт".into()))?;
+                rest.push(' ');
+                rest.push_str(strip_comment(more).trim());
+            }
+        }
+        let value = parse_value(&rest).map_err(err)?;
+        let section = match &current {
+            Target::Defaults => &mut config.defaults,
+            Target::Profile(p) => {
+                config.profiles.get_mut(p).expect("раздел добавлен при заголовке")
+            }
+            Target::Counters => {
+                let Value::One(re) = value else {
+                    return Err(err(format!("счётчик «{key}» — регулярное выражение в кавычках")));
+                };
+                if config.counters.iter().any(|(k, _)| *k == key) {
+                    return Err(err(format!("счётчик «{key}» уже задан")));
+                }
+                config.counters.push((key, re));
+                continue;
+            }
+        };
+        if section.iter().any(|(k, _)| *k == key) {
+            return Err(err(format!("ключ «{key}» уже задан")));
+        }
+        section.push((key, value));
+    }
+    Ok(config)
+}
+
+/// Всё до "#" вне кавычек.
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match (quote, c) {
+            (Some('"'), '\\') if !escaped => {
+                escaped = true;
+                continue;
+            }
+            (Some(q), c) if c == q && !escaped => quote = None,
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '#') => return &line[..i],
+            _ => {}
+        }
+        escaped = false;
+    }
+    line
+}
+
+fn closes_array(s: &str) -> bool {
+    strip_comment(s).trim_end().ends_with(']')
+}
+
+/// Ключ в кавычках ("only-author") — без них.
+fn unquote(s: &str) -> String {
+    match parse_string(s) {
+        Ok((v, "")) => v,
+        _ => s.to_string(),
+    }
+}
+
+fn parse_value(s: &str) -> Result<Value, String> {
+    match s {
+        "true" => return Ok(Value::Bool(true)),
+        "false" => return Ok(Value::Bool(false)),
+        _ => {}
+    }
+    if let Some(inner) = s.strip_prefix('[') {
+        let mut items = Vec::new();
+        let mut rest = inner.trim_start();
+        loop {
+            if let Some(tail) = rest.strip_prefix(']') {
+                if !tail.trim().is_empty() {
+                    return Err(format!("лишнее после массива: «{}»", tail.trim()));
+                }
+                return Ok(Value::List(items));
+            }
+            let (item, tail) = if rest.starts_with(['"', '\'']) {
+                parse_string(rest)?
+            } else {
+                // число в массиве — до запятой или скобки
+                let end = rest.find([',', ']']).ok_or("массив не закрыт")?;
+                (rest[..end].trim().to_string(), &rest[end..])
+            };
+            items.push(item);
+            let tail = tail.trim_start();
+            rest = match tail.strip_prefix(',') {
+                Some(t) => t.trim_start(),
+                None if tail.starts_with(']') => tail,
+                None => return Err(format!("в массиве ожидается «,» или «]»: «{tail}»")),
+            };
+        }
+    }
+    if s.starts_with(['"', '\'']) {
+        let (v, tail) = parse_string(s)?;
+        if !tail.trim().is_empty() {
+            return Err(format!("лишнее после строки: «{}»", tail.trim()));
+        }
+        return Ok(Value::One(v));
+    }
+    if s.is_empty() || s.contains(char::is_whitespace) {
+        return Err(format!("непонятное значение «{s}»: строки пишутся в кавычках"));
+    }
+    Ok(Value::One(s.to_string()))
+}
+
+/// Строка в начале `s`: "..." с \-экранированием или '...' как есть.
+fn parse_string(s: &str) -> Result<(String, &str), String> {
+    let mut chars = s.char_indices();
+    let quote = match chars.next() {
+        Some((_, q @ ('"' | '\''))) => q,
+        _ => return Err(format!("ожидается строка в кавычках: «{s}»")),
+    };
+    let mut out = String::new();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            c if c == quote => return Ok((out, &s[i + 1..])),
+            '\\' if quote == '"' => match chars.next().map(|(_, e)| e) {
+                Some('n') => out.push('\n'),
+                Some('t') => out.push('\t'),
+                Some(e @ ('"' | '\\')) => out.push(e),
+                Some(e) => return Err(format!("непонятное экранирование «\\{e}»")),
+                None => break,
+            },
+            c => out.push(c),
+        }
+    }
+    Err(format!("строка не закрыта: «{s}»"))
+}
@@ -0,0 +1,95 @@
+//
+// ===================== КОНТЕКСТ ПОИСКА (search -C) =====================
+//
+// Как grep -C: вокруг каждого найденного сообщения — N соседних, даже
+// если в них совпадений нет. Предыдущие копятся готовыми строками лога,
+// следующие пишутся сразу. Между несмежными кусками — строка "--".
+// Контекст в статистику не идёт: считаются только совпадения.
+//
+
+use chrono::NaiveDateTime;
+use ahash::AHashSet;
+
+use crate::log_out::LogOut;
+
+use std::collections::VecDeque;
+use std::io::{self, Write};
+
+const SEPARATOR: &[u8] = b"--\n";
+
+/// Строка лога, отложенная до совпадения: дата и автор — для --split-by.
+struct Pending {
+    date: Option<NaiveDateTime>,
+    author: String,
+    line: Vec<u8>,
+}
+
+pub struct SearchContext {
+    lines: usize,
+    before: VecDeque<Pending>,
+    // сколько сообщений после совпадения ещё печатать
+    after: usize,
+    // в этом чате уже что-то напечатано
+    printed: bool,
+    // с последней напечатанной строки были пропущенные сообщения
+    gap: bool,
+}
+
+impl SearchContext {
+    pub fn new(lines: usize) -> Self {
+        Self { lines, before: VecDeque::new(), after: 0, printed: false, gap: false }
+    }
+
+    /// Новый чат: соседи из прошлого не в счёт, а лог может быть другим.
+    pub fn new_chat(&mut self) {
+        self.before.clear();
+        self.after = 0;
+        self.printed = false;
+        self.gap = false;
+    }
+
+    /// Сообщение без совпадения; `write` печатает его строку лога.
+    pub fn skipped(
+        &mut self,
+        out: &mut LogOut,
+        date: Option<NaiveDateTime>,
+        author: &str,
+        opened: &mut AHashSet<String>,
+        write: impl FnOnce(&mut dyn Write) -> io::Result<()>,
+    ) -> io::Result<()> {
+        if self.after > 0 {
+            self.after -= 1;
+            out.select(date, author, opened)?;
+            return write(out);
+        }
+        if self.before.len() == self.lines {
+            self.gap = true;
+            if self.before.pop_front().is_none() {
+                return Ok(());
+            }
+        }
+        let mut line = Vec::new();
+        write(&mut line)?;
+        self.before.push_back(Pending { date, author: author.to_string(), line });
+        Ok(())
+    }
+
+    /// Перед строкой совпадения: разделитель и отложенные соседи.
+    pub fn before_match(
+        &mut self,
+        out: &mut LogOut,
+        opened: &mut AHashSet<String>,
+    ) -> io::Result<()> {
+        if self.printed && self.gap {
+            out.write_all(SEPARATOR)?;
+        }
+        for p in self.before.drain(..) {
+            out.select(p.date, &p.author, opened)?;
+            out.write_all(&p.line)?;
+        }
+        self.printed = true;
+        self.gap = false;
+        self.after = self.lines;
+        Ok(())
+    }
+}
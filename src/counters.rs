@@ -0,0 +1,136 @@
+//
+// ===================== СЧЁТЧИКИ (--counter, [counters]) =====================
+//
+// Именованные регулярные выражения: сколько раз каждое встретилось в
+// текстах, всего и по авторам. Задаются флагом --counter ИМЯ=REGEX или
+// разделом файла настроек:
+//
+//   [counters]
+//   memes = "(?i)kek|lol"
+//   thanks = '(?i)\bспасибо\b'
+//
+// Регистр — через (?i), как в /regex/ словарей --wordlist.
+//
+
+use ahash::AHashMap;
+use regex::Regex;
+
+use std::io::{self, Write};
+
+use crate::cache::{Codec, Reader};
+use crate::model::Message;
+use crate::{Metric, sorted_by_count};
+
+pub struct Counter {
+    pub name: String,
+    pub pattern: String,
+    re: Regex,
+    pub total: usize,
+    pub per_author: AHashMap<String, usize>,
+}
+
+/// --counter: "ИМЯ=REGEX".
+pub fn parse_counter(s: &str) -> Result<(String, String), String> {
+    let (name, pattern) = s
+        .split_once('=')
+        .map(|(n, p)| (n.trim(), p))
+        .filter(|(n, p)| !n.is_empty() && !p.is_empty())
+        .ok_or_else(|| format!("нужно ИМЯ=REGEX, а не «{s}»"))?;
+    Regex::new(pattern).map_err(|e| format!("счётчик «{name}»: {e}"))?;
+    Ok((name.to_string(), pattern.to_string()))
+}
+
+impl Counter {
+    pub fn new(name: &str, pattern: &str) -> Result<Self, regex::Error> {
+        Ok(Counter {
+            name: name.to_string(),
+            pattern: pattern.to_string(),
+            re: Regex::new(pattern)?,
+            total: 0,
+            per_author: AHashMap::new(),
+        })
+    }
+}
+
+// Regex в кэш не пишется: выражение компилируется заново при чтении
+impl Codec for Counter {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.name.encode(out);
+        self.pattern.encode(out);
+        self.total.encode(out);
+        self.per_author.encode(out);
+    }
+
+    fn decode(r: &mut Reader) -> Result<Self, String> {
+        let name = String::decode(r)?;
+        let pattern = String::decode(r)?;
+        Ok(Counter {
+            re: Regex::new(&pattern).map_err(|e| e.to_string())?,
+            name,
+            pattern,
+            total: usize::decode(r)?,
+            per_author: AHashMap::decode(r)?,
+        })
+    }
+}
+
+/// Все счётчики разом: текст сообщения собирается один раз на всех.
+#[derive(Default)]
+pub struct Counters(pub Vec<Counter>);
+
+impl Codec for Counters {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.0.encode(out);
+    }
+
+    fn decode(r: &mut Reader) -> Result<Self, String> {
+        Vec::decode(r).map(Counters)
+    }
+}
+
+impl Counters {
+    pub fn new(defs: &[(String, String)]) -> Result<Self, String> {
+        defs.iter()
+            .map(|(name, pattern)| {
+                Counter::new(name, pattern).map_err(|e| format!("Счётчик «{name}»: {e}"))
+            })
+            .collect::<Result<_, _>>()
+            .map(Counters)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl Metric for Counters {
+    fn observe(&mut self, msg: &Message) {
+        if self.0.is_empty() {
+            return;
+        }
+        let text = msg.plain_text();
+        if text.is_empty() {
+            return;
+        }
+        let author = msg.author();
+        for c in &mut self.0 {
+            let n = c.re.find_iter(&text).count();
+            if n == 0 {
+                continue;
+            }
+            c.total += n;
+            *c.per_author.entry(author.to_string()).or_insert(0) += n;
+        }
+    }
+
+    fn report(&self, w: &mut dyn Write) -> io::Result<()> {
+        writeln!(w, "Счётчики:")?;
+        for c in &self.0 {
+            writeln!(w, "  {} ({}): {}", c.name, c.pattern, c.total)?;
+            for (author, n) in sorted_by_count(&c.per_author) {
+                writeln!(w, "  - {}: {}", author, n)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,98 @@
+//
+// ===================== КАСТОМНЫЕ ЭМОДЗИ =====================
+//
+// Премиум-эмодзи — сущность "custom_emoji" с "document_id" (id стикера
+// из набора) и обычным эмодзи-заменой в "text". Наборы по id не
+// разрешить, но по id видно, какие эмодзи расходятся.
+//
+
+use ahash::AHashMap;
+use simd_json::borrowed::Object;
+use simd_json::{BorrowedValue, StaticNode};
+
+use std::io::{self, Write};
+
+use crate::model::Message;
+use crate::{Metric, add_counts, for_each_entity, top_by_count};
+
+const TOP_CUSTOM_EMOJI: usize = 20;
+const TOP_CUSTOM_EMOJI_AUTHORS: usize = 10;
+
+#[derive(Default)]
+pub struct CustomEmojiStats {
+    pub total: usize,
+    // document_id -> сколько раз
+    pub by_id: AHashMap<String, usize>,
+    // document_id -> обычный эмодзи, которым он отображается без премиума
+    pub fallback: AHashMap<String, String>,
+    // автор -> сколько кастомных эмодзи
+    pub per_author: AHashMap<String, usize>,
+}
+
+crate::cache::codec!(CustomEmojiStats { total, by_id, fallback, per_author });
+
+fn document_id(obj: &Object) -> Option<String> {
+    match obj.get("document_id")? {
+        BorrowedValue::String(s) => Some(s.to_string()),
+        BorrowedValue::Static(StaticNode::U64(n)) => Some(n.to_string()),
+        BorrowedValue::Static(StaticNode::I64(n)) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+impl CustomEmojiStats {
+    /// Сложить счётчики; замена для id остаётся та, что встретилась раньше.
+    pub fn merge(&mut self, other: CustomEmojiStats) {
+        self.total += other.total;
+        add_counts(&mut self.by_id, other.by_id);
+        for (id, text) in other.fallback {
+            self.fallback.entry(id).or_insert(text);
+        }
+        add_counts(&mut self.per_author, other.per_author);
+    }
+}
+
+impl Metric for CustomEmojiStats {
+    fn observe(&mut self, msg: &Message) {
+        let Some(msg_obj) = msg.raw else {
+            return;
+        };
+        let author = msg.author();
+        let author = author.as_ref();
+        let mut on_message = 0usize;
+        for_each_entity(msg_obj, |kind, text, obj| {
+            if kind != "custom_emoji" {
+                return;
+            }
+            let id = document_id(obj).unwrap_or_else(|| "?".to_string());
+            if !self.fallback.contains_key(&id) {
+                self.fallback.insert(id.clone(), text.to_string());
+            }
+            *self.by_id.entry(id).or_insert(0) += 1;
+            on_message += 1;
+        });
+        if on_message > 0 {
+            self.total += on_message;
+            *self.per_author.entry(author.to_string()).or_insert(0) += on_message;
+        }
+    }
+
+    fn report(&self, w: &mut dyn Write) -> io::Result<()> {
+        writeln!(w, "Кастомные эмодзи: {}, разных: {}", self.total, self.by_id.len())?;
+        if self.total == 0 {
+            return Ok(());
+        }
+        writeln!(w, "  популярные (document_id):")?;
+        for (id, count) in top_by_count(&self.by_id, TOP_CUSTOM_EMOJI) {
+            let emoji = self.fallback.get(id).map(String::as_str).unwrap_or("");
+            writeln!(w, "  - {} {}: {}", id, emoji, count)?;
+        }
+        writeln!(w, "  кто использует чаще всех:")?;
+        for (name, count) in
+            top_by_count(&self.per_author, TOP_CUSTOM_EMOJI_AUTHORS)
+        {
+            writeln!(w, "  - {}: {}", name, count)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,227 @@
+//
+// ===================== СРАВНЕНИЕ ЭКСПОРТОВ (tgjsps diff) =====================
+//
+// Два экспорта одного чата (или аккаунта), сообщения сопоставляются по
+// id внутри чата: новые, изменённые (другой текст) и удалённые.
+//
+// Удалённым считается только то, что попадает в период нового экспорта
+// (id не меньше первого id в нём): экспорт за последний месяц не значит,
+// что остальная история удалена. Чаты, которых в новом экспорте нет
+// совсем, перечисляются отдельно.
+//
+
+use ahash::AHashMap;
+use simd_json::prelude::*;
+use simd_json::{BorrowedValue, OwnedValue, json};
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::io::{self, BufWriter, Write};
+
+use crate::{
+    NO_CHAT_NAME, author_name, build_full_text, discover_export, for_each_dom_chat,
+    get_i64_field, get_str_field, read_input, value_to_id,
+};
+
+#[derive(clap::Args, Debug)]
+pub struct DiffArgs {
+    /// Старый экспорт: JSON, архив или папка
+    old: String,
+
+    /// Новый экспорт
+    new: String,
+
+    /// Вывести разницу в JSON
+    #[arg(long = "json")]
+    json: bool,
+}
+
+struct Msg {
+    date: String,
+    author: String,
+    text: String,
+}
+
+struct Chat {
+    name: String,
+    messages: BTreeMap<i64, Msg>,
+}
+
+#[derive(Default)]
+struct ChatDiff<'a> {
+    added: Vec<(i64, &'a Msg)>,
+    // (id, было, стало)
+    edited: Vec<(i64, &'a Msg, &'a Msg)>,
+    deleted: Vec<(i64, &'a Msg)>,
+}
+
+fn load(input: &str) -> Result<Vec<(String, Chat)>, Box<dyn Error>> {
+    let path = match discover_export(input)? {
+        Some(dir) => dir.join("result.json").to_string_lossy().into_owned(),
+        None => input.to_string(),
+    };
+    let mut buf = read_input(&path)?;
+    let root = simd_json::to_borrowed_value(&mut buf)
+        .map_err(|e| format!("Ошибка парсинга JSON в {input}: {e}"))?;
+    let BorrowedValue::Object(root_obj) = &root else {
+        return Err(format!("{input}: корень JSON не объект").into());
+    };
+
+    let mut chats = Vec::new();
+    for_each_dom_chat(root_obj, |chat_obj| {
+        let name = get_str_field(chat_obj, "name").unwrap_or(NO_CHAT_NAME).to_string();
+        let id = chat_obj.get("id").map(value_to_id).unwrap_or_else(|| name.clone());
+        let mut messages = BTreeMap::new();
+        if let Some(BorrowedValue::Array(list)) = chat_obj.get("messages") {
+            for msg_val in list.iter() {
+                let BorrowedValue::Object(msg_obj) = msg_val else {
+                    continue;
+                };
+                if get_str_field(msg_obj, "type") != Some("message") {
+                    continue;
+                }
+                let Some(msg_id) = get_i64_field(msg_obj, "id") else {
+                    continue;
+                };
+                let text = msg_obj.get("text").map(build_full_text).unwrap_or_default();
+                messages.insert(
+                    msg_id,
+                    Msg {
+                        date: get_str_field(msg_obj, "date").unwrap_or("").replace('T', " "),
+                        author: author_name(msg_obj, "from", "from_id").into_owned(),
+                        text,
+                    },
+                );
+            }
+        }
+        chats.push((id, Chat { name, messages }));
+    });
+    if chats.is_empty() {
+        return Err(format!("{input}: в корне нет ни \"messages\", ни \"chats.list\"").into());
+    }
+    Ok(chats)
+}
+
+fn diff_chat<'a>(old: &'a Chat, new: &'a Chat) -> ChatDiff<'a> {
+    let mut d = ChatDiff::default();
+    for (&id, msg) in &new.messages {
+        match old.messages.get(&id) {
+            None => d.added.push((id, msg)),
+            Some(was) if was.text != msg.text => d.edited.push((id, was, msg)),
+            Some(_) => {}
+        }
+    }
+    if let Some(&first) = new.messages.keys().next() {
+        for (&id, msg) in old.messages.range(first..) {
+            if !new.messages.contains_key(&id) {
+                d.deleted.push((id, msg));
+            }
+        }
+    }
+    d
+}
+
+fn one_line(text: &str) -> String {
+    text.replace('\n', " ")
+}
+
+fn write_msg<W: Write>(w: &mut W, mark: char, id: i64, msg: &Msg) -> io::Result<()> {
+    writeln!(w, "  {mark} #{id} {} {}: {}", msg.date, msg.author, one_line(&msg.text))
+}
+
+fn msg_json(id: i64, msg: &Msg) -> OwnedValue {
+    json!({ "id": id, "date": msg.date.as_str(), "author": msg.author.as_str(),
+            "text": msg.text.as_str() })
+}
+
+pub fn run(args: &DiffArgs) -> Result<(), Box<dyn Error>> {
+    let old = load(&args.old)?;
+    let new = load(&args.new)?;
+    let old_by_id: AHashMap<&str, &Chat> = old.iter().map(|(id, c)| (id.as_str(), c)).collect();
+    let new_ids: AHashMap<&str, ()> = new.iter().map(|(id, _)| (id.as_str(), ())).collect();
+
+    let empty = Chat { name: String::new(), messages: BTreeMap::new() };
+    let diffs: Vec<(&Chat, ChatDiff)> = new
+        .iter()
+        .map(|(id, chat)| {
+            let was = old_by_id.get(id.as_str()).copied().unwrap_or(&empty);
+            (chat, diff_chat(was, chat))
+        })
+        .collect();
+    let gone: Vec<&Chat> =
+        old.iter().filter(|(id, _)| !new_ids.contains_key(id.as_str())).map(|p| &p.1).collect();
+
+    let stdout = io::stdout();
+    let mut w = BufWriter::new(stdout.lock());
+    if args.json {
+        let chats: Vec<OwnedValue> = diffs
+            .iter()
+            .map(|(chat, d)| {
+                let edited: Vec<OwnedValue> = d
+                    .edited
+                    .iter()
+                    .map(|&(id, was, now)| {
+                        json!({ "id": id, "date": now.date.as_str(),
+                                "author": now.author.as_str(),
+                                "old_text": was.text.as_str(), "new_text": now.text.as_str() })
+                    })
+                    .collect();
+                json!({
+                    "chat": chat.name.as_str(),
+                    "added": d.added.iter().map(|&(id, m)| msg_json(id, m)).collect::<Vec<_>>(),
+                    "edited": edited,
+                    "deleted": d.deleted.iter().map(|&(id, m)| msg_json(id, m)).collect::<Vec<_>>(),
+                })
+            })
+            .collect();
+        let gone: Vec<OwnedValue> = gone
+            .iter()
+            .map(|c| json!({ "chat": c.name.as_str(), "messages": c.messages.len() as u64 }))
+            .collect();
+        json!({ "chats": chats, "missing_chats": gone }).write_pp(&mut w)?;
+        writeln!(w)?;
+        return Ok(w.flush()?);
+    }
+
+    let total = |f: fn(&ChatDiff) -> usize| diffs.iter().map(|(_, d)| f(d)).sum::<usize>();
+    writeln!(
+        w,
+        "Добавлено: {}, изменено: {}, удалено: {}",
+        total(|d| d.added.len()),
+        total(|d| d.edited.len()),
+        total(|d| d.deleted.len())
+    )?;
+    for (chat, d) in &diffs {
+        if d.added.is_empty() && d.edited.is_empty() && d.deleted.is_empty() {
+            continue;
+        }
+        writeln!(
+            w,
+            "Чат «{}»: добавлено {}, изменено {}, удалено {}",
+            chat.name,
+            d.added.len(),
+            d.edited.len(),
+            d.deleted.len()
+        )?;
+        for &(id, msg) in &d.added {
+            write_msg(&mut w, '+', id, msg)?;
+        }
+        for &(id, was, now) in &d.edited {
+            writeln!(w, "  ~ #{id} {} {}:", now.date, now.author)?;
+            writeln!(w, "      было:  {}", one_line(&was.text))?;
+            writeln!(w, "      стало: {}", one_line(&now.text))?;
+        }
+        for &(id, msg) in &d.deleted {
+            write_msg(&mut w, '-', id, msg)?;
+        }
+    }
+    for chat in gone {
+        writeln!(
+            w,
+            "Чата «{}» нет в новом экспорте ({} сообщений)",
+            chat.name,
+            chat.messages.len()
+        )?;
+    }
+    Ok(w.flush()?)
+}
@@ -0,0 +1,169 @@
+//
+// ===================== ДЛИТЕЛЬНОСТИ =====================
+//
+// "duration_seconds" есть у голосовых, видео, кружков и звонков.
+// Часы голосовых и видео интереснее, чем просто их число.
+//
+
+use ahash::AHashMap;
+use chrono::NaiveDateTime;
+use simd_json::borrowed::Object;
+
+use std::io::{self, Write};
+
+use crate::{get_i64_field, get_str_field};
+
+/// Длительность в секундах, если она есть.
+pub fn duration_seconds(msg_obj: &Object) -> Option<u64> {
+    get_i64_field(msg_obj, "duration_seconds").map(|s| s.max(0) as u64)
+}
+
+/// "1 ч 02 мин 05 с" / "3 мин 10 с" / "42 с"
+pub fn format_duration(secs: u64) -> String {
+    let (h, m, s) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if h > 0 {
+        format!("{h} ч {m:02} мин {s:02} с")
+    } else if m > 0 {
+        format!("{m} мин {s:02} с")
+    } else {
+        format!("{s} с")
+    }
+}
+
+/// Суммарная и средняя длительность одного вида медиа, в целом и по авторам.
+#[derive(Default)]
+pub struct DurationStats {
+    // сколько сообщений с известной длительностью
+    pub count: usize,
+    pub total_seconds: u64,
+    // автор -> (сколько, секунд)
+    pub per_author: AHashMap<String, (usize, u64)>,
+}
+
+crate::cache::codec!(DurationStats { count, total_seconds, per_author });
+
+impl DurationStats {
+    pub fn observe(&mut self, author: &str, msg_obj: &Object) {
+        let Some(secs) = duration_seconds(msg_obj) else {
+            return;
+        };
+        self.count += 1;
+        self.total_seconds += secs;
+        let entry = self.per_author.entry(author.to_string()).or_insert((0, 0));
+        entry.0 += 1;
+        entry.1 += secs;
+    }
+
+    pub fn average(&self) -> u64 {
+        self.total_seconds.checked_div(self.count as u64).unwrap_or(0)
+    }
+
+    /// Авторы по убыванию суммарной длительности.
+    pub fn authors_by_time(&self) -> Vec<(&str, usize, u64)> {
+        let mut v: Vec<_> = self
+            .per_author
+            .iter()
+            .map(|(a, &(n, secs))| (a.as_str(), n, secs))
+            .collect();
+        v.sort_by_key(|p| std::cmp::Reverse(p.2));
+        v
+    }
+
+    /// "всего 1 ч 02 мин 05 с, в среднем 42 с"
+    pub fn summary(&self) -> String {
+        format!(
+            "всего {}, в среднем {}",
+            format_duration(self.total_seconds),
+            format_duration(self.average())
+        )
+    }
+
+    pub fn write_per_author<W: Write>(&self, w: &mut W, title: &str) -> io::Result<()> {
+        writeln!(w, "{}: {}", title, self.summary())?;
+        for (author, n, secs) in self.authors_by_time() {
+            let avg = secs.checked_div(n as u64).unwrap_or(0);
+            writeln!(
+                w,
+                "- {}: {} шт., {} (в среднем {})",
+                author,
+                n,
+                format_duration(secs),
+                format_duration(avg)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+const LONGEST_MEDIA: usize = 10;
+
+pub struct LongMedia {
+    pub seconds: u64,
+    pub kind: &'static str,
+    pub id: i64,
+    pub author: String,
+    pub date: String,
+    pub file: String,
+}
+
+crate::cache::codec!(LongMedia { seconds, kind, id, author, date, file });
+
+/// Самые длинные ролики: не больше LONGEST_MEDIA, по убыванию длительности.
+#[derive(Default)]
+pub struct LongestMedia {
+    pub items: Vec<LongMedia>,
+}
+
+crate::cache::codec!(LongestMedia { items });
+
+impl LongestMedia {
+    /// `date` — разобранная дата, если она считалась; иначе берём строку из экспорта.
+    pub fn offer(
+        &mut self,
+        kind: &'static str,
+        author: &str,
+        date: Option<NaiveDateTime>,
+        msg_obj: &Object,
+    ) {
+        let Some(seconds) = duration_seconds(msg_obj) else {
+            return;
+        };
+        let full = self.items.len() >= LONGEST_MEDIA;
+        if full && self.items.last().is_some_and(|m| m.seconds >= seconds) {
+            return;
+        }
+        let pos = self.items.partition_point(|m| m.seconds >= seconds);
+        self.items.insert(
+            pos,
+            LongMedia {
+                seconds,
+                kind,
+                id: get_i64_field(msg_obj, "id").unwrap_or(0),
+                author: author.to_string(),
+                date: match date {
+                    Some(dt) => dt.format("%Y-%m-%d %H:%M").to_string(),
+                    None => get_str_field(msg_obj, "date").unwrap_or("").to_string(),
+                },
+                file: get_str_field(msg_obj, "file").unwrap_or("").to_string(),
+            },
+        );
+        self.items.truncate(LONGEST_MEDIA);
+    }
+
+    pub fn write_text<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        for (i, m) in self.items.iter().enumerate() {
+            writeln!(
+                w,
+                "{:>2}. {} — {}, {} (#{}, {}): {}",
+                i + 1,
+                format_duration(m.seconds),
+                m.author,
+                m.date,
+                m.id,
+                m.kind,
+                m.file
+            )?;
+        }
+        Ok(())
+    }
+}
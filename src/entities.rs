@@ -0,0 +1,92 @@
+//
+// ===================== РАЗМЕТКА ТЕКСТА =====================
+//
+// Типы сущностей из "text_entities": ссылки, код, жирный/курсив,
+// спойлеры, почта, телефоны, кэштеги... "plain" — обычный текст, не считаем.
+//
+
+use ahash::AHashMap;
+
+use std::io::{self, Write};
+
+use crate::model::Message;
+use crate::{Metric, add_counts, for_each_entity, sorted_by_count};
+
+#[derive(Default)]
+pub struct EntityStats {
+    // тип сущности -> сколько раз встретилась
+    pub by_type: AHashMap<String, usize>,
+    // сообщений хотя бы с одной сущностью
+    pub messages_with_entities: usize,
+}
+
+crate::cache::codec!(EntityStats { by_type, messages_with_entities });
+
+/// Подпись для известных типов; незнакомые печатаются как есть.
+fn type_label(kind: &str) -> &str {
+    match kind {
+        "link" => "ссылки",
+        "text_link" => "ссылки под текстом",
+        "email" => "почта",
+        "phone" => "телефоны",
+        "mention" => "упоминания @",
+        "mention_name" => "упоминания по имени",
+        "hashtag" => "хэштеги",
+        "cashtag" => "кэштеги",
+        "bot_command" => "команды ботам",
+        "bold" => "жирный",
+        "italic" => "курсив",
+        "underline" => "подчёркнутый",
+        "strikethrough" => "зачёркнутый",
+        "spoiler" => "спойлеры",
+        "code" => "код в строке",
+        "pre" => "блоки кода",
+        "blockquote" => "цитаты",
+        "custom_emoji" => "кастомные эмодзи",
+        other => other,
+    }
+}
+
+impl EntityStats {
+    /// Сложить счётчики по типам сущностей и сообщения с разметкой.
+    pub fn merge(&mut self, other: EntityStats) {
+        add_counts(&mut self.by_type, other.by_type);
+        self.messages_with_entities += other.messages_with_entities;
+    }
+}
+
+impl Metric for EntityStats {
+    fn observe(&mut self, msg: &Message) {
+        let Some(msg_obj) = msg.raw else {
+            return;
+        };
+        let mut any = false;
+        for_each_entity(msg_obj, |kind, _, _| {
+            if kind == "plain" {
+                return;
+            }
+            any = true;
+            match self.by_type.get_mut(kind) {
+                Some(c) => *c += 1,
+                None => {
+                    self.by_type.insert(kind.to_string(), 1);
+                }
+            }
+        });
+        if any {
+            self.messages_with_entities += 1;
+        }
+    }
+
+    fn report(&self, w: &mut dyn Write) -> io::Result<()> {
+        writeln!(
+            w,
+            "Разметка текста: {} сообщений с сущностями",
+            self.messages_with_entities
+        )?;
+        for (kind, count) in sorted_by_count(&self.by_type) {
+            writeln!(w, "  - {} ({}): {}", type_label(kind), kind, count)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,79 @@
+//
+// ===================== РАЗБОР МЕДИА ПО ПАПКАМ =====================
+//
+// --extract-media: копии файлов экспорта в <ГГГГ-ММ>/<автор>/ с датой
+// в имени (2023-05-14_10-22-01_photo_123.jpg), чтобы архив медиа можно
+// было листать без JSON. Оригиналы не трогаем.
+//
+
+use chrono::NaiveDateTime;
+use simd_json::borrowed::Object;
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::media::{for_each_media_path, is_placeholder};
+use crate::{get_i64_field, safe_file_name};
+
+pub struct MediaExtractor {
+    // папка экспорта и куда раскладывать
+    base: PathBuf,
+    out_dir: PathBuf,
+    pub copied: usize,
+    // нет в экспорте (не скачивались или удалены)
+    pub skipped: usize,
+}
+
+impl MediaExtractor {
+    pub fn new(base: PathBuf, out_dir: &Path) -> io::Result<Self> {
+        std::fs::create_dir_all(out_dir)?;
+        Ok(Self {
+            base,
+            out_dir: out_dir.to_path_buf(),
+            copied: 0,
+            skipped: 0,
+        })
+    }
+
+    pub fn observe(
+        &mut self,
+        author: &str,
+        kind: &str,
+        date: Option<NaiveDateTime>,
+        msg_obj: &Object,
+    ) -> io::Result<()> {
+        let id = get_i64_field(msg_obj, "id").unwrap_or(0);
+        let (month, stamp) = match date {
+            Some(dt) => (
+                dt.format("%Y-%m").to_string(),
+                dt.format("%Y-%m-%d_%H-%M-%S").to_string(),
+            ),
+            None => ("без-даты".to_string(), "без-даты".to_string()),
+        };
+
+        let mut res = Ok(());
+        for_each_media_path(msg_obj, |_, path| {
+            if res.is_err() {
+                return;
+            }
+            let src = self.base.join(path);
+            if is_placeholder(path) || !src.is_file() {
+                self.skipped += 1;
+                return;
+            }
+
+            let dir = self.out_dir.join(&month).join(safe_file_name(author));
+            let ext = Path::new(path)
+                .extension()
+                .map(|e| format!(".{}", e.to_string_lossy()))
+                .unwrap_or_default();
+            let dst = dir.join(format!("{stamp}_{kind}_{id}{ext}"));
+
+            res = std::fs::create_dir_all(&dir).and_then(|_| std::fs::copy(&src, &dst).map(|_| ()));
+            if res.is_ok() {
+                self.copied += 1;
+            }
+        });
+        res
+    }
+}
@@ -0,0 +1,146 @@
+//
+// ===================== ФИЛЬТРЫ СООБЩЕНИЙ =====================
+//
+// Отбрасывают сообщения ДО записи в лог и ДО подсчёта статистики.
+//
+// --no-bots: бот — автор, чьё имя кончается на "bot" (HelperBot, Combot,
+// Shieldy Bot), плюс всё, что перечислено в --bot.
+//
+
+use chrono::{NaiveDate, NaiveDateTime};
+use regex::Regex;
+
+#[derive(Default)]
+pub struct Filter {
+    pub since: Option<NaiveDateTime>,
+    pub until: Option<NaiveDateTime>,
+
+    // сравниваются и с `from`, и с `from_id`
+    pub only_authors: Vec<String>,
+    pub exclude_authors: Vec<String>,
+
+    // --no-bots и свой список ботов (имя или from_id)
+    pub no_bots: bool,
+    pub bots: Vec<String>,
+
+    // --grep: остаются только сообщения с совпадением в тексте
+    pub grep: Option<TextMatcher>,
+}
+
+impl Filter {
+    /// Нужна ли дата сообщения для фильтрации.
+    pub fn needs_date(&self) -> bool {
+        self.since.is_some() || self.until.is_some()
+    }
+
+    /// Сообщение без распознанной даты при активном диапазоне отбрасывается.
+    pub fn accepts_date(&self, date: Option<NaiveDateTime>) -> bool {
+        if !self.needs_date() {
+            return true;
+        }
+        let Some(dt) = date else {
+            return false;
+        };
+        if let Some(since) = self.since
+            && dt < since
+        {
+            return false;
+        }
+        if let Some(until) = self.until
+            && dt > until
+        {
+            return false;
+        }
+        true
+    }
+
+    pub fn accepts_author(&self, name: &str, from_id: &str) -> bool {
+        if !self.only_authors.is_empty()
+            && !self.only_authors.iter().any(|p| author_matches(p, name, from_id))
+        {
+            return false;
+        }
+        if self.no_bots && self.is_bot(name, from_id) {
+            return false;
+        }
+        !self
+            .exclude_authors
+            .iter()
+            .any(|p| author_matches(p, name, from_id))
+    }
+
+    fn is_bot(&self, name: &str, from_id: &str) -> bool {
+        let lower = name.trim_end().to_ascii_lowercase();
+        lower.ends_with("bot") || self.bots.iter().any(|p| author_matches(p, name, from_id))
+    }
+}
+
+pub enum TextMatcher {
+    /// Подстрока без учёта регистра (хранится уже в нижнем регистре).
+    Plain(String),
+    Regex(Regex),
+}
+
+impl TextMatcher {
+    pub fn new(pattern: &str, regex: bool) -> Result<Self, String> {
+        if regex {
+            Regex::new(pattern)
+                .map(TextMatcher::Regex)
+                .map_err(|e| format!("Некорректное регулярное выражение: {e}"))
+        } else {
+            Ok(TextMatcher::Plain(pattern.to_lowercase()))
+        }
+    }
+
+    /// Число непересекающихся совпадений в тексте.
+    pub fn count_matches(&self, text: &str) -> usize {
+        match self {
+            TextMatcher::Plain(needle) => {
+                if needle.is_empty() {
+                    return 0;
+                }
+                text.to_lowercase().matches(needle.as_str()).count()
+            }
+            TextMatcher::Regex(re) => re.find_iter(text).count(),
+        }
+    }
+}
+
+/// "user123456" совпадает и с "user123456", и с голым "123456".
+fn author_matches(pattern: &str, name: &str, from_id: &str) -> bool {
+    pattern == name
+        || pattern == from_id
+        || from_id.strip_suffix(pattern).is_some_and(|prefix| {
+            !prefix.is_empty() && prefix.bytes().all(|b| b.is_ascii_alphabetic())
+        })
+}
+
+const DATETIME_FORMATS: [&str; 4] = [
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M",
+];
+
+fn parse_date_bound(s: &str, end_of_day: bool) -> Result<NaiveDateTime, String> {
+    for fmt in DATETIME_FORMATS {
+        if let Ok(dt) = NaiveDateTime::parse_from_str(s, fmt) {
+            return Ok(dt);
+        }
+    }
+    let d = NaiveDate::parse_from_str(s, "%Y-%m-%d").map_err(|_| {
+        format!("не понимаю дату «{s}», нужно ГГГГ-ММ-ДД или ГГГГ-ММ-ДДTЧЧ:ММ[:СС]")
+    })?;
+    let (h, m, sec) = if end_of_day { (23, 59, 59) } else { (0, 0, 0) };
+    Ok(d.and_hms_opt(h, m, sec).unwrap())
+}
+
+/// `--since`: голая дата означает начало дня.
+pub fn parse_since(s: &str) -> Result<NaiveDateTime, String> {
+    parse_date_bound(s, false)
+}
+
+/// `--until`: голая дата означает конец дня (день включается целиком).
+pub fn parse_until(s: &str) -> Result<NaiveDateTime, String> {
+    parse_date_bound(s, true)
+}
@@ -0,0 +1,155 @@
+//
+// ===================== ПЕРЕСЫЛКИ =====================
+//
+// forwarded_from — имя канала или человека, откуда переслано. Если
+// оригинал скрыт настройками приватности, имени нет (null) — такие
+// пересылки собираются под одним общим источником.
+//
+// Спам пересылками: авторы, у которых пересылки — большая часть
+// сообщений (доля задаётся --forward-spam-ratio), и одна и та же
+// пересылка (источник + текст), запощенная несколько раз.
+//
+
+use ahash::AHashMap;
+use simd_json::borrowed::Object;
+
+use std::io::{self, Write};
+
+use crate::{get_str_field, percent_of, spam_key, top_by_count};
+
+const TOP_SOURCES: usize = 20;
+const TOP_FORWARDERS: usize = 15;
+const SOURCES_PER_AUTHOR: usize = 5;
+const HIDDEN_SOURCE: &str = "(источник скрыт)";
+// меньше сообщений — доля пересылок ничего не говорит
+const MIN_FORWARD_SPAM_MESSAGES: usize = 5;
+const TOP_REPEATED_FORWARDS: usize = 10;
+const REPEAT_QUOTE_CHARS: usize = 80;
+/// --forward-spam-ratio по умолчанию.
+pub const DEFAULT_SPAM_RATIO: f64 = 0.8;
+
+#[derive(Default)]
+pub struct ForwardStats {
+    pub total: usize,
+    // источник -> сколько пересылок
+    pub sources: AHashMap<String, usize>,
+    // автор -> (источник -> сколько)
+    pub per_author: AHashMap<String, AHashMap<String, usize>>,
+    // (источник, текст) -> сколько раз переслано
+    pub repeats: AHashMap<(String, String), usize>,
+    // с какой доли пересылок автор считается спамером
+    pub spam_ratio: f64,
+}
+
+crate::cache::codec!(ForwardStats { total, sources, per_author, repeats, spam_ratio });
+
+impl ForwardStats {
+    pub fn new(spam_ratio: f64) -> Self {
+        Self { spam_ratio, ..Self::default() }
+    }
+
+    /// `min_chars` — порог длины текста для повторов, как у обычного спама.
+    pub fn observe(&mut self, author: &str, msg_obj: &Object, min_chars: usize) {
+        let source = get_str_field(msg_obj, "forwarded_from").unwrap_or(HIDDEN_SOURCE);
+        self.total += 1;
+        if let Some(text) = msg_obj.get("text").and_then(|t| spam_key(t, min_chars)) {
+            *self.repeats.entry((source.to_string(), text)).or_insert(0) += 1;
+        }
+        *self
+            .per_author
+            .entry(author.to_string())
+            .or_default()
+            .entry(source.to_string())
+            .or_insert(0) += 1;
+        *self.sources.entry(source.to_string()).or_insert(0) += 1;
+    }
+
+    /// Авторы по числу пересылок.
+    pub fn forwarders(&self) -> Vec<(&str, usize)> {
+        let mut v: Vec<_> = self
+            .per_author
+            .iter()
+            .map(|(a, m)| (a.as_str(), m.values().sum::<usize>()))
+            .collect();
+        v.sort_by_key(|p| std::cmp::Reverse(p.1));
+        v
+    }
+
+    /// (автор, пересылок, всего сообщений) с долей пересылок не ниже порога.
+    pub fn forward_spammers<'a>(
+        &'a self,
+        messages: &AHashMap<String, usize>,
+    ) -> Vec<(&'a str, usize, usize)> {
+        let mut v: Vec<_> = self
+            .forwarders()
+            .into_iter()
+            .filter_map(|(author, fwd)| {
+                let total = messages.get(author).copied().unwrap_or(fwd).max(fwd);
+                let spam = total >= MIN_FORWARD_SPAM_MESSAGES
+                    && fwd as f64 >= total as f64 * self.spam_ratio;
+                spam.then_some((author, fwd, total))
+            })
+            .collect();
+        v.sort_by(|a, b| (b.1 * a.2).cmp(&(a.1 * b.2)).then_with(|| b.1.cmp(&a.1)));
+        v
+    }
+
+    /// Пересылки, запощенные не меньше `min_repeats` раз: (источник, текст, раз).
+    pub fn repeated(&self, min_repeats: usize) -> Vec<(&str, &str, usize)> {
+        let mut v: Vec<_> = self
+            .repeats
+            .iter()
+            .filter(|p| *p.1 >= min_repeats)
+            .map(|((source, text), &n)| (source.as_str(), text.as_str(), n))
+            .collect();
+        v.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.1.cmp(b.1)));
+        v
+    }
+
+    pub fn write_spam<W: Write>(
+        &self,
+        w: &mut W,
+        messages: &AHashMap<String, usize>,
+        min_repeats: usize,
+    ) -> io::Result<()> {
+        writeln!(
+            w,
+            "Пересылают больше, чем пишут сами (пересылок от {:.0}%, от {} сообщений):",
+            self.spam_ratio * 100.0,
+            MIN_FORWARD_SPAM_MESSAGES
+        )?;
+        for (author, fwd, total) in self.forward_spammers(messages) {
+            writeln!(w, "- {}: {} из {} ({:.1}%)", author, fwd, total, percent_of(fwd, total))?;
+        }
+        writeln!(w, "Одинаковые пересылки несколько раз:")?;
+        let repeated = self.repeated(min_repeats);
+        for (source, text, n) in repeated.into_iter().take(TOP_REPEATED_FORWARDS) {
+            let mut quote: String = text.chars().take(REPEAT_QUOTE_CHARS).collect();
+            if quote.len() < text.len() {
+                quote.push('…');
+            }
+            writeln!(w, "- {} раз из «{}»: {}", n, source, quote.replace('\n', " "))?;
+        }
+        Ok(())
+    }
+
+    pub fn write_text<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(w, "Пересылки: {}, источников: {}", self.total, self.sources.len())?;
+        if self.sources.is_empty() {
+            return Ok(());
+        }
+        writeln!(w, "  откуда пересылают чаще всего:")?;
+        for (source, count) in top_by_count(&self.sources, TOP_SOURCES) {
+            writeln!(w, "  - {}: {}", source, count)?;
+        }
+        writeln!(w, "  по авторам:")?;
+        for (author, total) in self.forwarders().into_iter().take(TOP_FORWARDERS) {
+            let top: Vec<String> = top_by_count(&self.per_author[author], SOURCES_PER_AUTHOR)
+                .into_iter()
+                .map(|(s, c)| format!("{s} ({c})"))
+                .collect();
+            writeln!(w, "  - {}: {} — {}", author, total, top.join(", "))?;
+        }
+        Ok(())
+    }
+}
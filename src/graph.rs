@@ -0,0 +1,124 @@
+//
+// ===================== ГРАФ ОТВЕТОВ (--graph) =====================
+//
+// Сеть "кто кому отвечает" с весами рёбер: Graphviz DOT или GraphML
+// (для Gephi) — по расширению файла. С --graph-mentions добавляются
+// рёбра упоминаний, --mention-graph пишет только их отдельным файлом.
+// У упоминаний цель — @username или имя из разметки, так что это могут
+// быть отдельные вершины.
+//
+
+use ahash::AHashMap;
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use crate::Stats;
+use crate::report::esc;
+
+struct Edge<'a> {
+    from: &'a str,
+    to: &'a str,
+    weight: u64,
+    kind: &'static str,
+}
+
+fn dot_str(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Рёбра ответов и/или упоминаний; возвращает (вершин, рёбер).
+pub fn write_graph(
+    path: &str,
+    stats: &Stats,
+    replies: bool,
+    mentions: bool,
+) -> io::Result<(usize, usize)> {
+    let mut edges: Vec<Edge> = Vec::new();
+    if replies {
+        edges.extend(stats.interactions.pairs().into_iter().map(|(from, to, d)| Edge {
+            from,
+            to,
+            weight: d.count(),
+            kind: "reply",
+        }));
+    }
+    if mentions {
+        edges.extend(stats.mentions.top_pairs().into_iter().map(|(from, to, c)| Edge {
+            from,
+            to,
+            weight: c as u64,
+            kind: "mention",
+        }));
+    }
+
+    // вершины в порядке первого появления, id — номер
+    let mut nodes: Vec<&str> = Vec::new();
+    let mut ids: AHashMap<&str, usize> = AHashMap::new();
+    for e in &edges {
+        for name in [e.from, e.to] {
+            if !ids.contains_key(name) {
+                ids.insert(name, nodes.len());
+                nodes.push(name);
+            }
+        }
+    }
+    let messages = |name: &str| stats.per_author.get(name).copied().unwrap_or(0);
+
+    let mut w = BufWriter::new(File::create(path)?);
+    if path.to_lowercase().ends_with(".graphml") {
+        writeln!(w, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(w, r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#)?;
+        writeln!(
+            w,
+            r#"  <key id="label" for="node" attr.name="label" attr.type="string"/>"#
+        )?;
+        writeln!(
+            w,
+            r#"  <key id="messages" for="node" attr.name="messages" attr.type="int"/>"#
+        )?;
+        writeln!(w, r#"  <key id="weight" for="edge" attr.name="weight" attr.type="int"/>"#)?;
+        writeln!(w, r#"  <key id="kind" for="edge" attr.name="kind" attr.type="string"/>"#)?;
+        writeln!(w, r#"  <graph id="{}" edgedefault="directed">"#, esc(&stats.chat_name))?;
+        for (i, name) in nodes.iter().enumerate() {
+            writeln!(
+                w,
+                "    <node id=\"n{i}\"><data key=\"label\">{}</data>\
+                 <data key=\"messages\">{}</data></node>",
+                esc(name),
+                messages(name)
+            )?;
+        }
+        for (i, e) in edges.iter().enumerate() {
+            writeln!(
+                w,
+                "    <edge id=\"e{i}\" source=\"n{}\" target=\"n{}\">\
+                 <data key=\"weight\">{}</data><data key=\"kind\">{}</data></edge>",
+                ids[e.from], ids[e.to], e.weight, e.kind
+            )?;
+        }
+        writeln!(w, "  </graph>")?;
+        writeln!(w, "</graphml>")?;
+    } else {
+        writeln!(w, "digraph {} {{", dot_str(&stats.chat_name))?;
+        for name in &nodes {
+            writeln!(w, "  {} [messages={}];", dot_str(name), messages(name))?;
+        }
+        for e in &edges {
+            let style = if e.kind == "mention" { ", style=dashed" } else { "" };
+            writeln!(
+                w,
+                "  {} -> {} [weight={}, label={}, kind={}{}];",
+                dot_str(e.from),
+                dot_str(e.to),
+                e.weight,
+                e.weight,
+                e.kind,
+                style
+            )?;
+        }
+        writeln!(w, "}}")?;
+    }
+    w.flush()?;
+    Ok((nodes.len(), edges.len()))
+}
@@ -0,0 +1,260 @@
+//
+// ===================== СЖАТИЕ ЛОГА В GZIP =====================
+//
+// Свой кодировщик deflate (RFC 1951) в обёртке gzip (RFC 1952), чтобы не
+// тащить зависимость ради одного формата. LZ77 с хеш-цепочками и
+// фиксированные коды Хаффмана: архив выходит примерно в полтора раза
+// больше, чем у `gzip`, но многогигабайтный лог больше не занимает диск.
+//
+// Вход режется на блоки по BLOCK байт, совпадения ищутся внутри блока.
+// Дописывание в существующий .gz добавляет новый gzip-член — gunzip и zcat
+// читают такие файлы целиком.
+//
+
+use std::io::{self, Write};
+
+const BLOCK: usize = 256 * 1024;
+const WINDOW: usize = 32 * 1024;
+const HASH_BITS: u32 = 15;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+// дальше по цепочке выигрыш мал, а время растёт
+const MAX_CHAIN: usize = 32;
+
+const LEN_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115,
+    131, 163, 195, 227, 258,
+];
+const LEN_EXTRA: [u8; 29] =
+    [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut c = i as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 != 0 { 0xEDB8_8320 ^ (c >> 1) } else { c >> 1 };
+            k += 1;
+        }
+        table[i] = c;
+        i += 1;
+    }
+    table
+};
+
+pub fn crc32_update(crc: u32, data: &[u8]) -> u32 {
+    let mut c = !crc;
+    for &b in data {
+        c = CRC_TABLE[((c ^ b as u32) & 0xFF) as usize] ^ (c >> 8);
+    }
+    !c
+}
+
+/// Коды Хаффмана пишутся старшим битом вперёд, а поток — младшим.
+fn reverse_bits(code: u32, len: u32) -> u32 {
+    code.reverse_bits() >> (32 - len)
+}
+
+struct BitWriter {
+    acc: u64,
+    bits: u32,
+    bytes: Vec<u8>,
+}
+
+impl BitWriter {
+    fn put(&mut self, value: u32, len: u32) {
+        self.acc |= (value as u64) << self.bits;
+        self.bits += len;
+        while self.bits >= 8 {
+            self.bytes.push(self.acc as u8);
+            self.acc >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    fn align(&mut self) {
+        if self.bits > 0 {
+            self.bytes.push(self.acc as u8);
+            self.acc = 0;
+            self.bits = 0;
+        }
+    }
+
+    /// Символ литералов/длин фиксированным кодом.
+    fn literal(&mut self, sym: u32) {
+        let (code, len) = match sym {
+            0..=143 => (0x30 + sym, 8),
+            144..=255 => (0x190 + sym - 144, 9),
+            256..=279 => (sym - 256, 7),
+            _ => (0xC0 + sym - 280, 8),
+        };
+        self.put(reverse_bits(code, len), len);
+    }
+
+    fn matched(&mut self, len: usize, dist: usize) {
+        let li = LEN_BASE.partition_point(|&b| b as usize <= len) - 1;
+        self.literal(257 + li as u32);
+        self.put((len - LEN_BASE[li] as usize) as u32, LEN_EXTRA[li] as u32);
+        let di = DIST_BASE.partition_point(|&b| b as usize <= dist) - 1;
+        self.put(reverse_bits(di as u32, 5), 5);
+        self.put((dist - DIST_BASE[di] as usize) as u32, DIST_EXTRA[di] as u32);
+    }
+}
+
+fn hash3(data: &[u8]) -> usize {
+    let v = (data[0] as u32) << 16 | (data[1] as u32) << 8 | data[2] as u32;
+    (v.wrapping_mul(0x9E37_79B1) >> (32 - HASH_BITS)) as usize
+}
+
+/// Один блок фиксированными кодами (BTYPE=01), без BFINAL.
+fn compress_block(data: &[u8], bw: &mut BitWriter, head: &mut [u32], prev: &mut [u32]) {
+    head.fill(0);
+    bw.put(0b010, 3);
+    // в head и prev — позиция + 1, 0 — пусто
+    let insert = |pos: usize, head: &mut [u32], prev: &mut [u32]| {
+        if pos + MIN_MATCH <= data.len() {
+            let h = hash3(&data[pos..]);
+            prev[pos % WINDOW] = head[h];
+            head[h] = pos as u32 + 1;
+        }
+    };
+
+    let mut pos = 0;
+    while pos < data.len() {
+        let mut best = (0, 0);
+        if pos + MIN_MATCH <= data.len() {
+            let max = (data.len() - pos).min(MAX_MATCH);
+            let mut cand = head[hash3(&data[pos..])];
+            let mut chain = 0;
+            while cand != 0 && chain < MAX_CHAIN {
+                let at = cand as usize - 1;
+                if pos - at > WINDOW {
+                    break;
+                }
+                let len = data[at..at + max]
+                    .iter()
+                    .zip(&data[pos..pos + max])
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                if len > best.0 {
+                    best = (len, pos - at);
+                    if len == max {
+                        break;
+                    }
+                }
+                cand = prev[at % WINDOW];
+                chain += 1;
+            }
+        }
+
+        if best.0 >= MIN_MATCH {
+            bw.matched(best.0, best.1);
+            for p in pos..pos + best.0 {
+                insert(p, head, prev);
+            }
+            pos += best.0;
+        } else {
+            bw.literal(data[pos] as u32);
+            insert(pos, head, prev);
+            pos += 1;
+        }
+    }
+    bw.literal(256);
+}
+
+/// Пишет gzip в `inner`; `finish` дописывает хвост (без него архив битый).
+pub struct GzWriter<W: Write> {
+    inner: W,
+    pending: Vec<u8>,
+    bw: BitWriter,
+    head: Vec<u32>,
+    prev: Vec<u32>,
+    crc: u32,
+    size: u32,
+    finished: bool,
+}
+
+impl<W: Write> GzWriter<W> {
+    pub fn new(mut inner: W) -> io::Result<Self> {
+        // ID1 ID2 CM=deflate FLG=0 MTIME=0 XFL=0 OS=unix
+        inner.write_all(&[0x1F, 0x8B, 8, 0, 0, 0, 0, 0, 0, 3])?;
+        Ok(Self {
+            inner,
+            pending: Vec::with_capacity(BLOCK),
+            bw: BitWriter { acc: 0, bits: 0, bytes: Vec::new() },
+            head: vec![0; 1 << HASH_BITS],
+            prev: vec![0; WINDOW],
+            crc: 0,
+            size: 0,
+            finished: false,
+        })
+    }
+
+    fn compress_pending(&mut self) -> io::Result<()> {
+        if !self.pending.is_empty() {
+            compress_block(&self.pending, &mut self.bw, &mut self.head, &mut self.prev);
+            self.pending.clear();
+        }
+        self.inner.write_all(&self.bw.bytes)?;
+        self.bw.bytes.clear();
+        Ok(())
+    }
+
+    pub fn finish(&mut self) -> io::Result<()> {
+        if self.finished {
+            return Ok(());
+        }
+        self.compress_pending()?;
+        // последний пустой блок: BFINAL=1, BTYPE=01, конец блока
+        self.bw.put(0b011, 3);
+        self.bw.literal(256);
+        self.bw.align();
+        self.bw.bytes.extend_from_slice(&self.crc.to_le_bytes());
+        self.bw.bytes.extend_from_slice(&self.size.to_le_bytes());
+        self.compress_pending()?;
+        self.finished = true;
+        self.inner.flush()
+    }
+}
+
+impl<W: Write> Write for GzWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.finished {
+            return Err(io::Error::other("запись в закрытый gzip"));
+        }
+        self.crc = crc32_update(self.crc, buf);
+        self.size = self.size.wrapping_add(buf.len() as u32);
+        let mut rest = buf;
+        while !rest.is_empty() {
+            let n = (BLOCK - self.pending.len()).min(rest.len());
+            self.pending.extend_from_slice(&rest[..n]);
+            rest = &rest[n..];
+            if self.pending.len() == BLOCK {
+                self.compress_pending()?;
+            }
+        }
+        Ok(buf.len())
+    }
+
+    /// Недописанные биты последнего байта остаются до следующего блока.
+    fn flush(&mut self) -> io::Result<()> {
+        self.compress_pending()?;
+        self.inner.flush()
+    }
+}
+
+impl<W: Write> Drop for GzWriter<W> {
+    fn drop(&mut self) {
+        let _ = self.finish();
+    }
+}
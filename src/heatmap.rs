@@ -0,0 +1,49 @@
+//
+// ===================== ТЕПЛОВАЯ КАРТА ДЕНЬ НЕДЕЛИ × ЧАС =====================
+//
+// 7×24 счётчика сообщений. В тексте клетка закрашивается одним из
+// SHADES по доле от самой загруженной клетки.
+//
+
+use std::io::{self, Write};
+
+use crate::WEEKDAYS_SHORT;
+
+pub type WeekHourGrid = [[usize; 24]; 7];
+
+const SHADES: [&str; 5] = ["  ", "░░", "▒▒", "▓▓", "██"];
+
+fn shade(count: usize, max: usize) -> &'static str {
+    if count == 0 {
+        return SHADES[0];
+    }
+    // 1..=4: четверти максимума
+    let level = (count * 4).div_ceil(max.max(1)).clamp(1, 4);
+    SHADES[level]
+}
+
+pub fn grid_max(grid: &WeekHourGrid) -> usize {
+    grid.iter().flatten().copied().max().unwrap_or(0)
+}
+
+pub fn write_heatmap<W: Write>(w: &mut W, grid: &WeekHourGrid) -> io::Result<()> {
+    let max = grid_max(grid);
+    writeln!(w, "Активность: день недели × час (максимум {} сообщений в клетке):", max)?;
+    write!(w, "    ")?;
+    for hour in 0..24 {
+        write!(w, " {:02}", hour)?;
+    }
+    writeln!(w)?;
+    for (day, row) in grid.iter().enumerate() {
+        write!(w, "  {}", WEEKDAYS_SHORT[day])?;
+        for &count in row {
+            write!(w, " {}", shade(count, max))?;
+        }
+        writeln!(w)?;
+    }
+    writeln!(
+        w,
+        "  {} — до 25% максимума, {} — до 50%, {} — до 75%, {} — больше 75%",
+        SHADES[1], SHADES[2], SHADES[3], SHADES[4]
+    )
+}
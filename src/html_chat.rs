@@ -0,0 +1,413 @@
+//
+// ===================== HTML-ВЕРСИЯ ПЕРЕПИСКИ =====================
+//
+// --html-chat DIR: статические страницы по месяцам (<чат>_<ГГГГ-ММ>.html)
+// и index.html со списком. Фото и стикеры показываются, голосовые и видео
+// проигрываются прямо на странице — файлы берутся из папки экспорта по
+// относительным ссылкам, ничего не копируется. Страница открывается при
+// смене месяца, так что память не зависит от размера чата. Если месяц
+// встречается снова, сообщения дописываются в его страницу; подвал со
+// ссылками на соседние месяцы пишется один раз, в finish, когда известны
+// все страницы.
+//
+// Ссылки из чужой переписки ведут только на http(s), tg: и mailto: —
+// javascript: и прочие схемы остаются обычным текстом.
+//
+
+use chrono::NaiveDateTime;
+use simd_json::BorrowedValue;
+use simd_json::borrowed::Object;
+
+use std::fmt::Write as _;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Component, Path, PathBuf};
+
+use crate::media::is_placeholder;
+use crate::model::Message;
+use crate::report::esc;
+use crate::{get_i64_field, get_poll_question, get_str_field, safe_file_name, service};
+
+const STYLE: &str = "
+body { font-family: -apple-system, 'Segoe UI', Roboto, sans-serif; margin: 0;
+       background: #e7ebf0; color: #222; }
+main { max-width: 760px; margin: 0 auto; padding: 16px; }
+nav { display: flex; justify-content: space-between; margin: 12px 0; }
+.msg { background: #fff; border-radius: 8px; padding: 8px 12px; margin: 6px 0;
+       box-shadow: 0 1px 2px rgba(0,0,0,.08); }
+.meta { font-size: 13px; color: #888; margin-bottom: 4px; }
+.meta b { color: #2b6cb0; }
+.meta a { color: #aaa; text-decoration: none; }
+.reply, .fwd { font-size: 13px; color: #666; border-left: 3px solid #4a90d9;
+               padding-left: 6px; margin-bottom: 4px; }
+.text { white-space: pre-wrap; word-wrap: break-word; }
+.media img { max-width: 100%; max-height: 480px; border-radius: 6px; }
+.media img.sticker { max-width: 160px; }
+.media video { max-width: 100%; max-height: 480px; }
+.missing { color: #999; font-style: italic; }
+.service { text-align: center; color: #666; font-size: 13px; margin: 10px 0; }
+ul.months { columns: 3; }
+";
+
+struct Page {
+    w: BufWriter<File>,
+    chat: String,
+    month: String,
+    file: String,
+}
+
+struct PageInfo {
+    chat: String,
+    month: String,
+    file: String,
+    messages: usize,
+}
+
+pub struct HtmlChat {
+    out_dir: PathBuf,
+    // путь от out_dir до папки экспорта, с '/' на конце (или пустой)
+    media_prefix: String,
+    page: Option<Page>,
+    pages: Vec<PageInfo>,
+}
+
+/// Путь `to` относительно `from` (обе папки); если не выходит — абсолютный.
+fn relative_path(from: &Path, to: &Path) -> PathBuf {
+    let (Ok(from), Ok(to)) = (from.canonicalize(), to.canonicalize()) else {
+        return to.to_path_buf();
+    };
+    let from: Vec<Component> = from.components().collect();
+    let to_c: Vec<Component> = to.components().collect();
+    let common = from.iter().zip(&to_c).take_while(|(a, b)| a == b).count();
+    if common == 0 {
+        return to;
+    }
+    let mut rel = PathBuf::new();
+    for _ in common..from.len() {
+        rel.push("..");
+    }
+    for c in &to_c[common..] {
+        rel.push(c.as_os_str());
+    }
+    rel
+}
+
+// путь файла -> значение для src/href
+fn href_path(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            ' ' => out.push_str("%20"),
+            '#' => out.push_str("%23"),
+            '?' => out.push_str("%3F"),
+            '%' => out.push_str("%25"),
+            '"' => out.push_str("%22"),
+            '\\' => out.push('/'),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+// текст с разметкой: ссылки кликабельны, жирный/курсив/код сохраняются
+fn render_text(html: &mut String, v: &BorrowedValue) {
+    match v {
+        BorrowedValue::String(s) => html.push_str(&esc(s)),
+        BorrowedValue::Array(parts) => {
+            for part in parts.iter() {
+                match part {
+                    BorrowedValue::String(s) => html.push_str(&esc(s)),
+                    BorrowedValue::Object(obj) => render_entity(html, obj),
+                    _ => {}
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+const SAFE_SCHEMES: [&str; 4] = ["http", "https", "tg", "mailto"];
+
+/// Адрес для href: без схемы — https://, со схемой — только из SAFE_SCHEMES.
+fn safe_href(url: &str) -> Option<String> {
+    let scheme = url.split_once(':').map(|(s, _)| s).filter(|s| {
+        s.starts_with(|c: char| c.is_ascii_alphabetic())
+            && s.chars().all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+    });
+    match scheme {
+        None => Some(format!("https://{url}")),
+        Some(s) if SAFE_SCHEMES.iter().any(|safe| s.eq_ignore_ascii_case(safe)) => {
+            Some(url.to_string())
+        }
+        Some(_) => None,
+    }
+}
+
+fn render_entity(html: &mut String, obj: &Object) {
+    let text = esc(get_str_field(obj, "text").unwrap_or(""));
+    let _ = match get_str_field(obj, "type").unwrap_or("") {
+        kind @ ("link" | "text_link") => {
+            let url = get_str_field(obj, if kind == "link" { "text" } else { "href" });
+            match url.and_then(safe_href) {
+                Some(href) => write!(html, "<a href=\"{}\">{text}</a>", esc(&href)),
+                None => write!(html, "{text}"),
+            }
+        }
+        "email" => write!(html, "<a href=\"mailto:{text}\">{text}</a>"),
+        "bold" => write!(html, "<b>{text}</b>"),
+        "italic" => write!(html, "<i>{text}</i>"),
+        "underline" => write!(html, "<u>{text}</u>"),
+        "strikethrough" => write!(html, "<s>{text}</s>"),
+        "code" => write!(html, "<code>{text}</code>"),
+        "pre" => write!(html, "<pre>{text}</pre>"),
+        "spoiler" => write!(html, "<span title=\"спойлер\">░{text}░</span>"),
+        _ => write!(html, "{text}"),
+    };
+}
+
+impl HtmlChat {
+    pub fn create(out_dir: &Path, export_dir: &Path) -> io::Result<Self> {
+        std::fs::create_dir_all(out_dir)?;
+        let rel = relative_path(out_dir, export_dir);
+        let mut media_prefix = href_path(&rel.to_string_lossy());
+        if !media_prefix.is_empty() && !media_prefix.ends_with('/') {
+            media_prefix.push('/');
+        }
+        Ok(Self {
+            out_dir: out_dir.to_path_buf(),
+            media_prefix,
+            page: None,
+            pages: Vec::new(),
+        })
+    }
+
+    /// Страница для (чат, месяц): текущая или новая.
+    fn page_for(&mut self, chat: &str, date: Option<NaiveDateTime>) -> io::Result<&mut Page> {
+        let month = match date {
+            Some(dt) => dt.format("%Y-%m").to_string(),
+            None => "без-даты".to_string(),
+        };
+        let same = self
+            .page
+            .as_ref()
+            .is_some_and(|p| p.chat == chat && p.month == month);
+        if !same {
+            let file = format!("{}_{}.html", safe_file_name(chat), month);
+            if let Some(mut page) = self.page.take() {
+                page.w.flush()?;
+            }
+
+            // экспорт обычно по порядку, но если месяц уже встречался — дописываем
+            let seen = self.pages.iter().any(|p| p.file == file);
+            let path = self.out_dir.join(&file);
+            let mut w = if seen {
+                BufWriter::new(OpenOptions::new().append(true).open(&path)?)
+            } else {
+                BufWriter::new(File::create(&path)?)
+            };
+            if !seen {
+                write_page_header(&mut w, chat, &month)?;
+                self.pages.push(PageInfo {
+                    chat: chat.to_string(),
+                    month: month.clone(),
+                    file: file.clone(),
+                    messages: 0,
+                });
+            }
+            self.page = Some(Page {
+                w,
+                chat: chat.to_string(),
+                month,
+                file,
+            });
+        }
+        let page = self.page.as_mut().expect("страница только что открыта");
+        if let Some(info) = self.pages.iter_mut().find(|p| p.file == page.file) {
+            info.messages += 1;
+        }
+        Ok(page)
+    }
+
+    pub fn message(
+        &mut self,
+        chat: &str,
+        msg: &Message,
+        author: &str,
+        date: Option<NaiveDateTime>,
+        msg_obj: &Object,
+    ) -> io::Result<()> {
+        let mut html = String::new();
+        let id = get_i64_field(msg_obj, "id").unwrap_or(0);
+        let time = match date {
+            Some(dt) => dt.format("%Y-%m-%d %H:%M").to_string(),
+            None => esc(get_str_field(msg_obj, "date").unwrap_or("")),
+        };
+        let _ = write!(
+            html,
+            "<div class=\"msg\" id=\"m{id}\"><div class=\"meta\"><b>{}</b> {time} \
+             <a href=\"#m{id}\">#{id}</a></div>",
+            esc(author)
+        );
+
+        if let Some(from) = get_str_field(msg_obj, "forwarded_from") {
+            let _ = write!(html, "<div class=\"fwd\">переслано от {}</div>", esc(from));
+        }
+        if let Some(reply_to) = get_i64_field(msg_obj, "reply_to_message_id") {
+            let _ = write!(
+                html,
+                "<div class=\"reply\">↳ ответ на <a href=\"#m{reply_to}\">#{reply_to}</a></div>"
+            );
+        }
+
+        if let Some(kind) = msg.media_kind() {
+            html.push_str("<div class=\"media\">");
+            self.render_media(&mut html, kind, msg_obj);
+            html.push_str("</div>");
+        }
+
+        if let Some(text_val) = msg_obj.get("text") {
+            html.push_str("<div class=\"text\">");
+            render_text(&mut html, text_val);
+            html.push_str("</div>");
+        }
+        html.push_str("</div>\n");
+
+        let page = self.page_for(chat, date)?;
+        page.w.write_all(html.as_bytes())
+    }
+
+    pub fn service(
+        &mut self,
+        chat: &str,
+        date: Option<NaiveDateTime>,
+        msg_obj: &Object,
+    ) -> io::Result<()> {
+        let line = esc(&service::describe(msg_obj));
+        let page = self.page_for(chat, date)?;
+        writeln!(page.w, "<div class=\"service\">{line}</div>")
+    }
+
+    fn render_media(&self, html: &mut String, kind: &str, msg_obj: &Object) {
+        let path = get_str_field(msg_obj, "photo")
+            .or_else(|| get_str_field(msg_obj, "file"))
+            .unwrap_or("");
+        if kind == "poll" {
+            let q = msg_obj.get("poll").and_then(get_poll_question).unwrap_or("");
+            let _ = write!(html, "📊 опрос: {}", esc(q));
+            return;
+        }
+        if path.is_empty() || is_placeholder(path) {
+            let _ = write!(html, "<span class=\"missing\">[{kind}: файл не выгружен]</span>");
+            return;
+        }
+
+        let src = format!("{}{}", self.media_prefix, href_path(path));
+        let src = esc(&src);
+        let _ = match kind {
+            "photo" => write!(html, "<img src=\"{src}\" loading=\"lazy\" alt=\"фото\">"),
+            "sticker" if path.ends_with(".webm") => write!(
+                html,
+                "<video class=\"sticker\" src=\"{src}\" autoplay loop muted></video>"
+            ),
+            "sticker" if path.ends_with(".tgs") => {
+                let emoji = get_str_field(msg_obj, "sticker_emoji").unwrap_or("");
+                write!(html, "<a href=\"{src}\">[стикер {}]</a>", esc(emoji))
+            }
+            "sticker" => write!(html, "<img class=\"sticker\" src=\"{src}\" loading=\"lazy\">"),
+            "voice_message" | "audio_file" => {
+                write!(html, "<audio controls preload=\"none\" src=\"{src}\"></audio>")
+            }
+            "animation" => write!(html, "<video src=\"{src}\" autoplay loop muted></video>"),
+            "video_file" | "video_message" => {
+                write!(html, "<video controls preload=\"none\" src=\"{src}\"></video>")
+            }
+            _ => {
+                let name = Path::new(path)
+                    .file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                write!(html, "📎 <a href=\"{src}\">{}</a>", esc(&name))
+            }
+        };
+    }
+
+    /// Дописывает подвалы страниц и index.html; возвращает число страниц.
+    pub fn finish(mut self) -> io::Result<usize> {
+        if let Some(mut page) = self.page.take() {
+            page.w.flush()?;
+        }
+        // чаты — в порядке появления, месяцы внутри чата — по порядку
+        let chat_order = |chat: &str| self.pages.iter().position(|p| p.chat == chat);
+        let mut order: Vec<(Option<usize>, &str, usize)> = self
+            .pages
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (chat_order(&p.chat), p.month.as_str(), i))
+            .collect();
+        order.sort();
+        let pages: Vec<&PageInfo> = order.iter().map(|&(_, _, i)| &self.pages[i]).collect();
+        for (i, page) in pages.iter().enumerate() {
+            let neighbour = |j: Option<usize>| {
+                j.and_then(|j| pages.get(j))
+                    .filter(|p| p.chat == page.chat)
+                    .map(|p| p.file.as_str())
+            };
+            let prev = neighbour(i.checked_sub(1));
+            let next = neighbour(Some(i + 1));
+            let mut w = OpenOptions::new().append(true).open(self.out_dir.join(&page.file))?;
+            write_page_footer(&mut w, prev, next)?;
+        }
+
+        let mut w = BufWriter::new(File::create(self.out_dir.join("index.html"))?);
+        writeln!(w, "<!DOCTYPE html>\n<html lang=\"ru\">\n<head>\n<meta charset=\"utf-8\">")?;
+        writeln!(w, "<title>Переписка</title>\n<style>{STYLE}</style>\n</head>\n<body>\n<main>")?;
+        let mut chat: Option<&str> = None;
+        for p in pages {
+            if chat != Some(p.chat.as_str()) {
+                if chat.is_some() {
+                    writeln!(w, "</ul>")?;
+                }
+                writeln!(w, "<h2>{}</h2>\n<ul class=\"months\">", esc(&p.chat))?;
+                chat = Some(&p.chat);
+            }
+            writeln!(
+                w,
+                "<li><a href=\"{}\">{}</a> ({})</li>",
+                href_path(&p.file),
+                p.month,
+                p.messages
+            )?;
+        }
+        if chat.is_some() {
+            writeln!(w, "</ul>")?;
+        }
+        writeln!(w, "</main>\n</body>\n</html>")?;
+        w.flush()?;
+        Ok(self.pages.len())
+    }
+}
+
+fn write_page_header<W: Write>(w: &mut W, chat: &str, month: &str) -> io::Result<()> {
+    let title = format!("{} — {}", esc(chat), month);
+    writeln!(w, "<!DOCTYPE html>\n<html lang=\"ru\">\n<head>\n<meta charset=\"utf-8\">")?;
+    writeln!(w, "<title>{title}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n<main>")?;
+    writeln!(w, "<h1>{title}</h1>")?;
+    writeln!(w, "<nav><a href=\"index.html\">оглавление</a></nav>")
+}
+
+// соседние месяцы того же чата
+fn write_page_footer<W: Write>(
+    w: &mut W,
+    prev: Option<&str>,
+    next: Option<&str>,
+) -> io::Result<()> {
+    write!(w, "<nav>")?;
+    if let Some(prev) = prev {
+        write!(w, "<a href=\"{}\">← предыдущий месяц</a>", href_path(prev))?;
+    }
+    write!(w, "<a href=\"index.html\">оглавление</a>")?;
+    if let Some(next) = next {
+        write!(w, "<a href=\"{}\">следующий месяц →</a>", href_path(next))?;
+    }
+    writeln!(w, "</nav>\n</main>\n</body>\n</html>")
+}
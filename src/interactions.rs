@@ -0,0 +1,224 @@
+//
+// ===================== ОТВЕТЫ МЕЖДУ УЧАСТНИКАМИ =====================
+//
+// По reply_to_message_id: кто кому отвечает и как быстро. Для этого
+// держим свой индекс id -> (автор, время); как и в replies.rs, имена
+// хранятся один раз, а id уникальны только в пределах чата.
+// Ответы самому себе не считаются.
+//
+// Из тех же пар — матрица "кто кому отвечает" и самые тесные пары
+// (ответы в обе стороны вместе).
+//
+
+use ahash::AHashMap;
+use chrono::NaiveDateTime;
+use simd_json::borrowed::Object;
+
+use std::io::{self, Write};
+
+use crate::durations::format_duration;
+use crate::get_i64_field;
+use crate::lengths::Distribution;
+
+const TOP_LATENCY_PAIRS: usize = 20;
+// пары с меньшим числом ответов в таблице задержек — шум
+const MIN_PAIR_REPLIES: u64 = 3;
+const TOP_REPLY_PAIRS: usize = 15;
+const MATRIX_SIZE: usize = 8;
+const MATRIX_NAME_CHARS: usize = 20;
+
+/// Пара участников и ответы в обе стороны.
+pub struct ReplyPair<'a> {
+    pub a: &'a str,
+    pub b: &'a str,
+    pub a_to_b: u64,
+    pub b_to_a: u64,
+}
+
+impl ReplyPair<'_> {
+    pub fn total(&self) -> u64 {
+        self.a_to_b + self.b_to_a
+    }
+}
+
+#[derive(Default)]
+pub struct InteractionStats {
+    names: Vec<String>,
+    name_ids: AHashMap<String, u32>,
+    // id -> (автор, unix-время) в текущем чате
+    by_id: AHashMap<i64, (u32, i64)>,
+    // (кто ответил, кому) -> задержки ответов в секундах
+    pairs: AHashMap<(u32, u32), Distribution>,
+}
+
+crate::cache::codec!(InteractionStats { names, name_ids, by_id, pairs });
+
+impl InteractionStats {
+    pub fn new_chat(&mut self) {
+        self.by_id.clear();
+    }
+
+    fn name_id(&mut self, author: &str) -> u32 {
+        match self.name_ids.get(author) {
+            Some(&idx) => idx,
+            None => {
+                let idx = self.names.len() as u32;
+                self.names.push(author.to_string());
+                self.name_ids.insert(author.to_string(), idx);
+                idx
+            }
+        }
+    }
+
+    /// `unixtime` — date_unixtime, если есть; иначе время из даты сообщения.
+    pub fn observe(
+        &mut self,
+        author: &str,
+        unixtime: Option<i64>,
+        date: Option<NaiveDateTime>,
+        msg_obj: &Object,
+    ) {
+        let Some(ts) = unixtime.or_else(|| date.map(|d| d.and_utc().timestamp())) else {
+            return;
+        };
+        let me = self.name_id(author);
+        if let Some(reply_to) = get_i64_field(msg_obj, "reply_to_message_id")
+            && let Some(&(target, target_ts)) = self.by_id.get(&reply_to)
+            && target != me
+        {
+            let delay = (ts - target_ts).clamp(0, u32::MAX as i64) as u32;
+            self.pairs.entry((me, target)).or_default().add(delay);
+        }
+        if let Some(id) = get_i64_field(msg_obj, "id") {
+            self.by_id.insert(id, (me, ts));
+        }
+    }
+
+    /// Кто отвечает -> все его задержки, по числу ответов.
+    pub fn repliers(&self) -> Vec<(&str, Distribution)> {
+        let mut per: AHashMap<u32, Distribution> = AHashMap::new();
+        for (&(from, _), d) in &self.pairs {
+            per.entry(from).or_default().merge(d);
+        }
+        let mut v: Vec<_> = per
+            .into_iter()
+            .map(|(from, d)| (self.names[from as usize].as_str(), d))
+            .collect();
+        v.sort_by_key(|p| std::cmp::Reverse(p.1.count()));
+        v
+    }
+
+    /// (кто ответил, кому, задержки), по числу ответов.
+    pub fn pairs(&self) -> Vec<(&str, &str, &Distribution)> {
+        let mut v: Vec<_> = self
+            .pairs
+            .iter()
+            .map(|(&(from, to), d)| {
+                (self.names[from as usize].as_str(), self.names[to as usize].as_str(), d)
+            })
+            .collect();
+        v.sort_by(|a, b| b.2.count().cmp(&a.2.count()).then_with(|| a.0.cmp(b.0)));
+        v
+    }
+
+    fn count(&self, from: u32, to: u32) -> u64 {
+        self.pairs.get(&(from, to)).map_or(0, |d| d.count())
+    }
+
+    /// Неупорядоченные пары по сумме ответов друг другу.
+    pub fn strongest_pairs(&self) -> Vec<ReplyPair<'_>> {
+        let mut v: Vec<ReplyPair> = self
+            .pairs
+            .keys()
+            .filter(|&&(from, to)| from < to || !self.pairs.contains_key(&(to, from)))
+            .map(|&(from, to)| {
+                let (a, b) = (from.min(to), from.max(to));
+                ReplyPair {
+                    a: &self.names[a as usize],
+                    b: &self.names[b as usize],
+                    a_to_b: self.count(a, b),
+                    b_to_a: self.count(b, a),
+                }
+            })
+            .collect();
+        v.sort_by(|x, y| y.total().cmp(&x.total()).then_with(|| x.a.cmp(y.a)));
+        v
+    }
+
+    pub fn write_matrix<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(w, "Кто кому отвечает — самые тесные пары:")?;
+        for p in self.strongest_pairs().into_iter().take(TOP_REPLY_PAIRS) {
+            writeln!(
+                w,
+                "- {} ↔ {}: {} ({} → {}: {}, обратно: {})",
+                p.a,
+                p.b,
+                p.total(),
+                p.a,
+                p.b,
+                p.a_to_b,
+                p.b_to_a
+            )?;
+        }
+
+        // самые "отвечающие" участники: ответы данные + полученные
+        let mut activity: AHashMap<u32, u64> = AHashMap::new();
+        for (&(from, to), d) in &self.pairs {
+            *activity.entry(from).or_insert(0) += d.count();
+            *activity.entry(to).or_insert(0) += d.count();
+        }
+        let mut people: Vec<(u32, u64)> = activity.into_iter().collect();
+        people.sort_by_key(|p| std::cmp::Reverse(p.1));
+        people.truncate(MATRIX_SIZE);
+
+        writeln!(w)?;
+        writeln!(w, "Матрица ответов (строка отвечает столбцу):")?;
+        for (i, &(id, _)) in people.iter().enumerate() {
+            writeln!(w, "  [{}] {}", i + 1, self.names[id as usize])?;
+        }
+        write!(w, "  {:<width$}", "", width = MATRIX_NAME_CHARS)?;
+        for i in 0..people.len() {
+            write!(w, " {:>5}", format!("[{}]", i + 1))?;
+        }
+        writeln!(w)?;
+        for &(row, _) in &people {
+            let short: String = self.names[row as usize].chars().take(MATRIX_NAME_CHARS).collect();
+            write!(w, "  {:<width$}", short, width = MATRIX_NAME_CHARS)?;
+            for &(col, _) in &people {
+                match self.count(row, col) {
+                    0 => write!(w, " {:>5}", "·")?,
+                    c => write!(w, " {:>5}", c)?,
+                }
+            }
+            writeln!(w)?;
+        }
+        Ok(())
+    }
+
+    pub fn write_latency<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(w, "Скорость ответов на чужие сообщения (медиана / 90-й перцентиль):")?;
+        for (author, d) in self.repliers() {
+            writeln!(
+                w,
+                "- {}: {} / {} (ответов: {})",
+                author,
+                format_duration(d.median() as u64),
+                format_duration(d.percentile(0.9) as u64),
+                d.count()
+            )?;
+        }
+        writeln!(w, "  по парам «кто → кому» (от {} ответов):", MIN_PAIR_REPLIES)?;
+        let pairs = self.pairs().into_iter().filter(|p| p.2.count() >= MIN_PAIR_REPLIES);
+        for (from, to, d) in pairs.take(TOP_LATENCY_PAIRS) {
+            writeln!(
+                w,
+                "  - {} → {}: медиана {} (ответов: {})",
+                from,
+                to,
+                format_duration(d.median() as u64),
+                d.count()
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,246 @@
+//
+// ===================== ДЛИНА СООБЩЕНИЙ =====================
+//
+// Распределения длин в символах и словах, в целом и по авторам.
+// Храним не сами длины, а "длина -> сколько раз": различных длин немного
+// (текст в Telegram — до 4096 символов), так что медиана и перцентили
+// точные при любом размере чата.
+//
+
+use ahash::AHashMap;
+use simd_json::BorrowedValue;
+
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use crate::model::Message;
+use crate::{Metric, for_each_text_segment, percent_of};
+
+const TOP_LENGTH_AUTHORS: usize = 20;
+
+/// Границы корзин гистограммы (включительно), по символам.
+pub const LENGTH_BUCKETS: [(u32, u32); 7] = [
+    (1, 10),
+    (11, 30),
+    (31, 80),
+    (81, 200),
+    (201, 500),
+    (501, 1000),
+    (1001, u32::MAX),
+];
+
+#[derive(Default)]
+pub struct Distribution {
+    freq: BTreeMap<u32, u64>,
+    count: u64,
+    sum: u64,
+}
+
+crate::cache::codec!(Distribution { freq, count, sum });
+
+impl Distribution {
+    pub fn add(&mut self, v: u32) {
+        *self.freq.entry(v).or_insert(0) += 1;
+        self.count += 1;
+        self.sum += v as u64;
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn sum(&self) -> u64 {
+        self.sum
+    }
+
+    pub fn min(&self) -> u32 {
+        self.freq.keys().next().copied().unwrap_or(0)
+    }
+
+    pub fn max(&self) -> u32 {
+        self.freq.keys().next_back().copied().unwrap_or(0)
+    }
+
+    pub fn mean(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            self.sum as f64 / self.count as f64
+        }
+    }
+
+    /// Наименьшее значение, не меньше которого `p` (0..=1) всех наблюдений.
+    pub fn percentile(&self, p: f64) -> u32 {
+        if self.count == 0 {
+            return 0;
+        }
+        let rank = ((p * self.count as f64).ceil() as u64).max(1);
+        let mut seen = 0u64;
+        for (&v, &n) in &self.freq {
+            seen += n;
+            if seen >= rank {
+                return v;
+            }
+        }
+        self.max()
+    }
+
+    pub fn median(&self) -> u32 {
+        self.percentile(0.5)
+    }
+
+    /// Добавить все наблюдения другого распределения.
+    pub fn merge(&mut self, other: &Distribution) {
+        for (&v, &n) in &other.freq {
+            *self.freq.entry(v).or_insert(0) += n;
+        }
+        self.count += other.count;
+        self.sum += other.sum;
+    }
+
+    /// Сколько значений попало в [lo, hi].
+    pub fn count_in(&self, lo: u32, hi: u32) -> u64 {
+        self.freq.range(lo..=hi).map(|(_, &n)| n).sum()
+    }
+}
+
+#[derive(Default)]
+pub struct LengthStats {
+    pub chars: Distribution,
+    pub words: Distribution,
+    // автор -> (символы, слова)
+    pub per_author: AHashMap<String, (Distribution, Distribution)>,
+}
+
+crate::cache::codec!(LengthStats { chars, words, per_author });
+
+/// (символов, слов) в тексте сообщения; сегменты склеиваются без пробелов.
+pub fn text_length(v: &BorrowedValue) -> (u32, u32) {
+    let mut chars = 0u32;
+    let mut words = 0u32;
+    let mut in_word = false;
+    for_each_text_segment(v, |s| {
+        for c in s.chars() {
+            chars += 1;
+            if c.is_whitespace() {
+                in_word = false;
+            } else if !in_word {
+                in_word = true;
+                words += 1;
+            }
+        }
+    });
+    (chars, words)
+}
+
+impl LengthStats {
+    /// Сложить распределения длин — общие и каждого автора.
+    pub fn merge(&mut self, other: LengthStats) {
+        self.chars.merge(&other.chars);
+        self.words.merge(&other.words);
+        for (author, (chars, words)) in other.per_author {
+            let entry = self.per_author.entry(author).or_default();
+            entry.0.merge(&chars);
+            entry.1.merge(&words);
+        }
+    }
+
+    /// Авторы по числу сообщений с текстом.
+    pub fn authors(&self) -> Vec<(&str, &Distribution, &Distribution)> {
+        let mut v: Vec<_> = self
+            .per_author
+            .iter()
+            .map(|(a, (c, w))| (a.as_str(), c, w))
+            .collect();
+        v.sort_by_key(|p| std::cmp::Reverse(p.1.count()));
+        v
+    }
+
+    /// Авторы по числу написанных слов: (автор, сообщений с текстом, слов).
+    pub fn authors_by_words(&self) -> Vec<(&str, u64, u64)> {
+        let mut v: Vec<_> = self
+            .per_author
+            .iter()
+            .map(|(a, (_, w))| (a.as_str(), w.count(), w.sum()))
+            .collect();
+        v.sort_by_key(|p| std::cmp::Reverse(p.2));
+        v
+    }
+
+    pub fn write_words_per_author<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(w, "Слова по участникам (всего / в среднем на сообщение):")?;
+        for (author, messages, words) in self.authors_by_words() {
+            let avg = if messages > 0 { words as f64 / messages as f64 } else { 0.0 };
+            writeln!(w, "- {}: {} слов / {:.1}", author, words, avg)?;
+        }
+        Ok(())
+    }
+}
+
+impl Metric for LengthStats {
+    fn observe(&mut self, msg: &Message) {
+        let Some(text_val) = msg.text_value() else {
+            return;
+        };
+        let author = msg.author();
+        let author = author.as_ref();
+        let (chars, words) = text_length(text_val);
+        self.chars.add(chars);
+        self.words.add(words);
+        let entry = match self.per_author.get_mut(author) {
+            Some(e) => e,
+            None => self.per_author.entry(author.to_string()).or_default(),
+        };
+        entry.0.add(chars);
+        entry.1.add(words);
+    }
+
+    fn report(&self, w: &mut dyn Write) -> io::Result<()> {
+        let (c, wd) = (&self.chars, &self.words);
+        writeln!(w, "Длина сообщений (символы / слова), сообщений с текстом: {}", c.count())?;
+        if c.count() == 0 {
+            return Ok(());
+        }
+        writeln!(
+            w,
+            "  мин {} / {}, медиана {} / {}, среднее {:.1} / {:.1}, 95% {} / {}, макс {} / {}",
+            c.min(),
+            wd.min(),
+            c.median(),
+            wd.median(),
+            c.mean(),
+            wd.mean(),
+            c.percentile(0.95),
+            wd.percentile(0.95),
+            c.max(),
+            wd.max()
+        )?;
+
+        writeln!(w, "  распределение по длине (символов):")?;
+        for (lo, hi) in LENGTH_BUCKETS {
+            let n = c.count_in(lo, hi);
+            let percent = percent_of(n as usize, c.count() as usize);
+            if hi == u32::MAX {
+                writeln!(w, "  - {}+: {} ({:.1}%)", lo, n, percent)?;
+            } else {
+                writeln!(w, "  - {}–{}: {} ({:.1}%)", lo, hi, n, percent)?;
+            }
+        }
+
+        writeln!(w, "  по авторам (символы: медиана / среднее / 95%; слова — так же):")?;
+        for (author, c, wd) in self.authors().into_iter().take(TOP_LENGTH_AUTHORS) {
+            writeln!(
+                w,
+                "  - {}: {} / {:.1} / {} (слов: {} / {:.1} / {})",
+                author,
+                c.median(),
+                c.mean(),
+                c.percentile(0.95),
+                wd.median(),
+                wd.mean(),
+                wd.percentile(0.95)
+            )?;
+        }
+        Ok(())
+    }
+}
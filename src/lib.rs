@@ -0,0 +1,242 @@
+//! Разбор экспорта Telegram (result.json из Telegram Desktop) и статистика
+//! по нему — то же, что считает утилита tgjsps, но без запуска бинарника.
+//!
+//! ```no_run
+//! use std::fs::File;
+//! use tgjsps::{Analyzer, Options};
+//!
+//! let options = Options { verbose: true, ..Options::default() };
+//! let stats = Analyzer::new(options).process(File::open("result.json")?)?;
+//! println!("{}: {} сообщений", stats.chat_name(), stats.total_messages());
+//! for (author, count) in stats.per_author() {
+//!     println!("{author}: {count}");
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! Свою статистику можно добавить к встроенной — [`Metric`] и
+//! [`Analyzer::metric`]. Для своих подсчётов без загрузки всего файла в
+//! память есть [`MessageIter`]: он отдаёт сообщения по одному.
+
+mod anonymize;
+mod append;
+mod approx;
+mod arrow_out;
+mod authors;
+mod cache;
+mod calls;
+mod charts;
+mod commands;
+mod config;
+mod context;
+mod counters;
+mod custom_emoji;
+mod diff;
+mod durations;
+mod extract;
+mod entities;
+mod filter;
+mod forwards;
+mod graph;
+mod gzip;
+mod heatmap;
+mod html_chat;
+mod interactions;
+mod lengths;
+mod links;
+mod log_out;
+mod log_template;
+mod logger;
+mod manifest;
+mod media;
+mod mentions;
+mod merge;
+mod metric;
+mod mmap;
+mod parallel;
+mod pins;
+mod presence;
+mod questions;
+mod reactions;
+mod redact;
+mod replies;
+mod report;
+mod script;
+mod sentiment;
+mod service;
+mod sqlite;
+mod stat_csv;
+mod stem;
+mod stat_json;
+mod stickers;
+mod stopwords;
+mod stream;
+mod style;
+mod table;
+mod timeline;
+mod timings;
+mod tui;
+mod unique;
+mod unpack;
+mod watch;
+mod vocab;
+mod wordlist;
+
+#[doc(hidden)]
+pub mod cli;
+pub mod model;
+pub mod output;
+pub mod parser;
+pub mod stats;
+
+pub use metric::Metric;
+pub use model::Message;
+pub use parser::{ChatMessage, MessageIter};
+pub use stats::{SpamConfig, Stats};
+
+pub(crate) use output::MessageRecord;
+pub(crate) use parser::{
+    NO_CHAT_NAME, STDIO, author_name, discover_export, expand_glob, for_each_dom_chat,
+    for_each_entity, for_each_text_segment, get_i64_field, get_poll_question, get_str_field,
+    read_input, safe_file_name, value_to_id,
+};
+pub(crate) use stats::{
+    TOP_COPYPASTA, WEEKDAYS, WEEKDAYS_SHORT, add_counts, build_full_text, copypasta, fast_tokenize,
+    observe_text, percent_of, sorted_by_count, spam_key, spam_repeats, spam_scores, top_by_count,
+    top_n_by, trim_ascii_punct,
+};
+
+use chrono::NaiveDateTime;
+use chrono_tz::Tz;
+
+use std::error::Error;
+use std::io::Read;
+use std::path::PathBuf;
+
+//
+// ===================== БИБЛИОТЕКА =====================
+//
+
+/// Что и как считать. `Default` — как у tgjsps без флагов.
+#[derive(Clone, Debug)]
+pub struct Options {
+    /// Расширенная статистика, как -v: слова, активность, реакции...
+    pub verbose: bool,
+    /// Длина топа слов (--top-words)
+    pub top_words: usize,
+    /// Приблизительный подсчёт слов и спама с таким пределом словарей (--approx)
+    pub approx: Option<usize>,
+    /// Поиск повторяющихся сообщений (--spam-*)
+    pub spam: SpamConfig,
+    /// Встроенный список стоп-слов; false — как --no-stopwords
+    pub stopwords: bool,
+    /// Свои стоп-слова, по слову на строку (--stopwords)
+    pub stopwords_file: Option<PathBuf>,
+    /// Считать основы слов, а не словоформы (--stem)
+    pub stem: bool,
+    /// Часовой пояс для часов и дней (--timezone); None — даты как в экспорте
+    pub timezone: Option<Tz>,
+    /// Только сообщения с этого момента (--since)
+    pub since: Option<NaiveDateTime>,
+    /// Только сообщения до этого момента (--until)
+    pub until: Option<NaiveDateTime>,
+    /// Только эти авторы, по имени или from_id (--only-author)
+    pub only_authors: Vec<String>,
+    /// Без этих авторов (--exclude-author)
+    pub exclude_authors: Vec<String>,
+    /// Без ботов (--no-bots)
+    pub no_bots: bool,
+    /// Свои боты, по имени или from_id (--bot)
+    pub bots: Vec<String>,
+    /// Только сообщения с этим текстом (--grep)
+    pub grep: Option<String>,
+    /// `grep` — регулярное выражение (--regex)
+    pub regex: bool,
+    /// Тематические словари (--wordlist)
+    pub wordlists: Vec<PathBuf>,
+    /// Счётчики регулярных выражений: (имя, выражение) (--counter, [counters])
+    pub counters: Vec<(String, String)>,
+    /// Словарная тональность (--sentiment) и свой словарь к ней (--lexicon)
+    pub sentiment: bool,
+    pub lexicon: Option<PathBuf>,
+    /// С какой доли пересланного автор — пересыльщик (--forward-spam-ratio)
+    pub forward_spam_ratio: f64,
+    /// Псевдонимы вместо имён и id (--anonymize)
+    pub anonymize: bool,
+    /// Авторы по имени, а не по from_id (--by-name)
+    pub by_name: bool,
+    /// История закрепов (--pins)
+    pub pins: bool,
+    /// Потоков для текстовой статистики (--threads)
+    pub threads: usize,
+    /// Чат из полного экспорта, по имени или id (--chat); None — все
+    pub chat: Option<String>,
+    /// Папка с файлами медиа для их размеров (--media-dir)
+    pub media_dir: Option<PathBuf>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            verbose: false,
+            top_words: stats::TOP_WORDS,
+            approx: None,
+            spam: SpamConfig::default(),
+            stopwords: true,
+            stopwords_file: None,
+            stem: false,
+            timezone: None,
+            since: None,
+            until: None,
+            only_authors: Vec::new(),
+            exclude_authors: Vec::new(),
+            no_bots: false,
+            bots: Vec::new(),
+            grep: None,
+            regex: false,
+            wordlists: Vec::new(),
+            counters: Vec::new(),
+            sentiment: false,
+            lexicon: None,
+            forward_spam_ratio: forwards::DEFAULT_SPAM_RATIO,
+            anonymize: false,
+            by_name: false,
+            pins: false,
+            threads: 1,
+            chat: None,
+            media_dir: None,
+        }
+    }
+}
+
+/// Разбор экспорта с заданными настройками.
+pub struct Analyzer {
+    options: Options,
+    metrics: Vec<Box<dyn Metric>>,
+}
+
+impl Analyzer {
+    pub fn new(options: Options) -> Self {
+        Self { options, metrics: Vec::new() }
+    }
+
+    /// Добавить свою метрику; после разбора она в [`Stats::metrics`] и в
+    /// конце [`Stats::write_text`].
+    pub fn metric(mut self, metric: impl Metric + 'static) -> Self {
+        self.metrics.push(Box::new(metric));
+        self
+    }
+
+    /// Разобрать экспорт из `reader`: result.json одного чата или полного
+    /// экспорта, можно в .gz или .zip. Читается в память целиком.
+    pub fn process(self, mut reader: impl Read) -> Result<Stats, Box<dyn Error>> {
+        let mut proc = stats::Processor::new(&self.options)?;
+        proc.stats.metrics.0 = self.metrics;
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf)?;
+        let mut buf = unpack::unpack_bytes(buf, "вход")?;
+        parser::run_dom(&mut buf, &mut proc)?;
+        proc.finish()?;
+        Ok(proc.stats)
+    }
+}
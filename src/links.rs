@@ -0,0 +1,209 @@
+//
+// ===================== ССЫЛКИ И ДОМЕНЫ =====================
+//
+// URL берутся из разметки: "link" — адрес прямо в тексте (бывает и без
+// схемы: github.com/x), "text_link" — адрес в "href" под словами.
+// В экспортах без разметки — http(s)://… из самого текста.
+//
+
+use ahash::{AHashMap, AHashSet};
+use chrono::NaiveDateTime;
+use simd_json::BorrowedValue;
+use simd_json::borrowed::Object;
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use crate::model::Message;
+use crate::{
+    Metric, add_counts, for_each_entity, for_each_text_segment, get_str_field, top_by_count,
+};
+
+const TOP_DOMAINS: usize = 20;
+const TOP_LINK_AUTHORS: usize = 15;
+const DOMAINS_PER_AUTHOR: usize = 5;
+
+/// Каждый URL сообщения.
+pub fn for_each_url<'a, F>(msg_obj: &'a Object, mut f: F)
+where
+    F: FnMut(&'a str),
+{
+    let has_markup = msg_obj.contains_key("text_entities")
+        || matches!(msg_obj.get("text"), Some(BorrowedValue::Array(_)));
+    if has_markup {
+        for_each_entity(msg_obj, |kind, text, obj| match kind {
+            "link" => f(text),
+            "text_link" => {
+                if let Some(href) = get_str_field(obj, "href") {
+                    f(href);
+                }
+            }
+            _ => {}
+        });
+        return;
+    }
+
+    if let Some(text_val) = msg_obj.get("text") {
+        for_each_text_segment(text_val, |s| scan_urls(s, &mut f));
+    }
+}
+
+// http(s)://… до пробела, без хвостовой пунктуации
+fn scan_urls<'a, F: FnMut(&'a str)>(s: &'a str, f: &mut F) {
+    let mut rest = s;
+    while let Some(pos) = rest.find("http") {
+        let tail = &rest[pos..];
+        let end = tail.find(char::is_whitespace).unwrap_or(tail.len());
+        let candidate = tail[..end].trim_end_matches(['.', ',', '!', '?', ')', ';', ':', '»', '"']);
+        if candidate.starts_with("http://") || candidate.starts_with("https://") {
+            f(candidate);
+        }
+        rest = &tail[end..];
+    }
+}
+
+/// Домен без "www." в нижнем регистре; None — не веб-ссылка (tg://, mailto:).
+pub fn url_domain(url: &str) -> Option<String> {
+    let rest = match url.find("://") {
+        Some(i) => {
+            let scheme = url[..i].to_ascii_lowercase();
+            if scheme != "http" && scheme != "https" {
+                return None;
+            }
+            &url[i + 3..]
+        }
+        None if url.contains(':') && !url.contains('.') => return None,
+        None => url,
+    };
+
+    let mut host = &rest[..rest.find(['/', '?', '#']).unwrap_or(rest.len())];
+    if let Some(at) = host.rfind('@') {
+        host = &host[at + 1..];
+    }
+    if let Some(colon) = host.find(':') {
+        host = &host[..colon];
+    }
+    let host = host.trim_end_matches('.').to_lowercase();
+    let host = host.strip_prefix("www.").unwrap_or(&host);
+    if !host.contains('.') {
+        return None;
+    }
+    Some(host.to_string())
+}
+
+#[derive(Default)]
+pub struct LinkStats {
+    pub total_urls: usize,
+    // домен -> сколько ссылок
+    pub domains: AHashMap<String, usize>,
+    // автор -> (домен -> сколько)
+    pub per_author: AHashMap<String, AHashMap<String, usize>>,
+}
+
+crate::cache::codec!(LinkStats { total_urls, domains, per_author });
+
+impl LinkStats {
+    /// Сложить число URL, счётчики доменов и домены каждого автора.
+    pub fn merge(&mut self, other: LinkStats) {
+        self.total_urls += other.total_urls;
+        add_counts(&mut self.domains, other.domains);
+        for (author, domains) in other.per_author {
+            add_counts(self.per_author.entry(author).or_default(), domains);
+        }
+    }
+
+    /// Авторы по числу ссылок (с доменом).
+    pub fn authors_by_links(&self) -> Vec<(&str, usize)> {
+        let mut v: Vec<_> = self
+            .per_author
+            .iter()
+            .map(|(a, m)| (a.as_str(), m.values().sum::<usize>()))
+            .collect();
+        v.sort_by_key(|p| std::cmp::Reverse(p.1));
+        v
+    }
+}
+
+impl Metric for LinkStats {
+    fn observe(&mut self, msg: &Message) {
+        let Some(msg_obj) = msg.raw else {
+            return;
+        };
+        let author = msg.author();
+        let author = author.as_ref();
+        for_each_url(msg_obj, |url| {
+            self.total_urls += 1;
+            let Some(domain) = url_domain(url) else {
+                return;
+            };
+            *self
+                .per_author
+                .entry(author.to_string())
+                .or_default()
+                .entry(domain.clone())
+                .or_insert(0) += 1;
+            *self.domains.entry(domain).or_insert(0) += 1;
+        });
+    }
+
+    fn report(&self, w: &mut dyn Write) -> io::Result<()> {
+        writeln!(w, "Ссылки: {}, доменов: {}", self.total_urls, self.domains.len())?;
+        if self.domains.is_empty() {
+            return Ok(());
+        }
+        writeln!(w, "  популярные домены:")?;
+        for (domain, count) in top_by_count(&self.domains, TOP_DOMAINS) {
+            writeln!(w, "  - {}: {}", domain, count)?;
+        }
+        writeln!(w, "  по авторам:")?;
+        for (author, total) in self.authors_by_links().into_iter().take(TOP_LINK_AUTHORS) {
+            let top: Vec<String> = top_by_count(&self.per_author[author], DOMAINS_PER_AUTHOR)
+                .into_iter()
+                .map(|(d, c)| format!("{d} ({c})"))
+                .collect();
+            writeln!(w, "  - {}: {} — {}", author, total, top.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// --links: каждый URL один раз, "дата<TAB>автор<TAB>url" — по первому
+/// появлению; пишется по ходу разбора.
+pub struct LinkDump {
+    out: BufWriter<File>,
+    seen: AHashSet<String>,
+}
+
+impl LinkDump {
+    pub fn create(path: &str) -> io::Result<Self> {
+        Ok(Self {
+            out: BufWriter::new(File::create(path)?),
+            seen: AHashSet::new(),
+        })
+    }
+
+    pub fn observe(
+        &mut self,
+        author: &str,
+        date: Option<NaiveDateTime>,
+        msg_obj: &Object,
+    ) -> io::Result<()> {
+        let mut res = Ok(());
+        for_each_url(msg_obj, |url| {
+            if res.is_err() || self.seen.contains(url) {
+                return;
+            }
+            self.seen.insert(url.to_string());
+            let date = match date {
+                Some(dt) => dt.format("%Y-%m-%d %H:%M:%S").to_string(),
+                None => get_str_field(msg_obj, "date").unwrap_or("?").to_string(),
+            };
+            res = writeln!(self.out, "{date}\t{author}\t{url}");
+        });
+        res
+    }
+
+    pub fn finish(mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
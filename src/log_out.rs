@@ -0,0 +1,315 @@
+//
+// ===================== ФАЙЛ ЛОГА (--split-by, --max-output-size) =====================
+//
+// Обычно лог чата — один файл. С --split-by month (year) строки
+// раскладываются по файлам chat_2023-01.txt, chat_2023-02.txt, ... рядом
+// с основным: к имени дописывается период. Сообщения без даты идут в
+// текущий файл (а до первой даты — в chat_без-даты.txt).
+//
+// --split-by author — файл на участника (chat_Вася.txt) только с его
+// сообщениями; служебные события — в файл того, кто их совершил.
+//
+// --max-output-size: файл, доросший до лимита, продолжается в
+// chat.part2.txt, chat.part3.txt, ... (с --split-by — у каждой части
+// своя нумерация). Переход — только между строками, поэтому файл может
+// превысить лимит на одну строку.
+//
+// Путь, оканчивающийся на .gz, пишется сжатым; номер периода и части
+// встаёт перед расширением: chat_2023-01.txt.gz, chat.part2.txt.gz.
+//
+// С --append существующие файлы дописываются и при первом открытии.
+//
+// Открытыми держится не больше MAX_OPEN_FILES файлов: авторов бывают
+// тысячи. Файл, который уже создавался за этот запуск, дописывается, а не
+// перезаписывается.
+//
+
+use ahash::{AHashMap, AHashSet};
+use chrono::{Datelike, NaiveDateTime};
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::gzip::GzWriter;
+use crate::{STDIO, safe_file_name};
+
+const MAX_OPEN_FILES: usize = 64;
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SplitBy {
+    /// Файл на каждый месяц: <имя>_ГГГГ-ММ
+    Month,
+    /// Файл на каждый год: <имя>_ГГГГ
+    Year,
+    /// Файл на каждого участника: <имя>_<автор>
+    Author,
+}
+
+impl SplitBy {
+    /// Часть имени файла; None — дата неизвестна, остаёмся в текущем.
+    fn key(self, date: Option<NaiveDateTime>, author: &str) -> Option<String> {
+        match self {
+            SplitBy::Month => date.map(|dt| format!("{}-{:02}", dt.year(), dt.month())),
+            SplitBy::Year => date.map(|dt| dt.year().to_string()),
+            SplitBy::Author => Some(safe_file_name(author)),
+        }
+    }
+
+    /// Как называется часть имени файла — для итоговой строки.
+    pub fn label(self) -> &'static str {
+        match self {
+            SplitBy::Month | SplitBy::Year => "период",
+            SplitBy::Author => "автор",
+        }
+    }
+}
+
+/// "dir/chat.txt" + "_2023-01" -> "dir/chat_2023-01.txt"; ".gz" не считается
+/// расширением: "chat.txt.gz" -> "chat_2023-01.txt.gz".
+fn with_suffix(path: &str, suffix: &str) -> String {
+    let (path, gz) = match path.strip_suffix(".gz") {
+        Some(p) => (p, ".gz"),
+        None => (path, ""),
+    };
+    let p = Path::new(path);
+    let stem = p.file_stem().map(|s| s.to_string_lossy()).unwrap_or_default();
+    let file_name = match p.extension() {
+        Some(ext) => format!("{stem}{suffix}.{}{gz}", ext.to_string_lossy()),
+        None => format!("{stem}{suffix}{gz}"),
+    };
+    p.with_file_name(file_name).to_string_lossy().into_owned()
+}
+
+/// "dir/chat.txt" + "2023-01" -> "dir/chat_2023-01.txt".
+fn period_path(base: &str, key: &str) -> String {
+    with_suffix(base, &format!("_{key}"))
+}
+
+/// "dir/chat.txt" + 2 -> "dir/chat.part2.txt"; первая часть — без номера.
+fn part_path(path: &str, part: usize) -> String {
+    if part == 1 {
+        return path.to_string();
+    }
+    with_suffix(path, &format!(".part{part}"))
+}
+
+enum Sink {
+    Plain(BufWriter<File>),
+    Gzip(GzWriter<BufWriter<File>>),
+    Stdout(BufWriter<io::Stdout>),
+    Discard(io::Sink),
+}
+
+impl Sink {
+    fn writer(&mut self) -> &mut dyn Write {
+        match self {
+            Sink::Plain(w) => w,
+            Sink::Gzip(w) => w,
+            Sink::Stdout(w) => w,
+            Sink::Discard(w) => w,
+        }
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        match self {
+            Sink::Plain(w) => w.flush(),
+            Sink::Gzip(w) => w.finish(),
+            Sink::Stdout(w) => w.flush(),
+            Sink::Discard(_) => Ok(()),
+        }
+    }
+}
+
+struct OpenFile {
+    out: Sink,
+    // несжатых байт: лимит --max-output-size — про то, что откроет читатель
+    size: u64,
+}
+
+impl OpenFile {
+    /// Уже создававшийся за этот запуск файл (или любой с --append) дописывается.
+    fn open(path: &str, opened: &mut AHashSet<String>, append: bool) -> io::Result<Self> {
+        if path == STDIO {
+            return Ok(Self { out: Sink::Stdout(BufWriter::new(io::stdout())), size: 0 });
+        }
+        let file = if opened.contains(path) {
+            OpenOptions::new().append(true).open(path)?
+        } else if append {
+            opened.insert(path.to_string());
+            OpenOptions::new().create(true).append(true).open(path)?
+        } else {
+            opened.insert(path.to_string());
+            File::create(path)?
+        };
+        let size = file.metadata()?.len();
+        let out = if path.ends_with(".gz") {
+            Sink::Gzip(GzWriter::new(BufWriter::new(file))?)
+        } else {
+            Sink::Plain(BufWriter::new(file))
+        };
+        Ok(Self { out, size })
+    }
+}
+
+pub struct LogOut {
+    base: String,
+    split: Option<SplitBy>,
+    max_size: Option<u64>,
+    append: bool,
+    // файл без номера части, куда идёт текущая строка
+    logical: Option<String>,
+    // путь файла, куда идёт текущая строка
+    current: Option<String>,
+    files: AHashMap<String, OpenFile>,
+    // файл без номера части -> номер текущей части
+    parts: AHashMap<String, usize>,
+}
+
+impl LogOut {
+    /// Без разбиения файл создаётся сразу, с разбиением — по первой строке.
+    pub fn create(
+        path: &str,
+        split: Option<SplitBy>,
+        max_size: Option<u64>,
+        append: bool,
+        opened: &mut AHashSet<String>,
+    ) -> io::Result<Self> {
+        let mut log = Self {
+            base: path.to_string(),
+            split,
+            max_size,
+            append,
+            logical: None,
+            current: None,
+            files: AHashMap::new(),
+            parts: AHashMap::new(),
+        };
+        if split.is_none() {
+            log.files.insert(path.to_string(), OpenFile::open(path, opened, append)?);
+            log.logical = Some(path.to_string());
+            log.current = Some(path.to_string());
+        }
+        Ok(log)
+    }
+
+    /// Лог, который никуда не пишется: статистика без лога.
+    pub fn discard() -> Self {
+        let mut files = AHashMap::new();
+        files.insert(String::new(), OpenFile { out: Sink::Discard(io::sink()), size: 0 });
+        Self {
+            base: String::new(),
+            split: None,
+            max_size: None,
+            append: false,
+            logical: None,
+            current: Some(String::new()),
+            files,
+            parts: AHashMap::new(),
+        }
+    }
+
+    /// Переключает файл под очередную строку; `opened` — все файлы лога
+    /// за этот запуск (уже создававшиеся дописываются).
+    pub fn select(
+        &mut self,
+        date: Option<NaiveDateTime>,
+        author: &str,
+        opened: &mut AHashSet<String>,
+    ) -> io::Result<()> {
+        let logical = match self.split {
+            None if self.max_size.is_none() => return Ok(()),
+            None => self.base.clone(),
+            Some(split) => match (split.key(date, author), &self.logical) {
+                (Some(key), _) => period_path(&self.base, &key),
+                (None, Some(logical)) => logical.clone(),
+                (None, None) => period_path(&self.base, "без-даты"),
+            },
+        };
+
+        let mut part = match self.parts.get(&logical) {
+            Some(&part) => part,
+            None => {
+                // файл мог дорасти до частей ещё в прошлом чате (или запуске)
+                let mut part = 1;
+                let exists = |path: &str| {
+                    opened.contains(path) || (self.append && Path::new(path).exists())
+                };
+                while exists(&part_path(&logical, part + 1)) {
+                    part += 1;
+                }
+                part
+            }
+        };
+        let mut path = part_path(&logical, part);
+        self.switch(&path, opened)?;
+        if let Some(max) = self.max_size
+            && self.files[&path].size >= max
+        {
+            part += 1;
+            path = part_path(&logical, part);
+            self.switch(&path, opened)?;
+        }
+        self.parts.insert(logical.clone(), part);
+        self.logical = Some(logical);
+        Ok(())
+    }
+
+    fn switch(&mut self, path: &str, opened: &mut AHashSet<String>) -> io::Result<()> {
+        if self.current.as_deref() == Some(path) {
+            return Ok(());
+        }
+        if !self.files.contains_key(path) {
+            if self.files.len() >= MAX_OPEN_FILES {
+                self.close_all()?;
+            }
+            self.files.insert(path.to_string(), OpenFile::open(path, opened, self.append)?);
+        }
+        self.current = Some(path.to_string());
+        Ok(())
+    }
+
+    fn close_all(&mut self) -> io::Result<()> {
+        for file in self.files.values_mut() {
+            file.out.close()?;
+        }
+        self.files.clear();
+        Ok(())
+    }
+
+    /// Дописывает буферы и закрывает файлы (у .gz — с хвостом архива).
+    pub fn finish(mut self) -> io::Result<()> {
+        self.current = None;
+        self.close_all()
+    }
+
+    fn out(&mut self) -> io::Result<&mut OpenFile> {
+        self.current
+            .as_ref()
+            .and_then(|path| self.files.get_mut(path))
+            .ok_or_else(|| io::Error::other("файл лога не выбран"))
+    }
+}
+
+impl Write for LogOut {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let file = self.out()?;
+        let n = file.out.writer().write(buf)?;
+        file.size += n as u64;
+        Ok(n)
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        let file = self.out()?;
+        file.out.writer().write_all(buf)?;
+        file.size += buf.len() as u64;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        for file in self.files.values_mut() {
+            file.out.writer().flush()?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,103 @@
+//
+// ===================== ШАБЛОН СТРОКИ ЛОГА =====================
+//
+// `--format "{date} {name} ({from_id}): {text}"` разбирается один раз
+// в список кусков; `{{` и `}}` — буквальные скобки.
+//
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Field {
+    /// id сообщения
+    Id,
+    Date,
+    /// имя автора (`{name}` или `{author}`)
+    Name,
+    FromId,
+    /// заглушка вложения: [фото], [голосовое] ...
+    Media,
+    Text,
+    /// отметка ответа "↳ ответ <автор>: "
+    Reply,
+}
+
+#[derive(Debug)]
+pub enum Piece {
+    Lit(String),
+    Field(Field),
+}
+
+pub const FIELDS_HELP: &str = "id, date, name (author), from_id, media, text, reply";
+
+pub fn parse_template(s: &str) -> Result<Vec<Piece>, String> {
+    let mut pieces = Vec::new();
+    let mut lit = String::new();
+    let mut chars = s.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                lit.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                lit.push('}');
+            }
+            '{' => {
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => name.push(c),
+                        None => return Err(format!("незакрытая «{{» в шаблоне «{s}»")),
+                    }
+                }
+                let field = match name.trim() {
+                    "id" => Field::Id,
+                    "date" => Field::Date,
+                    "name" | "author" => Field::Name,
+                    "from_id" => Field::FromId,
+                    "media" => Field::Media,
+                    "text" => Field::Text,
+                    "reply" => Field::Reply,
+                    other => {
+                        return Err(format!(
+                            "неизвестное поле «{{{other}}}», доступны: {FIELDS_HELP}"
+                        ));
+                    }
+                };
+                if !lit.is_empty() {
+                    pieces.push(Piece::Lit(std::mem::take(&mut lit)));
+                }
+                pieces.push(Piece::Field(field));
+            }
+            '}' => return Err(format!("одиночная «}}» в шаблоне «{s}» (нужно «}}}}»)")),
+            _ => lit.push(c),
+        }
+    }
+    if !lit.is_empty() {
+        pieces.push(Piece::Lit(lit));
+    }
+    Ok(pieces)
+}
+
+pub fn uses_field(pieces: &[Piece], field: Field) -> bool {
+    pieces.iter().any(|p| matches!(p, Piece::Field(f) if *f == field))
+}
+
+/// Подпись вложения для `{media}`.
+pub fn media_placeholder(kind: &str) -> String {
+    let label = match kind {
+        "photo" => "фото",
+        "video_file" => "видео",
+        "video_message" => "кружок",
+        "voice_message" => "голосовое",
+        "audio_file" => "аудио",
+        "animation" => "GIF",
+        "sticker" => "стикер",
+        "file" => "файл",
+        "poll" => "опрос",
+        other => other,
+    };
+    format!("[{label}]")
+}
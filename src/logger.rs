@@ -0,0 +1,46 @@
+//
+// ===================== ДИАГНОСТИКА (-q, -vv) =====================
+//
+// Служебные строки, предупреждения и ошибки идут через log и всегда в
+// stderr: в stdout остаётся только то, что просили вывести (статистика
+// или лог с -o -), и его можно спокойно перенаправлять.
+//   -q   — только ошибки
+//   -vv  — ещё и отладка: пропущенные сообщения и незнакомые поля
+//   -vvv — всё подряд
+//
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+struct StderrLogger;
+
+static LOGGER: StderrLogger = StderrLogger;
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        match record.level() {
+            Level::Error | Level::Warn | Level::Info => eprintln!("{}", record.args()),
+            Level::Debug | Level::Trace => eprintln!("[отладка] {}", record.args()),
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// `verbose` — сколько раз указан -v.
+pub fn init(quiet: bool, verbose: u8) {
+    let level = match (quiet, verbose) {
+        (true, _) => LevelFilter::Error,
+        (false, 0 | 1) => LevelFilter::Info,
+        (false, 2) => LevelFilter::Debug,
+        (false, _) => LevelFilter::Trace,
+    };
+    let _ = log::set_logger(&LOGGER);
+    log::set_max_level(level);
+}
@@ -0,0 +1,137 @@
+//
+// ===================== МАНИФЕСТ МЕДИА =====================
+//
+// Все файлы, на которые ссылается экспорт: сообщение, автор, дата, путь
+// и есть ли файл на самом деле. Экспорт часто пропускает медиа (лимиты
+// размера, снятые галочки) — по манифесту видно, что именно потеряно.
+// CSV или JSON — по расширению файла; пишется по ходу разбора.
+//
+
+use chrono::NaiveDateTime;
+use simd_json::borrowed::Object;
+use simd_json::prelude::*;
+use simd_json::json;
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::media::{for_each_media_path, is_placeholder};
+use crate::stat_csv::write_row;
+use crate::{get_i64_field, get_str_field};
+
+struct Entry<'a> {
+    chat: &'a str,
+    id: i64,
+    date: &'a str,
+    author: &'a str,
+    kind: &'a str,
+    field: &'a str,
+    path: &'a str,
+    exists: bool,
+}
+
+pub struct MediaManifest {
+    out: BufWriter<File>,
+    json: bool,
+    // папка экспорта, относительно которой заданы пути
+    base: PathBuf,
+    pub entries: usize,
+    pub missing: usize,
+}
+
+impl MediaManifest {
+    pub fn create(path: &str, base: PathBuf) -> io::Result<Self> {
+        let json = Path::new(path)
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("json"));
+        let mut out = BufWriter::new(File::create(path)?);
+        if json {
+            out.write_all(b"[\n")?;
+        } else {
+            write_row(
+                &mut out,
+                &["chat", "id", "date", "author", "kind", "field", "path", "exists"],
+            )?;
+        }
+        Ok(Self {
+            out,
+            json,
+            base,
+            entries: 0,
+            missing: 0,
+        })
+    }
+
+    pub fn observe(
+        &mut self,
+        chat: &str,
+        author: &str,
+        kind: &str,
+        date: Option<NaiveDateTime>,
+        msg_obj: &Object,
+    ) -> io::Result<()> {
+        let id = get_i64_field(msg_obj, "id").unwrap_or(0);
+        let date = match date {
+            Some(dt) => dt.format("%Y-%m-%d %H:%M:%S").to_string(),
+            None => get_str_field(msg_obj, "date").unwrap_or("").to_string(),
+        };
+
+        let mut res = Ok(());
+        for_each_media_path(msg_obj, |field, path| {
+            if res.is_err() {
+                return;
+            }
+            let exists = !is_placeholder(path) && self.base.join(path).is_file();
+            if !exists {
+                self.missing += 1;
+            }
+            let entry = Entry {
+                chat,
+                id,
+                date: &date,
+                author,
+                kind,
+                field,
+                path,
+                exists,
+            };
+            res = self.write_entry(&entry);
+            self.entries += 1;
+        });
+        res
+    }
+
+    fn write_entry(&mut self, e: &Entry) -> io::Result<()> {
+        if self.json {
+            if self.entries > 0 {
+                self.out.write_all(b",\n")?;
+            }
+            let entry = json!({
+                "chat": e.chat,
+                "id": e.id,
+                "date": e.date,
+                "author": e.author,
+                "kind": e.kind,
+                "field": e.field,
+                "path": e.path,
+                "exists": e.exists
+            });
+            entry.write(&mut self.out)
+        } else {
+            let id = e.id.to_string();
+            let exists = if e.exists { "yes" } else { "no" };
+            let row = [e.chat, &id, e.date, e.author, e.kind, e.field, e.path, exists];
+            write_row(&mut self.out, &row)
+        }
+    }
+
+    /// Возвращает (всего файлов, отсутствуют).
+    pub fn finish(mut self) -> io::Result<(usize, usize)> {
+        if self.json {
+            self.out.write_all(b"\n]\n")?;
+        }
+        self.out.flush()?;
+        Ok((self.entries, self.missing))
+    }
+}
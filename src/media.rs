@@ -0,0 +1,121 @@
+//
+// ===================== ФАЙЛЫ МЕДИА =====================
+//
+// "photo" и "file" — пути относительно папки экспорта. Если медиа при
+// выгрузке пропущены, вместо пути стоит "(File not included. Change data
+// exporting settings to download.)" — такие файлы считаются отсутствующими.
+//
+
+use ahash::AHashMap;
+use simd_json::borrowed::Object;
+
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::get_str_field;
+
+const TOP_MEDIA_AUTHORS: usize = 15;
+
+/// Пути к файлам медиа сообщения: (поле, путь или заглушка из экспорта).
+pub fn for_each_media_path<'a, F>(msg_obj: &'a Object, mut f: F)
+where
+    F: FnMut(&'static str, &'a str),
+{
+    for key in ["photo", "file"] {
+        if let Some(path) = get_str_field(msg_obj, key) {
+            f(key, path);
+        }
+    }
+}
+
+/// Заглушка вместо пути — файл при выгрузке не скачивался.
+pub fn is_placeholder(path: &str) -> bool {
+    path.starts_with('(')
+}
+
+/// "532 Б" / "14.2 КиБ" / "1.3 ГиБ"
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["Б", "КиБ", "МиБ", "ГиБ", "ТиБ"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} {}", UNITS[0])
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+// как sorted_by_count, но для значений-пар и байтов
+fn sorted_desc<V: Copy, K: Ord>(map: &AHashMap<String, V>, key: impl Fn(V) -> K) -> Vec<(&str, V)> {
+    let mut v: Vec<_> = map.iter().map(|(k, &x)| (k.as_str(), x)).collect();
+    v.sort_by_key(|p| std::cmp::Reverse(key(p.1)));
+    v
+}
+
+#[derive(Default)]
+pub struct MediaSizeStats {
+    pub files: usize,
+    pub total_bytes: u64,
+    // упомянуты в JSON, но на диске их нет (или не скачивались)
+    pub missing: usize,
+    // вид медиа -> (файлов, байт)
+    pub by_kind: AHashMap<String, (usize, u64)>,
+    // автор -> байт
+    pub per_author: AHashMap<String, u64>,
+}
+
+crate::cache::codec!(MediaSizeStats { files, total_bytes, missing, by_kind, per_author });
+
+impl MediaSizeStats {
+    pub fn observe(&mut self, media_dir: &Path, author: &str, kind: &str, msg_obj: &Object) {
+        for_each_media_path(msg_obj, |_, path| {
+            let size = if is_placeholder(path) {
+                None
+            } else {
+                std::fs::metadata(media_dir.join(path)).ok().map(|m| m.len())
+            };
+            let Some(size) = size else {
+                self.missing += 1;
+                return;
+            };
+            self.files += 1;
+            self.total_bytes += size;
+            let entry = self.by_kind.entry(kind.to_string()).or_insert((0, 0));
+            entry.0 += 1;
+            entry.1 += size;
+            *self.per_author.entry(author.to_string()).or_insert(0) += size;
+        });
+    }
+
+    pub fn kinds_by_size(&self) -> Vec<(&str, (usize, u64))> {
+        sorted_desc(&self.by_kind, |(_, bytes)| bytes)
+    }
+
+    pub fn authors_by_size(&self) -> Vec<(&str, u64)> {
+        sorted_desc(&self.per_author, |bytes| bytes)
+    }
+
+    pub fn write_text<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(
+            w,
+            "Медиа на диске: {} в {} файлах (нет на диске: {})",
+            format_bytes(self.total_bytes),
+            self.files,
+            self.missing
+        )?;
+        writeln!(w, "  по видам:")?;
+        for (kind, (files, bytes)) in self.kinds_by_size() {
+            writeln!(w, "  - {}: {} ({} файлов)", kind, format_bytes(bytes), files)?;
+        }
+        writeln!(w, "  по авторам:")?;
+        for (author, bytes) in self.authors_by_size().into_iter().take(TOP_MEDIA_AUTHORS) {
+            writeln!(w, "  - {}: {}", author, format_bytes(bytes))?;
+        }
+        Ok(())
+    }
+}
+
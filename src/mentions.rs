@@ -0,0 +1,136 @@
+//
+// ===================== УПОМИНАНИЯ =====================
+//
+// Сущности "mention" (@username) и "mention_name" (упоминание по имени
+// для пользователей без username). Кто кого упоминает — матрица
+// "автор × упомянутый" по самым активным с обеих сторон.
+//
+// У "mention_name" есть user_id: если этот участник уже писал в чат,
+// упоминание записывается на его имя автора, и в графе упоминаний
+// он — та же вершина, что и в графе ответов.
+//
+
+use ahash::AHashMap;
+
+use std::io::{self, Write};
+
+use crate::model::Message;
+use crate::{Metric, for_each_entity, get_i64_field, top_by_count};
+
+const TOP_MENTIONED: usize = 15;
+const TOP_PAIRS: usize = 15;
+// сторона матрицы — больше в консоль не влезает
+const MATRIX_SIZE: usize = 8;
+const MATRIX_NAME_CHARS: usize = 20;
+
+#[derive(Default)]
+pub struct MentionStats {
+    pub total: usize,
+    // @username или имя -> сколько раз упомянут
+    pub mentioned: AHashMap<String, usize>,
+    // автор -> сколько раз упоминал других
+    pub mentioners: AHashMap<String, usize>,
+    // автор -> (упомянутый -> сколько)
+    pub pairs: AHashMap<String, AHashMap<String, usize>>,
+    // user_id из from_id ("user123") -> имя автора
+    names_by_id: AHashMap<i64, String>,
+}
+
+crate::cache::codec!(MentionStats { total, mentioned, mentioners, pairs, names_by_id });
+
+impl MentionStats {
+    /// Пары (автор, упомянутый, сколько) по убыванию.
+    pub fn top_pairs(&self) -> Vec<(&str, &str, usize)> {
+        let mut v: Vec<_> = self
+            .pairs
+            .iter()
+            .flat_map(|(a, m)| m.iter().map(move |(t, &c)| (a.as_str(), t.as_str(), c)))
+            .collect();
+        v.sort_by_key(|p| std::cmp::Reverse(p.2));
+        v
+    }
+}
+
+impl Metric for MentionStats {
+    fn observe(&mut self, msg: &Message) {
+        let (Some(msg_obj), Some(_)) = (msg.raw, msg.text_value()) else {
+            return;
+        };
+        let author = msg.author();
+        let author = author.as_ref();
+        let from_id = msg.from_id.unwrap_or("no_id");
+        if let Some(id) = from_id.strip_prefix("user").and_then(|s| s.parse().ok())
+            && !self.names_by_id.contains_key(&id)
+        {
+            self.names_by_id.insert(id, author.to_string());
+        }
+        let names_by_id = &self.names_by_id;
+        for_each_entity(msg_obj, |kind, text, obj| {
+            let target = match kind {
+                "mention" => text.to_lowercase(),
+                "mention_name" => get_i64_field(obj, "user_id")
+                    .and_then(|id| names_by_id.get(&id))
+                    .map_or_else(|| text.to_string(), |name| name.clone()),
+                _ => return,
+            };
+            self.total += 1;
+            *self.mentioned.entry(target.clone()).or_insert(0) += 1;
+            *self.mentioners.entry(author.to_string()).or_insert(0) += 1;
+            *self
+                .pairs
+                .entry(author.to_string())
+                .or_default()
+                .entry(target)
+                .or_insert(0) += 1;
+        });
+    }
+
+    fn report(&self, w: &mut dyn Write) -> io::Result<()> {
+        writeln!(w, "Упоминания: {}", self.total)?;
+        if self.total == 0 {
+            return Ok(());
+        }
+        writeln!(w, "  кого упоминают чаще всех:")?;
+        for (name, count) in top_by_count(&self.mentioned, TOP_MENTIONED) {
+            writeln!(w, "  - {}: {}", name, count)?;
+        }
+        writeln!(w, "  кто кого:")?;
+        for (author, target, count) in self.top_pairs().into_iter().take(TOP_PAIRS) {
+            writeln!(w, "  - {} → {}: {}", author, target, count)?;
+        }
+
+        // матрица: строки — самые упоминающие, столбцы — самые упоминаемые
+        let rows: Vec<&str> = top_by_count(&self.mentioners, MATRIX_SIZE)
+            .into_iter()
+            .map(|(n, _)| n)
+            .collect();
+        let cols: Vec<&str> = top_by_count(&self.mentioned, MATRIX_SIZE)
+            .into_iter()
+            .map(|(n, _)| n)
+            .collect();
+
+        writeln!(w)?;
+        writeln!(w, "Матрица упоминаний (строка упоминает столбец):")?;
+        for (i, col) in cols.iter().enumerate() {
+            writeln!(w, "  [{}] {}", i + 1, col)?;
+        }
+        write!(w, "  {:<width$}", "", width = MATRIX_NAME_CHARS)?;
+        for i in 0..cols.len() {
+            write!(w, " {:>5}", format!("[{}]", i + 1))?;
+        }
+        writeln!(w)?;
+        for row in rows {
+            let short: String = row.chars().take(MATRIX_NAME_CHARS).collect();
+            write!(w, "  {:<width$}", short, width = MATRIX_NAME_CHARS)?;
+            let counts = self.pairs.get(row);
+            for col in &cols {
+                match counts.and_then(|m| m.get(*col)) {
+                    Some(c) => write!(w, " {:>5}", c)?,
+                    None => write!(w, " {:>5}", "·")?,
+                }
+            }
+            writeln!(w)?;
+        }
+        Ok(())
+    }
+}
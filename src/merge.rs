@@ -0,0 +1,189 @@
+//
+// ===================== НЕСКОЛЬКО ЭКСПОРТОВ (-i a -i b) =====================
+//
+// Экспорты за пересекающиеся периоды склеиваются в один: чаты — по id,
+// сообщения — по id сообщения. Если одно сообщение есть в нескольких
+// экспортах, остаётся версия из последнего по списку (в свежем экспорте
+// видны правки). Сообщения каждого чата сортируются по дате, затем по id.
+//
+// Один чат из одиночных экспортов остаётся одиночным экспортом; иначе
+// получается экспорт аккаунта со всеми чатами в chats.list.
+//
+// tgjsps merge записывает склеенный экспорт в JSON — дальше его можно
+// разбирать как обычный, не перечисляя все части каждый раз.
+//
+
+use ahash::AHashMap;
+use log::info;
+use simd_json::BorrowedValue;
+use simd_json::borrowed::Object;
+use simd_json::prelude::*;
+
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use crate::{
+    STDIO, discover_export, expand_glob, get_i64_field, get_str_field, read_input, value_to_id,
+};
+
+#[derive(clap::Args, Debug)]
+pub struct MergeArgs {
+    /// Экспорты: JSON, архивы, папки или шаблоны вида 'exports/*.json'
+    #[arg(required = true)]
+    inputs: Vec<String>,
+
+    /// Куда записать склеенный экспорт; "-" — в stdout
+    #[arg(short = 'o', long = "output", default_value = "merged.json")]
+    output: String,
+}
+
+#[derive(Default)]
+struct MergedChat<'a> {
+    // поля чата (name, type, id) из последнего экспорта
+    meta: Object<'a>,
+    by_id: AHashMap<i64, BorrowedValue<'a>>,
+    no_id: Vec<BorrowedValue<'a>>,
+}
+
+#[derive(Default)]
+pub struct Merger<'a> {
+    chats: Vec<MergedChat<'a>>,
+    // id чата (или имя, если id нет) -> индекс в chats
+    index: AHashMap<String, usize>,
+    // среди входов был экспорт аккаунта
+    account: bool,
+    pub inputs: usize,
+    pub duplicates: usize,
+}
+
+fn sort_key<'v>(msg: &'v BorrowedValue) -> (&'v str, i64) {
+    match msg {
+        BorrowedValue::Object(obj) => (
+            get_str_field(obj, "date").unwrap_or(""),
+            get_i64_field(obj, "id").unwrap_or(0),
+        ),
+        _ => ("", 0),
+    }
+}
+
+impl<'a> Merger<'a> {
+    pub fn add(&mut self, root: BorrowedValue<'a>) -> Result<(), String> {
+        let BorrowedValue::Object(mut root) = root else {
+            return Err("Корень JSON не объект".into());
+        };
+        self.inputs += 1;
+        if root.contains_key("messages") {
+            self.add_chat(*root);
+            return Ok(());
+        }
+        self.account = true;
+        for section in ["chats", "left_chats"] {
+            if let Some(BorrowedValue::Object(mut sec)) = root.remove(section)
+                && let Some(BorrowedValue::Array(list)) = sec.remove("list")
+            {
+                for chat in list.into_iter() {
+                    if let BorrowedValue::Object(chat) = chat {
+                        self.add_chat(*chat);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn add_chat(&mut self, mut chat: Object<'a>) {
+        let messages = chat.remove("messages");
+        let key = match chat.get("id") {
+            Some(id) => value_to_id(id),
+            None => get_str_field(&chat, "name").unwrap_or("").to_string(),
+        };
+        let i = *self.index.entry(key).or_insert_with(|| {
+            self.chats.push(MergedChat::default());
+            self.chats.len() - 1
+        });
+        let merged = &mut self.chats[i];
+        merged.meta = chat;
+
+        let Some(BorrowedValue::Array(messages)) = messages else {
+            return;
+        };
+        for msg in messages.into_iter() {
+            let id = match &msg {
+                BorrowedValue::Object(obj) => get_i64_field(obj, "id"),
+                _ => None,
+            };
+            match id {
+                Some(id) => {
+                    if merged.by_id.insert(id, msg).is_some() {
+                        self.duplicates += 1;
+                    }
+                }
+                None => merged.no_id.push(msg),
+            }
+        }
+    }
+
+    /// Склеенный корень экспорта.
+    pub fn finish(self) -> BorrowedValue<'a> {
+        let single = !self.account && self.chats.len() == 1;
+        let mut chats: Vec<BorrowedValue<'a>> = self
+            .chats
+            .into_iter()
+            .map(|chat| {
+                let mut messages: Vec<BorrowedValue<'a>> =
+                    chat.by_id.into_values().chain(chat.no_id).collect();
+                messages.sort_by(|a, b| sort_key(a).cmp(&sort_key(b)));
+                let mut obj = chat.meta;
+                obj.insert("messages".into(), BorrowedValue::Array(Box::new(messages)));
+                BorrowedValue::Object(Box::new(obj))
+            })
+            .collect();
+        if single && let Some(chat) = chats.pop() {
+            return chat;
+        }
+
+        let mut list = Object::with_capacity(1);
+        list.insert("list".into(), BorrowedValue::Array(Box::new(chats)));
+        let mut root = Object::with_capacity(1);
+        root.insert("chats".into(), BorrowedValue::Object(Box::new(list)));
+        BorrowedValue::Object(Box::new(root))
+    }
+}
+
+/// tgjsps merge: прочитать входы, склеить и записать JSON.
+pub fn run(args: &MergeArgs) -> Result<(), Box<dyn Error>> {
+    let mut inputs = Vec::new();
+    for pattern in &args.inputs {
+        for input in expand_glob(pattern)? {
+            match discover_export(&input)? {
+                Some(dir) => inputs.push(dir.join("result.json").to_string_lossy().into_owned()),
+                None => inputs.push(input),
+            }
+        }
+    }
+    // строки склеенного DOM заимствованы из буферов: они живут до конца
+    let mut bufs: Vec<Vec<u8>> = inputs.iter().map(|i| read_input(i)).collect::<Result<_, _>>()?;
+    let mut merger = Merger::default();
+    for (input, buf) in inputs.iter().zip(bufs.iter_mut()) {
+        let root = simd_json::to_borrowed_value(buf)
+            .map_err(|e| format!("Ошибка парсинга JSON в {input}: {e}"))?;
+        merger.add(root)?;
+    }
+    let (count, duplicates) = (merger.inputs, merger.duplicates);
+    let merged = merger.finish();
+
+    let mut w: BufWriter<Box<dyn Write>> = if args.output == STDIO {
+        BufWriter::new(Box::new(io::stdout().lock()))
+    } else {
+        BufWriter::new(Box::new(File::create(&args.output)?))
+    };
+    merged.write(&mut w)?;
+    writeln!(w)?;
+    w.flush()?;
+    info!(
+        "Склеено экспортов: {count}, повторов по id отброшено: {duplicates}; записано в {}",
+        args.output
+    );
+    Ok(())
+}
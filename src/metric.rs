@@ -0,0 +1,72 @@
+//
+// ===================== МЕТРИКИ =====================
+//
+// Счётчик, которому хватает одного сообщения: смотрит на каждое и в конце
+// печатает свой раздел. Свои метрики библиотеки ([`crate::Analyzer::metric`])
+// видят все сообщения, прошедшие фильтры, и печатаются в конце текстовой
+// статистики.
+//
+// Встроенные метрики -v идут двумя циклами в stats.rs:
+//   - по сообщению, в порядке экспорта: стикеры, вопросы, упоминания;
+//   - по тексту (только сообщения с текстом, с --threads — по частям и
+//     потом merge): длина, капс, стиль, разметка, ссылки, кастомные эмодзи,
+//     команды ботам.
+//
+// Метриками не стали, и это намеренно:
+//   - то, чему нужна дата с --timezone (часы, лента, присутствие,
+//     взаимодействия, реакции, тональность) — Message её не хранит;
+//   - служебные сообщения (звонки, смены чата) — сюда они не попадают;
+//   - слова, спам, пересылки и уникальные — им нужны стоп-слова, --stem,
+//     --approx и пороги спама, которых у Metric нет;
+//   - длительности и размеры медиа — им нужен --media-dir;
+//   - --wordlist и простые счётчики по типам медиа — поля Stats.
+//
+
+use crate::cache::{Codec, Reader};
+use crate::model::Message;
+
+use std::io::{self, Write};
+
+/// Своя статистика по сообщениям.
+///
+/// ```
+/// use std::io::{self, Write};
+/// use tgjsps::{Message, Metric};
+///
+/// #[derive(Default)]
+/// struct Edited(usize);
+///
+/// impl Metric for Edited {
+///     fn observe(&mut self, msg: &Message) {
+///         self.0 += msg.edited.is_some() as usize;
+///     }
+///
+///     fn report(&self, w: &mut dyn Write) -> io::Result<()> {
+///         writeln!(w, "Отредактировано: {}", self.0)
+///     }
+/// }
+/// ```
+pub trait Metric: Send {
+    /// Очередное сообщение (служебные сюда не попадают).
+    fn observe(&mut self, msg: &Message);
+
+    /// Конец разбора, до [`Metric::report`]: дописать итог, закрыть ресурсы.
+    fn finish(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Раздел текстовой статистики.
+    fn report(&self, w: &mut dyn Write) -> io::Result<()>;
+}
+
+/// Свои метрики в [`crate::Stats`]. В кэш не пишутся: CLI их не заводит.
+#[derive(Default)]
+pub(crate) struct Metrics(pub(crate) Vec<Box<dyn Metric>>);
+
+impl Codec for Metrics {
+    fn encode(&self, _: &mut Vec<u8>) {}
+
+    fn decode(_: &mut Reader) -> Result<Self, String> {
+        Ok(Metrics::default())
+    }
+}
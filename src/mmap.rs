@@ -0,0 +1,106 @@
+//
+// ===================== ФАЙЛ В ПАМЯТИ (mmap) =====================
+//
+// Вместо std::fs::read экспорт отображается в память: страницы подгружает
+// ОС по мере разбора, отдельной копии файла в куче нет, и пик RSS меньше
+// на размер экспорта. MAP_PRIVATE: simd-json правит буфер на месте, такие
+// страницы копируются, а файл на диске не меняется.
+//
+// Не вышло (не unix, пустой файл, файл из /proc) — None, и файл читается
+// как раньше. Файл не должен обрезаться, пока идёт разбор (--watch ждёт,
+// пока экспорт допишется): чтение страницы за новым концом файла — SIGBUS.
+// Пока отображение живо, на SIGBUS стоит свой обработчик: вместо молчаливого
+// падения — сообщение и код выхода 1. memmap2 тут не помог бы: обрезанный
+// файл и для него — SIGBUS.
+//
+
+use std::ops::{Deref, DerefMut};
+
+pub struct Mmap {
+    ptr: *mut u8,
+    len: usize,
+    // обработчик SIGBUS, который стоял до open
+    #[cfg(unix)]
+    old_sigbus: libc::sigaction,
+}
+
+// Только async-signal-safe: write и _exit, без аллокаций и паники.
+#[cfg(unix)]
+extern "C" fn on_sigbus(_: libc::c_int) {
+    const MSG: &[u8] = "Фатальная ошибка: входной файл обрезан во время разбора\n".as_bytes();
+    // SAFETY: запись готового буфера в stderr и немедленный выход
+    unsafe {
+        libc::write(libc::STDERR_FILENO, MSG.as_ptr().cast(), MSG.len());
+        libc::_exit(1);
+    }
+}
+
+impl Mmap {
+    #[cfg(unix)]
+    pub fn open(path: &str) -> Option<Mmap> {
+        use std::os::fd::AsRawFd;
+
+        let file = std::fs::File::open(path).ok()?;
+        let len = usize::try_from(file.metadata().ok()?.len()).ok()?;
+        if len == 0 {
+            return None;
+        }
+        // SAFETY: новое частное отображение открытого файла; после mmap
+        // дескриптор можно закрыть, отображение живёт до munmap в Drop
+        let ptr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_PRIVATE,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return None;
+        }
+        // SAFETY: sigaction только заполняется; старый обработчик вернёт Drop
+        let old_sigbus = unsafe {
+            let mut action: libc::sigaction = std::mem::zeroed();
+            action.sa_sigaction = on_sigbus as extern "C" fn(libc::c_int) as libc::sighandler_t;
+            let mut old: libc::sigaction = std::mem::zeroed();
+            libc::sigaction(libc::SIGBUS, &action, &mut old);
+            old
+        };
+        Some(Mmap { ptr: ptr.cast(), len, old_sigbus })
+    }
+
+    #[cfg(not(unix))]
+    pub fn open(_path: &str) -> Option<Mmap> {
+        None
+    }
+}
+
+impl Deref for Mmap {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        // SAFETY: ptr..ptr+len отображены, пока жив Mmap
+        unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
+    }
+}
+
+impl DerefMut for Mmap {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        // SAFETY: как в deref; отображение частное и доступно на запись
+        unsafe { std::slice::from_raw_parts_mut(self.ptr, self.len) }
+    }
+}
+
+impl Drop for Mmap {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        // SAFETY: то самое отображение из open, освобождается один раз;
+        // обработчик SIGBUS — тот, что был до open
+        unsafe {
+            libc::munmap(self.ptr.cast(), self.len);
+            libc::sigaction(libc::SIGBUS, &self.old_sigbus, std::ptr::null_mut());
+        }
+    }
+}
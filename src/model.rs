@@ -0,0 +1,562 @@
+//
+// ===================== МОДЕЛЬ СООБЩЕНИЯ =====================
+//
+// Сообщение экспорта Telegram Desktop как типизированная структура, а не
+// набор get_str_field по объекту. Строки заимствуются из разобранного
+// JSON, так что разбор сообщения почти ничего не копирует.
+//
+// Формат экспорта меняется от версии к версии, поэтому все поля
+// необязательны, поле неожиданного типа читается как отсутствующее, а
+// незнакомые типы сообщений, вложений, служебных событий и разметки
+// попадают в Other / Unknown вместо ошибки.
+//
+
+use chrono::NaiveDateTime;
+use chrono_tz::Tz;
+use serde::de::value::{
+    BoolDeserializer, BorrowedStrDeserializer, F64Deserializer, I64Deserializer,
+    MapAccessDeserializer, StrDeserializer, U64Deserializer,
+};
+use serde::de::{self, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+use simd_json::BorrowedValue;
+use simd_json::borrowed::Object;
+
+use crate::parser::{display_name, resolve_date, text_is_empty};
+
+use std::borrow::Cow;
+use std::fmt;
+use std::marker::PhantomData;
+
+/// Одно сообщение из "messages".
+#[derive(Deserialize, Debug, Default)]
+pub struct Message<'a> {
+    #[serde(default, deserialize_with = "lenient")]
+    pub id: Option<i64>,
+    #[serde(rename = "type", default, deserialize_with = "or_default")]
+    pub kind: MessageKind,
+    /// Местное время экспорта, "2024-01-31T12:00:00"
+    #[serde(default, deserialize_with = "lenient")]
+    pub date: Option<&'a str>,
+    /// То же в UTC; в экспортах строкой, но бывает и числом
+    #[serde(default, deserialize_with = "unixtime")]
+    pub date_unixtime: Option<i64>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub edited: Option<&'a str>,
+
+    /// Автор; у удалённых аккаунтов null
+    #[serde(default, deserialize_with = "lenient")]
+    pub from: Option<&'a str>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub from_id: Option<&'a str>,
+    /// Кто вызвал служебное событие
+    #[serde(default, deserialize_with = "lenient")]
+    pub actor: Option<&'a str>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub actor_id: Option<&'a str>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub action: Option<ServiceAction>,
+
+    /// Откуда переслано: Some(None) — оригинал скрыт настройками приватности
+    #[serde(default, deserialize_with = "present")]
+    pub forwarded_from: Option<Option<&'a str>>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub forwarded_from_id: Option<&'a str>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub reply_to_message_id: Option<i64>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub via_bot: Option<&'a str>,
+
+    #[serde(borrow, default)]
+    pub text: Text<'a>,
+    #[serde(borrow, default, deserialize_with = "entity_list")]
+    pub text_entities: Option<Vec<TextEntity<'a>>>,
+
+    /// Путь к фото или "(File not included...)"
+    #[serde(default, deserialize_with = "lenient")]
+    pub photo: Option<&'a str>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub file: Option<&'a str>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub media_type: Option<MediaType>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub sticker_emoji: Option<&'a str>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub duration_seconds: Option<u64>,
+    #[serde(borrow, default, deserialize_with = "lenient_nested")]
+    pub poll: Option<Poll<'a>>,
+
+    /// Исходный объект: поля, которых в модели нет (реакции, document_id...)
+    #[serde(skip)]
+    pub raw: Option<&'a Object<'a>>,
+}
+
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageKind {
+    Message,
+    Service,
+    // и сообщения без "type"
+    #[default]
+    #[serde(other)]
+    Other,
+}
+
+/// "text": строка или массив из строк и размеченных кусков.
+#[derive(Debug)]
+pub enum Text<'a> {
+    Plain(&'a str),
+    Parts(Vec<TextPart<'a>>),
+}
+
+impl Default for Text<'_> {
+    fn default() -> Self {
+        Text::Plain("")
+    }
+}
+
+#[derive(Debug)]
+pub enum TextPart<'a> {
+    Plain(&'a str),
+    Entity(TextEntity<'a>),
+}
+
+/// Размеченный кусок текста из "text_entities" или массива "text".
+#[derive(Deserialize, Debug)]
+pub struct TextEntity<'a> {
+    #[serde(rename = "type", default, deserialize_with = "or_default")]
+    pub kind: EntityKind,
+    #[serde(default, deserialize_with = "or_default")]
+    pub text: &'a str,
+    /// text_link: куда ведёт ссылка
+    #[serde(default, deserialize_with = "lenient")]
+    pub href: Option<&'a str>,
+    /// mention_name: упомянутый без @username
+    #[serde(default, deserialize_with = "lenient")]
+    pub user_id: Option<i64>,
+    /// custom_emoji: путь к файлу премиум-эмодзи
+    #[serde(default, deserialize_with = "lenient")]
+    pub document_id: Option<&'a str>,
+}
+
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EntityKind {
+    #[default]
+    Plain,
+    Bold,
+    Italic,
+    Underline,
+    Strikethrough,
+    Spoiler,
+    Code,
+    Pre,
+    Blockquote,
+    Link,
+    TextLink,
+    Email,
+    Phone,
+    Mention,
+    MentionName,
+    Hashtag,
+    Cashtag,
+    BotCommand,
+    CustomEmoji,
+    #[serde(other)]
+    Unknown,
+}
+
+/// Вложение из "media_type"; фото, файлы и опросы — отдельными полями.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MediaType {
+    VoiceMessage,
+    VideoFile,
+    VideoMessage,
+    AudioFile,
+    Animation,
+    Sticker,
+    #[serde(other)]
+    Other,
+}
+
+impl MediaType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MediaType::VoiceMessage => "voice_message",
+            MediaType::VideoFile => "video_file",
+            MediaType::VideoMessage => "video_message",
+            MediaType::AudioFile => "audio_file",
+            MediaType::Animation => "animation",
+            MediaType::Sticker => "sticker",
+            MediaType::Other => "other",
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct Poll<'a> {
+    #[serde(default, deserialize_with = "or_default")]
+    pub question: &'a str,
+    #[serde(default, deserialize_with = "or_default")]
+    pub closed: bool,
+    #[serde(default, deserialize_with = "or_default")]
+    pub total_voters: u64,
+    #[serde(borrow, default)]
+    pub answers: Vec<PollAnswer<'a>>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct PollAnswer<'a> {
+    #[serde(default, deserialize_with = "or_default")]
+    pub text: &'a str,
+    #[serde(default, deserialize_with = "or_default")]
+    pub voters: u64,
+    /// Выбран ли ответ тем, кто делал экспорт
+    #[serde(default, deserialize_with = "or_default")]
+    pub chosen: bool,
+}
+
+/// "action" служебного сообщения.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ServiceAction {
+    CreateGroup,
+    CreateChannel,
+    MigrateToSupergroup,
+    MigrateFromGroup,
+    InviteMembers,
+    RemoveMembers,
+    JoinGroupByLink,
+    JoinGroupByRequest,
+    PinMessage,
+    EditGroupTitle,
+    EditGroupPhoto,
+    DeleteGroupPhoto,
+    PhoneCall,
+    GroupCall,
+    #[serde(other)]
+    Other,
+}
+
+impl<'a> Message<'a> {
+    /// Разбор из уже распарсенного JSON; строки заимствуются из `value`.
+    pub fn from_value(value: &'a BorrowedValue<'a>) -> Result<Self, simd_json::Error> {
+        let mut msg = Message::deserialize(value)?;
+        if let BorrowedValue::Object(obj) = value {
+            msg.raw = Some(obj);
+        }
+        Ok(msg)
+    }
+
+    /// "text" как в JSON (строка или массив кусков), если текст не пустой.
+    pub fn text_value(&self) -> Option<&'a BorrowedValue<'a>> {
+        self.raw?.get("text").filter(|t| !text_is_empty(t))
+    }
+
+    /// Имя автора; у удалённых аккаунтов — "Удалённый аккаунт (user123)".
+    pub fn author(&self) -> Cow<'a, str> {
+        display_name(self.from, self.from_id)
+    }
+
+    /// Дата в поясе `tz`, а без него — как в экспорте.
+    pub fn date(&self, tz: Option<Tz>) -> Option<NaiveDateTime> {
+        resolve_date(self.date, self.date_unixtime, tz)
+    }
+
+    pub fn is_forwarded(&self) -> bool {
+        self.forwarded_from.is_some() || self.forwarded_from_id.is_some()
+    }
+
+    /// Вид вложения одним словом: photo, voice_message, sticker, file, poll...
+    pub fn media_kind(&self) -> Option<&'static str> {
+        if self.photo.is_some() {
+            Some("photo")
+        } else if let Some(mt) = self.media_type {
+            Some(mt.as_str())
+        } else if self.file.is_some() {
+            Some("file")
+        } else if self.poll.is_some() {
+            Some("poll")
+        } else {
+            None
+        }
+    }
+
+    /// Все куски текста подряд, без разметки.
+    pub fn text_segments(&self) -> impl Iterator<Item = &'a str> + '_ {
+        let (plain, parts) = match &self.text {
+            Text::Plain(s) => (Some(*s), &[][..]),
+            Text::Parts(parts) => (None, parts.as_slice()),
+        };
+        plain.into_iter().chain(parts.iter().map(|part| match part {
+            TextPart::Plain(s) => *s,
+            TextPart::Entity(e) => e.text,
+        }))
+    }
+
+    /// Текст целиком, без разметки.
+    pub fn plain_text(&self) -> String {
+        self.text_segments().collect()
+    }
+
+    /// Размеченные куски: "text_entities", а в старых экспортах без него —
+    /// объекты внутри массива "text".
+    pub fn entities(&self) -> impl Iterator<Item = &TextEntity<'a>> {
+        let from_text = match (&self.text_entities, &self.text) {
+            (None, Text::Parts(parts)) => parts.as_slice(),
+            _ => &[],
+        };
+        let listed = self.text_entities.iter().flatten();
+        listed.chain(from_text.iter().filter_map(|part| match part {
+            TextPart::Entity(e) => Some(e),
+            TextPart::Plain(_) => None,
+        }))
+    }
+}
+
+// Текст бывает у каждого сообщения, поэтому он разбирается своими
+// визиторами, без буфера untagged-перечислений. Значение другого типа
+// пропускается, как и в lenient.
+macro_rules! skip_other_types {
+    ($value:expr) => {
+        fn visit_bool<E: de::Error>(self, _: bool) -> Result<Self::Value, E> {
+            Ok($value)
+        }
+        fn visit_i64<E: de::Error>(self, _: i64) -> Result<Self::Value, E> {
+            Ok($value)
+        }
+        fn visit_u64<E: de::Error>(self, _: u64) -> Result<Self::Value, E> {
+            Ok($value)
+        }
+        fn visit_f64<E: de::Error>(self, _: f64) -> Result<Self::Value, E> {
+            Ok($value)
+        }
+        fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+            Ok($value)
+        }
+    };
+}
+
+struct TextVisitor;
+
+impl<'de> Visitor<'de> for TextVisitor {
+    type Value = Text<'de>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("текст сообщения")
+    }
+
+    fn visit_borrowed_str<E: de::Error>(self, s: &'de str) -> Result<Self::Value, E> {
+        Ok(Text::Plain(s))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut parts = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(part) = seq.next_element()? {
+            parts.push(part);
+        }
+        Ok(Text::Parts(parts))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        while map.next_entry::<IgnoredAny, IgnoredAny>()?.is_some() {}
+        Ok(Text::default())
+    }
+
+    skip_other_types!(Text::default());
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for Text<'a> {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        d.deserialize_any(TextVisitor)
+    }
+}
+
+struct TextPartVisitor;
+
+impl<'de> Visitor<'de> for TextPartVisitor {
+    type Value = TextPart<'de>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("строка или размеченный кусок текста")
+    }
+
+    fn visit_borrowed_str<E: de::Error>(self, s: &'de str) -> Result<Self::Value, E> {
+        Ok(TextPart::Plain(s))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        TextEntity::deserialize(MapAccessDeserializer::new(map)).map(TextPart::Entity)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        while seq.next_element::<IgnoredAny>()?.is_some() {}
+        Ok(TextPart::Plain(""))
+    }
+
+    skip_other_types!(TextPart::Plain(""));
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for TextPart<'a> {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        d.deserialize_any(TextPartVisitor)
+    }
+}
+
+/// "text_entities": куски, которые не объекты, пропускаются.
+fn entity_list<'de, D>(d: D) -> Result<Option<Vec<TextEntity<'de>>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let parts = match Text::deserialize(d)? {
+        Text::Parts(parts) => parts,
+        Text::Plain(_) => return Ok(None),
+    };
+    let entities = parts.into_iter().filter_map(|part| match part {
+        TextPart::Entity(e) => Some(e),
+        TextPart::Plain(_) => None,
+    });
+    Ok(Some(entities.collect()))
+}
+
+/// date_unixtime: "1700000000" или 1700000000.
+fn unixtime<'de, D: Deserializer<'de>>(d: D) -> Result<Option<i64>, D::Error> {
+    struct Unixtime(i64);
+
+    struct UnixtimeVisitor;
+
+    impl Visitor<'_> for UnixtimeVisitor {
+        type Value = Unixtime;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("время в секундах числом или строкой")
+        }
+
+        fn visit_i64<E: de::Error>(self, n: i64) -> Result<Self::Value, E> {
+            Ok(Unixtime(n))
+        }
+
+        fn visit_u64<E: de::Error>(self, n: u64) -> Result<Self::Value, E> {
+            i64::try_from(n).map(Unixtime).map_err(E::custom)
+        }
+
+        fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+            s.parse().map(Unixtime).map_err(E::custom)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Unixtime {
+        fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+            d.deserialize_any(UnixtimeVisitor)
+        }
+    }
+
+    Ok(lenient::<D, Unixtime>(d)?.map(|t| t.0))
+}
+
+// Простые значения (строки, числа, флаги, названия типов) пересобираются
+// из примитива; вложенные объекты и массивы на месте простого значения
+// пропускаются целиком.
+struct LenientVisitor<T>(PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for LenientVisitor<T> {
+    type Value = Option<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("любое значение")
+    }
+
+    fn visit_borrowed_str<E: de::Error>(self, s: &'de str) -> Result<Self::Value, E> {
+        Ok(T::deserialize(BorrowedStrDeserializer::<E>::new(s)).ok())
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+        Ok(T::deserialize(StrDeserializer::<E>::new(s)).ok())
+    }
+
+    fn visit_bool<E: de::Error>(self, b: bool) -> Result<Self::Value, E> {
+        Ok(T::deserialize(BoolDeserializer::<E>::new(b)).ok())
+    }
+
+    fn visit_i64<E: de::Error>(self, n: i64) -> Result<Self::Value, E> {
+        Ok(T::deserialize(I64Deserializer::<E>::new(n)).ok())
+    }
+
+    fn visit_u64<E: de::Error>(self, n: u64) -> Result<Self::Value, E> {
+        Ok(T::deserialize(U64Deserializer::<E>::new(n)).ok())
+    }
+
+    fn visit_f64<E: de::Error>(self, n: f64) -> Result<Self::Value, E> {
+        Ok(T::deserialize(F64Deserializer::<E>::new(n)).ok())
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, d: D) -> Result<Self::Value, D::Error> {
+        d.deserialize_any(self)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        while seq.next_element::<IgnoredAny>()?.is_some() {}
+        Ok(None)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        while map.next_entry::<IgnoredAny, IgnoredAny>()?.is_some() {}
+        Ok(None)
+    }
+}
+
+/// Значение неожиданного типа — как отсутствующее поле.
+fn lenient<'de, D, T>(d: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    d.deserialize_any(LenientVisitor(PhantomData))
+}
+
+fn or_default<'de, D, T>(d: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + Default,
+{
+    lenient(d).map(Option::unwrap_or_default)
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Nested<T> {
+    Valid(T),
+    Other(IgnoredAny),
+}
+
+/// То же для вложенного объекта (опрос): он разбирается через буфер
+/// untagged, но встречается редко.
+fn lenient_nested<'de, D, T>(d: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Ok(match Nested::<Option<T>>::deserialize(d)? {
+        Nested::Valid(v) => v,
+        Nested::Other(_) => None,
+    })
+}
+
+/// Поле есть, но null или непонятное — Some(None); поля нет — None
+/// (через default).
+fn present<'de, D, T>(d: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    lenient(d).map(Some)
+}
@@ -0,0 +1,677 @@
+use simd_json::prelude::*;
+use simd_json::json;
+
+use chrono::NaiveDateTime;
+
+use crate::log_template::{Field, Piece};
+use crate::model::Message;
+use crate::parser::{
+    get_i64_field, get_msg_date, get_poll_question, get_str_field, write_text_value,
+};
+use crate::replies::MessageIndex;
+use crate::stats::{
+    COPYPASTA_QUOTE_CHARS, Stats, TOP_COPYPASTA, WEEKDAYS, approx_note, build_full_text, copypasta,
+    percent_of, sorted_by_count, spam_repeats, spam_scores, top_by_count,
+};
+use crate::table::{Align, Table, Term};
+use crate::{
+    Metric, durations, heatmap, log_template, pins, redact, service, stat_csv, stat_json, table,
+    vocab,
+};
+
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+//
+// ===================== ЗАПИСЬ ЛОГА =====================
+//
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum OutputFormat {
+    /// Текстовый лог: "имя(id): текст"
+    Text,
+    /// Одно нормализованное сообщение — один JSON-объект на строку
+    Jsonl,
+}
+
+impl OutputFormat {
+    pub(crate) fn extension(self) -> &'static str {
+        match self {
+            OutputFormat::Text => "txt",
+            OutputFormat::Jsonl => "jsonl",
+        }
+    }
+}
+
+/// Формат даты по умолчанию для --with-date и {date}.
+pub(crate) const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Оформление строк текстового лога.
+pub(crate) struct LogStyle {
+    // --with-date: дата в начале строки
+    pub(crate) with_date: bool,
+    pub(crate) date_format: String,
+    // --service: служебные события строками "*** ... ***"
+    pub(crate) service: bool,
+    // --format: свой шаблон строки вместо "name(id): текст"
+    pub(crate) template: Option<Vec<Piece>>,
+    // индекс id -> автор для отметок ответов (--replies / {reply})
+    pub(crate) replies: Option<MessageIndex>,
+    // --redact: маскировка телефонов, почты, ссылок в тексте
+    pub(crate) redact: Option<redact::Redactor>,
+}
+
+impl Default for LogStyle {
+    fn default() -> Self {
+        Self {
+            with_date: false,
+            date_format: DEFAULT_DATE_FORMAT.to_string(),
+            service: false,
+            template: None,
+            replies: None,
+            redact: None,
+        }
+    }
+}
+
+impl LogStyle {
+    pub(crate) fn needs_date(&self) -> bool {
+        self.with_date
+            || self
+                .template
+                .as_deref()
+                .is_some_and(|t| log_template::uses_field(t, Field::Date))
+    }
+}
+
+// строка лога "[дата ]name(id): [↳ ответ автор: ]текст" или по шаблону --format
+pub(crate) fn write_log_line<W: Write>(
+    out: &mut W,
+    style: &LogStyle,
+    msg: &Message,
+    msg_obj: &simd_json::borrowed::Object,
+    name: &str,
+    date: Option<NaiveDateTime>,
+    has_any_text: bool,
+) -> io::Result<()> {
+    let from_id = msg.from_id.unwrap_or("no_id");
+    if let Some(template) = &style.template {
+        for piece in template {
+            match piece {
+                Piece::Lit(s) => out.write_all(s.as_bytes())?,
+                Piece::Field(Field::Id) => {
+                    if let Some(id) = get_i64_field(msg_obj, "id") {
+                        write!(out, "{id}")?;
+                    }
+                }
+                Piece::Field(Field::Date) => write_log_date(out, style, msg_obj, date)?,
+                Piece::Field(Field::Name) => out.write_all(name.as_bytes())?,
+                Piece::Field(Field::FromId) => out.write_all(from_id.as_bytes())?,
+                Piece::Field(Field::Media) => {
+                    if let Some(kind) = msg.media_kind() {
+                        out.write_all(log_template::media_placeholder(kind).as_bytes())?;
+                    }
+                }
+                Piece::Field(Field::Text) => write_log_body(out, style, msg_obj, has_any_text)?,
+                Piece::Field(Field::Reply) => write_reply_marker(out, style, msg_obj)?,
+            }
+        }
+        return out.write_all(b"\n");
+    }
+
+    if style.with_date {
+        out.write_all(b"[")?;
+        write_log_date(out, style, msg_obj, date)?;
+        out.write_all(b"] ")?;
+    }
+
+    out.write_all(name.as_bytes())?;
+    out.write_all(b"(")?;
+    out.write_all(from_id.as_bytes())?;
+    out.write_all(b"): ")?;
+
+    write_reply_marker(out, style, msg_obj)?;
+    write_log_body(out, style, msg_obj, has_any_text)?;
+
+    out.write_all(b"\n")
+}
+
+// строка служебного события "[дата ]*** описание ***"
+pub(crate) fn write_service_line<W: Write>(
+    out: &mut W,
+    style: &LogStyle,
+    msg_obj: &simd_json::borrowed::Object,
+    date: Option<NaiveDateTime>,
+) -> io::Result<()> {
+    if style.needs_date() {
+        out.write_all(b"[")?;
+        write_log_date(out, style, msg_obj, date)?;
+        out.write_all(b"] ")?;
+    }
+    writeln!(out, "*** {} ***", service::describe(msg_obj))
+}
+
+pub(crate) fn write_log_date<W: Write>(
+    out: &mut W,
+    style: &LogStyle,
+    msg_obj: &simd_json::borrowed::Object,
+    date: Option<NaiveDateTime>,
+) -> io::Result<()> {
+    match date {
+        Some(dt) => write!(out, "{}", dt.format(&style.date_format)),
+        // нераспознанную дату печатаем как есть
+        None => write!(out, "{}", get_str_field(msg_obj, "date").unwrap_or("?")),
+    }
+}
+
+pub(crate) fn write_reply_marker<W: Write>(
+    out: &mut W,
+    style: &LogStyle,
+    msg_obj: &simd_json::borrowed::Object,
+) -> io::Result<()> {
+    if let Some(index) = &style.replies
+        && let Some(reply_to) = get_i64_field(msg_obj, "reply_to_message_id")
+    {
+        match index.get(reply_to) {
+            Some((author, quote)) if !quote.is_empty() => {
+                let quote = redacted(style, quote);
+                write!(out, "↳ ответ {author} «{quote}»: ")?
+            }
+            Some((author, _)) => write!(out, "↳ ответ {author}: ")?,
+            None => write!(out, "↳ ответ на #{reply_to}: ")?,
+        }
+    }
+    Ok(())
+}
+
+// текст сообщения; без текста — вопрос опроса, если есть
+pub(crate) fn write_log_body<W: Write>(
+    out: &mut W,
+    style: &LogStyle,
+    msg_obj: &simd_json::borrowed::Object,
+    has_any_text: bool,
+) -> io::Result<()> {
+    if has_any_text {
+        if let Some(text_val) = msg_obj.get("text") {
+            match &style.redact {
+                // ссылка бывает отдельным сегментом разметки — маскируем текст целиком
+                Some(r) => out.write_all(r.apply(&build_full_text(text_val)).as_bytes())?,
+                None => write_text_value(text_val, out)?,
+            }
+        }
+    } else if let Some(poll_val) = msg_obj.get("poll")
+        && let Some(q) = get_poll_question(poll_val)
+    {
+        out.write_all("[опрос: ".as_bytes())?;
+        out.write_all(redacted(style, q).as_bytes())?;
+        out.write_all(b"]")?;
+    }
+    Ok(())
+}
+
+/// Текст для лога: с --redact — с замаскированными личными данными.
+pub(crate) fn redacted<'a>(style: &LogStyle, text: &'a str) -> Cow<'a, str> {
+    match &style.redact {
+        Some(r) => r.apply(text),
+        None => Cow::Borrowed(text),
+    }
+}
+
+pub(crate) fn write_jsonl_line<W: Write>(
+    out: &mut W,
+    style: &LogStyle,
+    rec: &MessageRecord,
+) -> io::Result<()> {
+    let line = json!({
+        "id": rec.id,
+        "date": rec.date,
+        "author": rec.author,
+        "from_id": rec.from_id,
+        "text": redacted(style, &rec.text).as_ref(),
+        "media_type": rec.media_type,
+        "reply_to": rec.reply_to
+    });
+    line.write(out)?;
+    out.write_all(b"\n")
+}
+
+/// Нормализованное сообщение для табличных выгрузок (SQLite, Arrow).
+pub(crate) struct MessageRecord<'a> {
+    pub(crate) id: i64,
+    // как в экспорте и разобранная (с запасным date_unixtime)
+    pub(crate) date: Option<&'a str>,
+    pub(crate) datetime: Option<NaiveDateTime>,
+    pub(crate) author: &'a str,
+    pub(crate) from_id: &'a str,
+    pub(crate) text: String,
+    pub(crate) media_type: Option<&'a str>,
+    pub(crate) reply_to: Option<i64>,
+}
+
+impl<'a> MessageRecord<'a> {
+    pub(crate) fn new(
+        msg: &Message<'a>,
+        msg_obj: &'a simd_json::borrowed::Object,
+        author: &'a str,
+    ) -> Self {
+        let text = match msg_obj.get("text") {
+            Some(text_val) => build_full_text(text_val),
+            None => String::new(),
+        };
+        MessageRecord {
+            id: get_i64_field(msg_obj, "id").unwrap_or(0),
+            date: get_str_field(msg_obj, "date"),
+            datetime: get_msg_date(msg_obj, None),
+            author,
+            from_id: msg.from_id.unwrap_or("no_id"),
+            text,
+            media_type: msg.media_kind(),
+            reply_to: get_i64_field(msg_obj, "reply_to_message_id"),
+        }
+    }
+}
+
+//
+// ===================== ВЫВОД СТАТЫ =====================
+//
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum StatFormat {
+    /// Человекочитаемый текст
+    Text,
+    /// Структурированный JSON-документ
+    Json,
+    /// Таблицы CSV (в файлы stat_<таблица>.csv при --txt)
+    Csv,
+}
+
+/// Пишет статистику в файл(ы) stat.*, возвращает записанные пути.
+pub(crate) fn write_stats_to_files(
+    stats: &Stats,
+    format: StatFormat,
+    verbose: bool,
+) -> io::Result<Vec<String>> {
+    let path = match format {
+        StatFormat::Text => "stat.txt",
+        StatFormat::Json => "stat.json",
+        StatFormat::Csv => return stat_csv::write_stats_csv_files("stat", stats, verbose),
+    };
+    let file = File::create(path)?;
+    let mut w = BufWriter::new(file);
+    write_stats_as(&mut w, stats, format, verbose, Term::default())?;
+    w.flush()?;
+    Ok(vec![path.to_string()])
+}
+
+pub(crate) fn write_stats_as<W: Write>(
+    w: &mut W,
+    stats: &Stats,
+    format: StatFormat,
+    verbose: bool,
+    term: Term,
+) -> io::Result<()> {
+    match format {
+        StatFormat::Text => write_stats(w, stats, verbose, term),
+        StatFormat::Json => stat_json::write_stats_json(w, stats, verbose),
+        StatFormat::Csv => stat_csv::write_stats_csv(w, stats, verbose),
+    }
+}
+
+/// `term` — печать в терминал: участники и топ слов таблицами,
+/// гистограммы активности полосами.
+pub(crate) fn write_stats<W: Write>(
+    w: &mut W,
+    stats: &Stats,
+    verbose: bool,
+    term: Term,
+) -> io::Result<()> {
+    writeln!(w, "Чат: {}", stats.chat_name)?;
+    writeln!(w, "Всего сообщений: {}", stats.total_messages)?;
+    writeln!(
+        w,
+        "  сообщений с чем-то медийным: {}",
+        stats.messages_with_any_media
+    )?;
+    writeln!(w, "    фотографии: {}", stats.photo_messages)?;
+    writeln!(w, "    видео: {}", stats.video_messages)?;
+    if stats.video_durations.count > 0 {
+        writeln!(w, "      длительность: {}", stats.video_durations.summary())?;
+    }
+    writeln!(w, "    голосовые: {}", stats.voice_messages)?;
+    if stats.voice_durations.count > 0 {
+        writeln!(w, "      длительность: {}", stats.voice_durations.summary())?;
+    }
+    writeln!(w, "    аудио: {}", stats.audio_messages)?;
+    writeln!(w, "    GIF / анимации: {}", stats.gif_messages)?;
+    writeln!(w, "    стикеры: {}", stats.sticker_messages)?;
+    writeln!(
+        w,
+        "    файлы (без media_type): {}",
+        stats.file_messages
+    )?;
+    writeln!(w, "  опросов: {}", stats.poll_messages)?;
+    writeln!(w, "  пересланных сообщений: {}", stats.forwarded_messages)?;
+    writeln!(w, "  сообщений со ссылками: {}", stats.link_messages)?;
+    writeln!(w, "  уникальных авторов: {}", stats.per_author.len())?;
+    writeln!(w)?;
+
+    if stats.service.total > 0 {
+        stats.service.write_text(w)?;
+        writeln!(w)?;
+    }
+    if stats.media_sizes.files + stats.media_sizes.missing > 0 {
+        stats.media_sizes.write_text(w)?;
+        writeln!(w)?;
+    }
+    if stats.calls.total > 0 {
+        stats.calls.write_text(w)?;
+        writeln!(w)?;
+    }
+
+    // авторы
+    writeln!(w, "Сообщения по участникам:")?;
+    if term.tables {
+        let mut t = Table::new(&[
+            ("участник", Align::Left),
+            ("сообщений", Align::Right),
+            ("доля", Align::Right),
+        ]);
+        for (name, count) in sorted_by_count(&stats.per_author) {
+            let percent = percent_of(count, stats.total_messages);
+            t.row(vec![name.to_string(), count.to_string(), format!("{percent:.1}%")]);
+        }
+        t.write(w, term)?;
+    } else {
+        for (name, count) in sorted_by_count(&stats.per_author) {
+            let percent = percent_of(count, stats.total_messages);
+            writeln!(w, "- {}: {} ({:.1}%)", name, count, percent)?;
+        }
+    }
+
+    // совпадения --grep
+    if let Some(pattern) = &stats.grep_pattern {
+        writeln!(w)?;
+        writeln!(
+            w,
+            "Совпадения «{}»: {} в {} сообщениях",
+            pattern, stats.grep_matches, stats.total_messages
+        )?;
+        for (name, count) in sorted_by_count(&stats.grep_per_author) {
+            writeln!(w, "- {}: {}", name, count)?;
+        }
+    }
+
+    // --wordlist
+    for list in &stats.wordlists {
+        writeln!(w)?;
+        list.write_text(w, &stats.per_author)?;
+    }
+
+    // --counter
+    if !stats.counters.is_empty() {
+        writeln!(w)?;
+        stats.counters.report(w)?;
+    }
+
+    // --sentiment
+    if let Some(s) = &stats.sentiment {
+        writeln!(w)?;
+        s.write_text(w)?;
+    }
+
+    if verbose {
+        // ========== Топ слов (глобально) ==========
+        writeln!(w)?;
+        writeln!(w, "Топ слов (глобально){}:", approx_note(stats))?;
+        let top = top_by_count(&stats.word_freq, stats.top_words);
+        if term.tables {
+            let mut t = Table::new(&[("слово", Align::Left), ("раз", Align::Right)]);
+            for (word, count) in top {
+                t.row(vec![word.to_string(), count.to_string()]);
+            }
+            t.write(w, term)?;
+        } else {
+            for (word, count) in top {
+                writeln!(w, "- {}: {}", word, count)?;
+            }
+        }
+
+        // ========== Словарный запас ==========
+        writeln!(w)?;
+        vocab::write_richness(w, stats)?;
+
+        // ========== Характерные слова ==========
+        writeln!(w)?;
+        vocab::write_signature_words(w, stats)?;
+
+        // ========== Уникальное ==========
+        writeln!(w)?;
+        stats.uniques.write_text(w)?;
+
+        // ========== Активность по часам ==========
+        writeln!(w)?;
+        writeln!(w, "Активность по часам (0–23):")?;
+        let mut best_hour = 0usize;
+        let mut best_hour_count = 0usize;
+        for hour in 0..24 {
+            let c = stats.hour_hist[hour];
+            if c > best_hour_count {
+                best_hour_count = c;
+                best_hour = hour;
+            }
+            if !term.tables {
+                writeln!(w, "  {:02}:00–{:02}:59: {}", hour, hour, c)?;
+            }
+        }
+        if term.tables {
+            let rows: Vec<_> =
+                (0..24).map(|h| (format!("{h:02}:00–{h:02}:59"), stats.hour_hist[h])).collect();
+            table::write_bars(w, &rows, term)?;
+        }
+        writeln!(
+            w,
+            "Самый активный час: {:02}:00–{:02}:59 ({} сообщений)",
+            best_hour, best_hour, best_hour_count
+        )?;
+
+        // ========== Активность по дням месяца ==========
+        writeln!(w)?;
+        writeln!(w, "Активность по дням месяца:")?;
+        let mut best_day = 1usize;
+        let mut best_day_count = 0usize;
+        for day in 1..stats.day_hist.len() {
+            let c = stats.day_hist[day];
+            if c > best_day_count {
+                best_day_count = c;
+                best_day = day;
+            }
+            if !term.tables {
+                writeln!(w, "  {:02}: {}", day, c)?;
+            }
+        }
+        if term.tables {
+            let rows: Vec<_> =
+                (1..stats.day_hist.len()).map(|d| (format!("{d:02}"), stats.day_hist[d])).collect();
+            table::write_bars(w, &rows, term)?;
+        }
+        writeln!(
+            w,
+            "Самый активный день месяца: {:02} ({} сообщений)",
+            best_day, best_day_count
+        )?;
+
+        // ========== Активность по дням недели ==========
+        writeln!(w)?;
+        writeln!(w, "Активность по дням недели:")?;
+        if term.tables {
+            let rows: Vec<_> =
+                WEEKDAYS.iter().zip(stats.weekday_hist).map(|(n, c)| (n.to_string(), c)).collect();
+            table::write_bars(w, &rows, term)?;
+        } else {
+            for (name, c) in WEEKDAYS.iter().zip(stats.weekday_hist) {
+                writeln!(w, "  {}: {}", name, c)?;
+            }
+        }
+        // при равенстве — более ранний день недели
+        let busiest = (0..7).rev().max_by_key(|&d| stats.weekday_hist[d]).unwrap_or(0);
+        let quietest = (0..7).rev().min_by_key(|&d| stats.weekday_hist[d]).unwrap_or(0);
+        writeln!(
+            w,
+            "Самый активный день недели: {} ({} сообщений), самый тихий: {} ({})",
+            WEEKDAYS[busiest],
+            stats.weekday_hist[busiest],
+            WEEKDAYS[quietest],
+            stats.weekday_hist[quietest]
+        )?;
+
+        // ========== День недели × час ==========
+        writeln!(w)?;
+        heatmap::write_heatmap(w, &stats.week_hour)?;
+
+        // ========== Месяцы и годы ==========
+        writeln!(w)?;
+        stats.timeline.write_text(w)?;
+        writeln!(w)?;
+        stats.timeline.write_streaks(w)?;
+        if !stats.service.by_month.is_empty() {
+            writeln!(w)?;
+            stats.service.write_growth(w)?;
+        }
+
+        // ========== Первое и последнее сообщение ==========
+        writeln!(w)?;
+        stats.presence.write_text(w)?;
+
+        // ========== Длина сообщений ==========
+        writeln!(w)?;
+        stats.lengths.report(w)?;
+        writeln!(w)?;
+        stats.lengths.write_words_per_author(w)?;
+
+        // ========== Капс ==========
+        writeln!(w)?;
+        stats.shouting.report(w)?;
+
+        // ========== Стиль письма ==========
+        writeln!(w)?;
+        stats.style.report(w)?;
+
+        // ========== Вопросы ==========
+        writeln!(w)?;
+        stats.questions.report(w)?;
+
+        // ========== Спам ==========
+        writeln!(w)?;
+        writeln!(
+            w,
+            "Потенциальные спамеры (повторяющийся одинаковый текст){}:",
+            approx_note(stats)
+        )?;
+        for (author, extra) in spam_scores(stats).into_iter().take(stats.spam.top) {
+            writeln!(w, "- {}: {} дополнительных повторов", author, extra)?;
+            if stats.spam.details {
+                for (text, count) in spam_repeats(stats, author) {
+                    writeln!(w, "    {}× «{}»", count, text.replace('\n', " "))?;
+                }
+            }
+        }
+        writeln!(w)?;
+        writeln!(w, "Копипаста (одинаковый текст от разных людей):")?;
+        for c in copypasta(stats).into_iter().take(TOP_COPYPASTA) {
+            let mut quote: String = c.text.chars().take(COPYPASTA_QUOTE_CHARS).collect();
+            if quote.len() < c.text.len() {
+                quote.push('…');
+            }
+            writeln!(
+                w,
+                "- «{}» — {} авторов, {} раз: {}",
+                quote.replace('\n', " "),
+                c.authors.len(),
+                c.count,
+                c.authors.join(", ")
+            )?;
+        }
+        writeln!(w)?;
+        stats.forwards.write_spam(w, &stats.per_author, stats.spam.min_repeats)?;
+
+        // ========== Скорость ответов ==========
+        writeln!(w)?;
+        stats.interactions.write_latency(w)?;
+
+        // ========== Кто кому отвечает ==========
+        writeln!(w)?;
+        stats.interactions.write_matrix(w)?;
+
+        // ========== Реакции ==========
+        writeln!(w)?;
+        stats.reactions.write_text(w)?;
+
+        // ========== Разметка ==========
+        writeln!(w)?;
+        stats.entities.report(w)?;
+
+        // ========== Ссылки ==========
+        writeln!(w)?;
+        stats.links.report(w)?;
+
+        // ========== Пересылки ==========
+        writeln!(w)?;
+        stats.forwards.write_text(w)?;
+
+        // ========== Голосовые ==========
+        if stats.voice_durations.count > 0 {
+            writeln!(w)?;
+            stats.voice_durations.write_per_author(w, "Голосовые по авторам")?;
+        }
+
+        // ========== Видео ==========
+        let (video, round) = (&stats.video_durations, &stats.round_video_durations);
+        if video.count + round.count > 0 {
+            writeln!(w)?;
+            writeln!(
+                w,
+                "Видео: всего {} (видеофайлы: {}; кружки: {} шт., {})",
+                durations::format_duration(video.total_seconds + round.total_seconds),
+                video.summary(),
+                round.count,
+                round.summary()
+            )?;
+            writeln!(w, "Самые длинные видео:")?;
+            stats.longest_videos.write_text(w)?;
+        }
+
+        // ========== Стикеры ==========
+        writeln!(w)?;
+        stats.stickers.report(w)?;
+
+        // ========== Кастомные эмодзи ==========
+        writeln!(w)?;
+        stats.custom_emoji.report(w)?;
+
+        // ========== Команды ботам ==========
+        writeln!(w)?;
+        stats.commands.report(w)?;
+
+        // ========== Упоминания ==========
+        writeln!(w)?;
+        stats.mentions.report(w)?;
+
+        // ========== Закрепы ==========
+        writeln!(w)?;
+        pins::write_text(w, &stats.pins)?;
+
+        // ========== Название и фото чата ==========
+        if !stats.service.changes.is_empty() {
+            writeln!(w)?;
+            stats.service.write_changes(w)?;
+        }
+    }
+
+    for metric in stats.metrics() {
+        writeln!(w)?;
+        metric.report(w)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,131 @@
+//
+// ===================== ПОТОКИ (--threads) =====================
+//
+// Разбор JSON и запись лога остаются последовательными, но самая дорогая
+// часть -v — слова, спам, длины, стиль, разметка и ссылки — от порядка
+// сообщений не зависит. Такие сообщения откладываются, в конце чата
+// делятся на N кусков, каждый поток считает свой Stats, и куски
+// складываются в общий по порядку.
+//
+// Только для разбора в памяти: при --streaming откладывать нечего —
+// сообщение живёт, пока его читают.
+//
+
+use simd_json::BorrowedValue;
+
+use std::thread;
+
+use crate::model::Message;
+use crate::stopwords::Stopwords;
+use crate::{SpamConfig, Stats, add_counts, approx, observe_text};
+
+struct Deferred {
+    // номер сообщения в массиве messages чата
+    index: usize,
+    // сообщение после --anonymize / имён по from_id; None — как в массиве
+    rewritten: Option<BorrowedValue<'static>>,
+}
+
+pub struct TextJobs {
+    threads: usize,
+    current: usize,
+    pending: Vec<Deferred>,
+}
+
+impl TextJobs {
+    /// None — считать как обычно, в одном потоке.
+    pub fn new(threads: usize, verbose: bool) -> Option<Self> {
+        let threads = match threads {
+            0 => thread::available_parallelism().map_or(1, |n| n.get()),
+            n => n,
+        };
+        (verbose && threads > 1).then(|| TextJobs { threads, current: 0, pending: Vec::new() })
+    }
+
+    /// Номер сообщения, которое сейчас обрабатывается.
+    pub fn at(&mut self, index: usize) {
+        self.current = index;
+    }
+
+    pub fn defer(&mut self) {
+        let index = self.current;
+        self.pending.push(Deferred { index, rewritten: None });
+    }
+
+    /// Переписанная копия текущего сообщения, если оно отложено.
+    pub fn attach(&mut self, msg: BorrowedValue<'static>) {
+        if let Some(last) = self.pending.last_mut()
+            && last.index == self.current
+        {
+            last.rewritten = Some(msg);
+        }
+    }
+
+    /// Посчитать отложенное по сообщениям чата `messages` и добавить в `stats`.
+    pub fn run(
+        &mut self,
+        messages: &[BorrowedValue],
+        stats: &mut Stats,
+        stopwords: &Stopwords,
+        stem: bool,
+    ) {
+        let pending = std::mem::take(&mut self.pending);
+        if pending.is_empty() {
+            return;
+        }
+        let chunk = pending.len().div_ceil(self.threads);
+        let min_chars = stats.spam.min_chars;
+        let approx = stats.approx;
+        let parts: Vec<Stats> = thread::scope(|s| {
+            let workers: Vec<_> = pending
+                .chunks(chunk)
+                .map(|part| {
+                    s.spawn(move || {
+                        let spam = SpamConfig { min_chars, ..SpamConfig::default() };
+                        let mut local = Stats { spam, approx, ..Stats::default() };
+                        for d in part {
+                            let value = d.rewritten.as_ref().unwrap_or(&messages[d.index]);
+                            if let BorrowedValue::Object(msg_obj) = value
+                                && let Some(text_val) = msg_obj.get("text")
+                                && let Ok(msg) = Message::from_value(value)
+                            {
+                                observe_text(&mut local, stopwords, stem, &msg, msg_obj, text_val);
+                            }
+                        }
+                        local
+                    })
+                })
+                .collect();
+            workers.into_iter().map(|w| w.join().expect("поток статистики упал")).collect()
+        });
+        for part in parts {
+            merge(stats, part);
+        }
+    }
+}
+
+/// Добавить в `stats` то, что `observe_text` насчитал в `part` — следующем
+/// по порядку куске сообщений. Итог тот же, что при подсчёте подряд: счётчики
+/// складываются, а где важно первое появление, остаётся значение из `stats`.
+fn merge(stats: &mut Stats, part: Stats) {
+    add_counts(&mut stats.word_freq, part.word_freq);
+    approx::trim(&mut stats.word_freq, stats.approx);
+    for (author, words) in part.word_freq_per_author {
+        let into = stats.word_freq_per_author.entry(author).or_default();
+        add_counts(into, words);
+        approx::trim(into, stats.approx);
+    }
+    for (author, texts) in part.spam_map {
+        let into = stats.spam_map.entry(author).or_default();
+        add_counts(into, texts);
+        approx::trim(into, stats.approx);
+    }
+    stats.lengths.merge(part.lengths);
+    stats.shouting.merge(part.shouting);
+    stats.style.merge(part.style);
+    stats.entities.merge(part.entities);
+    stats.links.merge(part.links);
+    stats.custom_emoji.merge(part.custom_emoji);
+    stats.commands.merge(part.commands);
+    stats.uniques.merge(part.uniques);
+}
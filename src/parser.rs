@@ -0,0 +1,744 @@
+use simd_json::prelude::*;
+use simd_json::{BorrowedValue, StaticNode};
+
+use chrono::{DateTime, Local, NaiveDateTime};
+use chrono_tz::Tz;
+
+use crate::model::Message;
+use crate::output::OutputFormat;
+use crate::stats::Processor;
+use crate::stream::JsonStream;
+use crate::timings::timed;
+use crate::{authors, links, mmap, unpack};
+
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Instant;
+
+//
+// ===================== ОСНОВНОЙ ПАРСИНГ =====================
+//
+
+/// '*' и '?' в имени файла (не в папках): "exports/*.json".
+pub(crate) fn wildcard_match(pattern: &[char], name: &[char]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some(('*', rest)) => (0..=name.len()).any(|i| wildcard_match(rest, &name[i..])),
+        Some((&p, rest)) => name
+            .split_first()
+            .is_some_and(|(&c, tail)| (p == '?' || p == c) && wildcard_match(rest, tail)),
+    }
+}
+
+/// Шаблон -i -> подходящие файлы по имени; без '*' и '?' — сам путь.
+pub(crate) fn expand_glob(pattern: &str) -> Result<Vec<String>, String> {
+    let path = std::path::Path::new(pattern);
+    let Some(file_pattern) = path.file_name().map(|n| n.to_string_lossy())
+    else {
+        return Ok(vec![pattern.to_string()]);
+    };
+    if !file_pattern.contains(['*', '?']) {
+        return Ok(vec![pattern.to_string()]);
+    }
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    let wanted: Vec<char> = file_pattern.chars().collect();
+    let entries = std::fs::read_dir(&dir)
+        .map_err(|e| format!("Не удалось прочитать папку {}: {e}", dir.display()))?;
+    let mut found: Vec<String> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| {
+            let name: Vec<char> = e.file_name().to_string_lossy().chars().collect();
+            wildcard_match(&wanted, &name)
+        })
+        .map(|e| e.path().to_string_lossy().into_owned())
+        .collect();
+    if found.is_empty() {
+        return Err(format!("По шаблону «{pattern}» ничего не найдено"));
+    }
+    found.sort();
+    Ok(found)
+}
+
+/// -i указывает на папку: ищем в ней result.json, а если его нет — в
+/// единственной подпапке с экспортом (Telegram Desktop/ChatExport_.../).
+/// Папка, где нашёлся JSON, становится --media-dir, если та не задана.
+pub(crate) fn discover_export(input: &str) -> Result<Option<PathBuf>, String> {
+    let dir = PathBuf::from(input);
+    if !dir.is_dir() {
+        return Ok(None);
+    }
+    if dir.join("result.json").is_file() {
+        return Ok(Some(dir));
+    }
+    let entries = std::fs::read_dir(&dir)
+        .map_err(|e| format!("Не удалось прочитать папку {}: {e}", dir.display()))?;
+    let mut exports: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.join("result.json").is_file())
+        .collect();
+    exports.sort();
+    match exports.len() {
+        1 => Ok(exports.pop()),
+        0 if dir.join("messages.html").is_file() => Err(format!(
+            "В {} экспорт в HTML — нужен JSON (формат «Машиночитаемый JSON»)",
+            dir.display()
+        )),
+        0 => Err(format!("В папке {} нет result.json", dir.display())),
+        _ => {
+            let names: Vec<String> = exports
+                .iter()
+                .filter_map(|p| p.file_name().map(|n| n.to_string_lossy().into_owned()))
+                .collect();
+            Err(format!(
+                "В папке {} несколько экспортов ({}) — укажите один",
+                dir.display(),
+                names.join(", ")
+            ))
+        }
+    }
+}
+
+/// Экспорт больше этого размера автоматически читается потоково.
+pub(crate) const STREAMING_THRESHOLD: u64 = 512 * 1024 * 1024;
+
+/// -i - / -o -: stdin и stdout вместо файла.
+pub(crate) const STDIO: &str = "-";
+
+/// Имя чата, если в экспорте его нет.
+pub(crate) const NO_CHAT_NAME: &str = "<без имени>";
+
+/// Вход целиком в памяти: файл, распакованный архив или stdin.
+pub(crate) fn read_input(input: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    if input == STDIO {
+        let mut buf = Vec::new();
+        io::stdin().lock().read_to_end(&mut buf)?;
+        return unpack::unpack_bytes(buf, "stdin");
+    }
+    match unpack::unpack(input)? {
+        Some(buf) => Ok(buf),
+        None => Ok(std::fs::read(input)?),
+    }
+}
+
+/// Один вход: файл, архив или stdin.
+pub(crate) fn run_input(
+    input: &str,
+    streaming: bool,
+    proc: &mut Processor,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // stdin и архив читаются в память целиком, дальше — как обычный JSON
+    let unpacked = timed(&mut proc.timings.read, || {
+        if input == STDIO { read_input(input).map(Some) } else { unpack::unpack(input) }
+    })?;
+    if let Some(mut buf) = unpacked {
+        if streaming || buf.len() as u64 >= STREAMING_THRESHOLD {
+            run_streaming(|| Ok(buf.as_slice()), proc)
+        } else {
+            run_dom(&mut buf, proc)
+        }
+    } else {
+        let input_size = std::fs::metadata(input)?.len();
+        if streaming || input_size >= STREAMING_THRESHOLD {
+            let open = || Ok(BufReader::with_capacity(1 << 20, File::open(input)?));
+            run_streaming(open, proc)
+        } else if let Some(mut map) = timed(&mut proc.timings.read, || mmap::Mmap::open(input)) {
+            run_dom(&mut map, proc)
+        } else {
+            run_dom(&mut timed(&mut proc.timings.read, || std::fs::read(input))?, proc)
+        }
+    }
+}
+
+// весь файл в память + DOM со строками из этого буфера: быстро, но память ~ размер экспорта
+pub(crate) fn run_dom(
+    buf: &mut [u8],
+    proc: &mut Processor,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let root: BorrowedValue = timed(&mut proc.timings.parse, || simd_json::to_borrowed_value(buf))
+        .map_err(|e| format!("Ошибка парсинга JSON: {e}"))?;
+    run_dom_root(root, proc)
+}
+
+pub(crate) fn run_dom_root(
+    root: BorrowedValue,
+    proc: &mut Processor,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let root_obj = match &root {
+        BorrowedValue::Object(map) => map,
+        _ => return Err("Корень JSON не объект".into()),
+    };
+
+    if let Some(names) = proc.author_names.as_mut() {
+        for_each_dom_chat(root_obj, |chat_obj| {
+            if let Some(BorrowedValue::Array(messages)) = chat_obj.get("messages") {
+                for msg_val in messages.iter() {
+                    if let BorrowedValue::Object(msg_obj) = msg_val {
+                        names.observe(msg_obj);
+                    }
+                }
+            }
+        });
+        names.finish();
+    }
+
+    // экспорт одного чата: messages прямо в корне
+    if root_obj.contains_key("messages") {
+        return run_dom_chat(root_obj, proc, false);
+    }
+
+    let mut res = Ok(());
+    for_each_dom_chat(root_obj, |chat_obj| {
+        if res.is_ok() {
+            res = run_dom_chat(chat_obj, proc, true);
+        }
+    });
+    res
+}
+
+/// Чаты экспорта: корень одиночного экспорта или chats.list[] (+ left_chats.list[]).
+pub(crate) fn for_each_dom_chat<'a>(
+    root_obj: &'a simd_json::borrowed::Object,
+    mut f: impl FnMut(&'a simd_json::borrowed::Object),
+) {
+    if root_obj.contains_key("messages") {
+        f(root_obj);
+        return;
+    }
+    for section in ["chats", "left_chats"] {
+        let list = root_obj.get(section).and_then(|v| match v {
+            BorrowedValue::Object(obj) => obj.get("list"),
+            _ => None,
+        });
+        if let Some(BorrowedValue::Array(chats)) = list {
+            for chat_val in chats.iter() {
+                if let BorrowedValue::Object(chat_obj) = chat_val {
+                    f(chat_obj);
+                }
+            }
+        }
+    }
+}
+
+pub(crate) fn run_dom_chat(
+    chat_obj: &simd_json::borrowed::Object,
+    proc: &mut Processor,
+    nested: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // имя чата
+    let chat_name = get_str_field(chat_obj, "name").unwrap_or(NO_CHAT_NAME);
+    let chat_id = chat_obj.get("id").map(value_to_id).unwrap_or_default();
+
+    // messages
+    let messages_val = chat_obj
+        .get("messages")
+        .ok_or("В чате нет поля \"messages\"")?;
+
+    let messages = match messages_val {
+        BorrowedValue::Array(arr) => arr.as_ref(),
+        _ => return Err("\"messages\" не массив".into()),
+    };
+
+    proc.begin_chat(chat_name, &chat_id, nested)?;
+    for (i, msg_val) in messages.iter().enumerate() {
+        if let Some(jobs) = proc.text_jobs.as_mut() {
+            jobs.at(i);
+        }
+        proc.process_message(msg_val)?;
+    }
+    if let Some(jobs) = proc.text_jobs.as_mut() {
+        timed(&mut proc.timings.aggregate, || {
+            jobs.run(messages, &mut proc.stats, &proc.stopwords, proc.stem)
+        });
+    }
+
+    Ok(())
+}
+
+// по одному сообщению за раз: память ограничена размером сообщения;
+// `open` даёт вход с начала — для имён авторов он читается дважды
+pub(crate) fn run_streaming<R: BufRead>(
+    open: impl Fn() -> io::Result<R>,
+    proc: &mut Processor,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // сообщение живёт, пока его читают: откладывать для потоков нечего
+    proc.text_jobs = None;
+    let start = Instant::now();
+    let before = (proc.timings.process, proc.timings.output);
+    if let Some(names) = proc.author_names.as_mut() {
+        // имена нужны до первого сообщения: отдельный лёгкий проход по файлу
+        let mut stream = JsonStream::new(open()?);
+        stream_names(&mut stream, names, false)?;
+        names.finish();
+    }
+    let mut stream = JsonStream::new(open()?);
+    let res = stream_chat(&mut stream, proc, false);
+    // чтение и разбор — всё, кроме обработки сообщений и закрытия логов
+    let t = &mut proc.timings;
+    let busy = (t.process - before.0) + (t.output - before.1);
+    t.read_parse += start.elapsed().saturating_sub(busy);
+    t.process = before.0;
+    res
+}
+
+pub(crate) fn stream_names<R: BufRead>(
+    stream: &mut JsonStream<R>,
+    names: &mut authors::AuthorNames,
+    nested: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    stream.walk_object(|s, key| match key {
+        "messages" => s.walk_array(|msg_val| {
+            if let BorrowedValue::Object(msg_obj) = msg_val {
+                names.observe(msg_obj);
+            }
+            Ok(())
+        }),
+        "chats" | "left_chats" if !nested => s.walk_object(|s, key| match key {
+            "list" => s.walk_array_raw(|s| stream_names(s, names, true)),
+            _ => s.skip_value(),
+        }),
+        _ => s.skip_value(),
+    })
+}
+
+// корень одиночного экспорта и элементы chats.list устроены одинаково
+// (name, type, id, messages), в корне аккаунта вместо messages — chats
+pub(crate) fn stream_chat<R: BufRead>(
+    stream: &mut JsonStream<R>,
+    proc: &mut Processor,
+    nested: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut chat_name = NO_CHAT_NAME.to_string();
+    let mut chat_id = String::new();
+
+    stream.walk_object(|s, key| match key {
+        "name" => {
+            if let BorrowedValue::String(name) = s.read_value()? {
+                chat_name = name.into_owned();
+            }
+            Ok(())
+        }
+        "id" => {
+            chat_id = value_to_id(&s.read_value()?);
+            Ok(())
+        }
+        "messages" => {
+            proc.begin_chat(&chat_name, &chat_id, nested)?;
+            s.walk_array(|msg_val| {
+                let start = Instant::now();
+                proc.process_message(msg_val)?;
+                proc.timings.process += start.elapsed();
+                Ok(())
+            })
+        }
+        "chats" | "left_chats" if !nested => s.walk_object(|s, key| match key {
+            "list" => s.walk_array_raw(|s| stream_chat(s, proc, true)),
+            _ => s.skip_value(),
+        }),
+        _ => s.skip_value(),
+    })
+}
+
+//
+// ===================== ИТЕРАТОР СООБЩЕНИЙ =====================
+//
+// Тот же потоковый разбор, но сообщения не уходят в Processor, а
+// отдаются по одному: свои подсчёты в библиотеке без экспорта в памяти.
+// Экспорт аккаунта обходится чат за чатом, как в stream_chat.
+//
+
+/// Сообщение из [`MessageIter`]: разобранный JSON и чат, откуда оно.
+pub struct ChatMessage {
+    pub chat_name: Arc<str>,
+    pub chat_id: Arc<str>,
+    pub value: BorrowedValue<'static>,
+}
+
+impl ChatMessage {
+    /// Типизированный вид; строки заимствуются из `value`.
+    pub fn message(&self) -> Result<Message<'_>, simd_json::Error> {
+        Message::from_value(&self.value)
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Frame {
+    // корень экспорта или элемент chats.list
+    Chat { root: bool, first: bool },
+    // "chats" / "left_chats" в корне экспорта аккаунта
+    ChatLists { first: bool },
+    ChatList { first: bool },
+    Messages { first: bool },
+}
+
+/// Сообщения экспорта по одному, без чтения файла целиком. Служебные
+/// сообщения тоже отдаются — тип в [`Message::kind`].
+///
+/// ```no_run
+/// use std::fs::File;
+/// use std::io::BufReader;
+/// use tgjsps::MessageIter;
+///
+/// let mut voice = 0;
+/// for item in MessageIter::new(BufReader::new(File::open("result.json")?)) {
+///     let item = item?;
+///     if item.message()?.media_type.is_some_and(|m| m.as_str() == "voice_message") {
+///         voice += 1;
+///     }
+/// }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct MessageIter<R: BufRead> {
+    stream: JsonStream<R>,
+    frames: Vec<Frame>,
+    chat_name: Arc<str>,
+    chat_id: Arc<str>,
+    scratch: Vec<u8>,
+    started: bool,
+    failed: bool,
+}
+
+impl<R: BufRead> MessageIter<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            stream: JsonStream::new(reader),
+            frames: Vec::new(),
+            chat_name: Arc::from(NO_CHAT_NAME),
+            chat_id: Arc::from(""),
+            scratch: Vec::new(),
+            started: false,
+            failed: false,
+        }
+    }
+
+    fn step(&mut self) -> Result<Option<ChatMessage>, Box<dyn std::error::Error>> {
+        if !self.started {
+            self.started = true;
+            if unpack::is_packed(self.stream.peek_raw()?) {
+                return Err("MessageIter читает только JSON: архив нужно распаковать".into());
+            }
+            self.stream.begin_object()?;
+            self.frames.push(Frame::Chat { root: true, first: true });
+        }
+        while let Some(frame) = self.frames.last_mut() {
+            match *frame {
+                Frame::Chat { root, first } => {
+                    *frame = Frame::Chat { root, first: false };
+                    let Some(key) = self.stream.next_key(first, &mut self.scratch)? else {
+                        self.frames.pop();
+                        continue;
+                    };
+                    match key.as_str() {
+                        "name" => {
+                            if let BorrowedValue::String(name) = self.stream.read_value()? {
+                                self.chat_name = Arc::from(name.as_ref());
+                            }
+                        }
+                        "id" => self.chat_id = Arc::from(value_to_id(&self.stream.read_value()?)),
+                        "messages" => {
+                            self.stream.begin_array()?;
+                            self.frames.push(Frame::Messages { first: true });
+                        }
+                        "chats" | "left_chats" if root => {
+                            self.stream.begin_object()?;
+                            self.frames.push(Frame::ChatLists { first: true });
+                        }
+                        _ => self.stream.skip_value()?,
+                    }
+                }
+                Frame::ChatLists { first } => {
+                    *frame = Frame::ChatLists { first: false };
+                    match self.stream.next_key(first, &mut self.scratch)?.as_deref() {
+                        None => {
+                            self.frames.pop();
+                        }
+                        Some("list") => {
+                            self.stream.begin_array()?;
+                            self.frames.push(Frame::ChatList { first: true });
+                        }
+                        Some(_) => self.stream.skip_value()?,
+                    }
+                }
+                Frame::ChatList { first } => {
+                    *frame = Frame::ChatList { first: false };
+                    if self.stream.next_element(first)? {
+                        self.chat_name = Arc::from(NO_CHAT_NAME);
+                        self.chat_id = Arc::from("");
+                        self.stream.begin_object()?;
+                        self.frames.push(Frame::Chat { root: false, first: true });
+                    } else {
+                        self.frames.pop();
+                    }
+                }
+                Frame::Messages { first } => {
+                    *frame = Frame::Messages { first: false };
+                    if !self.stream.next_element(first)? {
+                        self.frames.pop();
+                        continue;
+                    }
+                    return Ok(Some(ChatMessage {
+                        chat_name: self.chat_name.clone(),
+                        chat_id: self.chat_id.clone(),
+                        value: self.stream.read_value()?,
+                    }));
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl<R: BufRead> Iterator for MessageIter<R> {
+    type Item = Result<ChatMessage, Box<dyn std::error::Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let res = self.step();
+        // после ошибки разбора позиция в потоке не определена
+        self.failed = res.is_err();
+        res.transpose()
+    }
+}
+
+pub(crate) fn value_to_id(v: &BorrowedValue) -> String {
+    match v {
+        BorrowedValue::String(s) => s.to_string(),
+        BorrowedValue::Static(n) => n.to_string(),
+        _ => String::new(),
+    }
+}
+
+/// Имя, пригодное для файла или папки: всё, кроме букв, цифр и '-', -> '_'.
+pub(crate) fn safe_file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' { c } else { '_' })
+        .collect()
+}
+
+/// Имя файла лога для чата из экспорта аккаунта: chat_<имя>.txt
+/// (.jsonl для JSONL) рядом с основным выходным файлом.
+pub(crate) fn chat_output_path(output_path: &str, chat_name: &str, format: OutputFormat) -> String {
+    let safe = safe_file_name(chat_name);
+    let file_name = format!("chat_{safe}.{}", format.extension());
+    match std::path::Path::new(output_path).parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => {
+            dir.join(file_name).to_string_lossy().into_owned()
+        }
+        _ => file_name,
+    }
+}
+
+//
+// ===================== ХЕЛПЕРЫ ПО JSON =====================
+//
+
+// "date" — локальное время машины, где делали экспорт; с --timezone
+// основной источник — date_unixtime (UTC), переведённый в нужный пояс.
+// Без пояса date_unixtime — запасной вариант, если "date" не разобрать
+// (старые и локализованные экспорты): тогда берём местный пояс.
+pub(crate) fn get_msg_date(
+    msg_obj: &simd_json::borrowed::Object,
+    tz: Option<Tz>,
+) -> Option<NaiveDateTime> {
+    resolve_date(get_str_field(msg_obj, "date"), get_unixtime(msg_obj), tz)
+}
+
+pub(crate) fn resolve_date(
+    date: Option<&str>,
+    unixtime: Option<i64>,
+    tz: Option<Tz>,
+) -> Option<NaiveDateTime> {
+    if let Some(tz) = tz
+        && let Some(ts) = unixtime
+    {
+        return DateTime::from_timestamp(ts, 0).map(|utc| utc.with_timezone(&tz).naive_local());
+    }
+    if let Some(dt) = date.and_then(parse_msg_date) {
+        return Some(dt);
+    }
+    DateTime::from_timestamp(unixtime?, 0).map(|utc| utc.with_timezone(&Local).naive_local())
+}
+
+// в экспортах date_unixtime — строка с числом, но бывает и числом
+pub(crate) fn get_unixtime(msg_obj: &simd_json::borrowed::Object) -> Option<i64> {
+    match msg_obj.get("date_unixtime")? {
+        BorrowedValue::String(s) => s.parse().ok(),
+        _ => get_i64_field(msg_obj, "date_unixtime"),
+    }
+}
+
+pub(crate) fn parse_msg_date(date_str: &str) -> Option<NaiveDateTime> {
+    if let Ok(dt) = NaiveDateTime::parse_from_str(date_str, "%Y-%m-%dT%H:%M:%S") {
+        return Some(dt);
+    }
+    // пробел вместо T, дробные секунды, смещение пояса
+    if let Ok(dt) = NaiveDateTime::parse_from_str(date_str, "%Y-%m-%d %H:%M:%S%.f") {
+        return Some(dt);
+    }
+    if let Ok(dt) = NaiveDateTime::parse_from_str(date_str, "%Y-%m-%dT%H:%M:%S%.f") {
+        return Some(dt);
+    }
+    DateTime::parse_from_rfc3339(date_str)
+        .ok()
+        .map(|dt| dt.naive_local())
+}
+
+/// Поля сообщений в экспорте Telegram Desktop; остальные -vv показывает
+/// как незнакомые — возможно, формат экспорта поменялся.
+pub(crate) const KNOWN_MESSAGE_FIELDS: &[&str] = &[
+    "id", "type", "date", "date_unixtime", "edited", "edited_unixtime", "from", "from_id",
+    "author", "forwarded_from", "forwarded_from_id", "saved_from", "reply_to_message_id",
+    "reply_to_peer_id", "via_bot", "actor", "actor_id", "action", "title", "members",
+    "message_id", "inviter", "duration_seconds", "discard_reason", "text", "text_entities",
+    "photo", "photo_file_size", "width", "height", "file", "file_name", "file_size", "thumbnail",
+    "thumbnail_file_size", "media_type", "sticker_emoji", "mime_type", "performer", "duration",
+    "self_destruct_period_seconds", "contact_information", "contact_vcard",
+    "location_information", "live_location_period_seconds", "place_name", "address",
+    "game_title", "game_description", "game_link", "game_message_id", "score",
+    "invoice_information", "poll", "reactions", "inline_bot_buttons", "emoticon", "new_title",
+    "new_icon_emoji_id", "boosts", "to_id", "to", "distance", "values", "period",
+];
+
+/// "#id" сообщения для диагностики.
+pub(crate) fn msg_ref(msg_obj: &simd_json::borrowed::Object) -> String {
+    match get_i64_field(msg_obj, "id") {
+        Some(id) => format!("#{id}"),
+        None => "без id".to_string(),
+    }
+}
+
+pub(crate) fn get_i64_field(obj: &simd_json::borrowed::Object, key: &str) -> Option<i64> {
+    match obj.get(key)? {
+        BorrowedValue::Static(StaticNode::I64(n)) => Some(*n),
+        BorrowedValue::Static(StaticNode::U64(n)) => i64::try_from(*n).ok(),
+        _ => None,
+    }
+}
+
+pub(crate) fn get_str_field<'a>(
+    obj: &'a simd_json::borrowed::Object,
+    key: &str,
+) -> Option<&'a str> {
+    obj.get(key).and_then(|v| match v {
+        BorrowedValue::String(s) => Some(s.as_ref()),
+        _ => None,
+    })
+}
+
+/// Имя из пары полей "from"/"from_id" (или "actor"/"actor_id"). У удалённых
+/// аккаунтов имя null: тогда "Удалённый аккаунт (user123)", чтобы разные
+/// удалённые не сливались в одного Unknown.
+pub(crate) fn author_name<'a>(
+    obj: &'a simd_json::borrowed::Object,
+    name_key: &str,
+    id_key: &str,
+) -> Cow<'a, str> {
+    display_name(get_str_field(obj, name_key), get_str_field(obj, id_key))
+}
+
+pub(crate) fn display_name<'a>(name: Option<&'a str>, id: Option<&str>) -> Cow<'a, str> {
+    match (name, id) {
+        (Some(name), _) => Cow::Borrowed(name),
+        (None, Some(id)) => Cow::Owned(format!("Удалённый аккаунт ({id})")),
+        (None, None) => Cow::Borrowed("Unknown"),
+    }
+}
+
+// обход всех текстовых сегментов (строки и obj["text"])
+pub(crate) fn for_each_text_segment<'a, F>(v: &'a BorrowedValue, mut f: F)
+where
+    F: FnMut(&'a str),
+{
+    match v {
+        BorrowedValue::String(s) => f(s.as_ref()),
+        BorrowedValue::Array(arr) => {
+            for part in arr.as_ref().iter() {
+                match part {
+                    BorrowedValue::String(s) => f(s.as_ref()),
+                    BorrowedValue::Object(obj) => {
+                        if let Some(BorrowedValue::String(t)) = obj.get("text") {
+                            f(t.as_ref());
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Размеченные куски текста (тип, текст, сам объект — для href и т.п.):
+/// из "text_entities", а в старых экспортах без него — из объектов
+/// внутри массива "text".
+pub(crate) fn for_each_entity<'a, F>(msg_obj: &'a simd_json::borrowed::Object, mut f: F)
+where
+    F: FnMut(&'a str, &'a str, &'a simd_json::borrowed::Object),
+{
+    let parts = match msg_obj.get("text_entities").or_else(|| msg_obj.get("text")) {
+        Some(BorrowedValue::Array(arr)) => arr,
+        _ => return,
+    };
+    for part in parts.iter() {
+        if let BorrowedValue::Object(obj) = part
+            && let Some(kind) = get_str_field(obj, "type")
+            && let Some(text) = get_str_field(obj, "text")
+        {
+            f(kind, text, obj);
+        }
+    }
+}
+
+// лёгкая запись текста без аллокаций (используется и в обычном, и в verbose)
+pub(crate) fn write_text_value<W: Write>(v: &BorrowedValue, w: &mut W) -> io::Result<()> {
+    let mut res: io::Result<()> = Ok(());
+    for_each_text_segment(v, |s| {
+        if res.is_ok()
+            && let Err(e) = w.write_all(s.as_bytes())
+        {
+            res = Err(e);
+        }
+    });
+    res
+}
+
+pub(crate) fn text_is_empty(v: &BorrowedValue) -> bool {
+    let mut any = false;
+    let mut all_empty = true;
+    for_each_text_segment(v, |s| {
+        any = true;
+        if !s.is_empty() {
+            all_empty = false;
+        }
+    });
+    if !any {
+        true
+    } else {
+        all_empty
+    }
+}
+
+pub(crate) fn msg_has_link(msg_obj: &simd_json::borrowed::Object) -> bool {
+    let mut res = false;
+    links::for_each_url(msg_obj, |_| res = true);
+    res
+}
+
+pub(crate) fn get_poll_question<'a>(poll_val: &'a BorrowedValue) -> Option<&'a str> {
+    match poll_val {
+        BorrowedValue::Object(obj) => obj
+            .get("question")
+            .and_then(|v| match v {
+                BorrowedValue::String(s) => Some(s.as_ref()),
+                _ => None,
+            }),
+        _ => None,
+    }
+}
@@ -0,0 +1,84 @@
+//
+// ===================== ЗАКРЕПЛЁННЫЕ СООБЩЕНИЯ =====================
+//
+// pin_message ссылается на id уже прошедшего сообщения, поэтому текст
+// достаётся из индекса сообщений (как у ответов, но с цитатой подлиннее).
+// Закрепы обычно и есть самое важное в группе — отдельная история.
+//
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use crate::replies::MessageIndex;
+
+pub const PIN_QUOTE_CHARS: usize = 300;
+
+pub struct PinnedMessage {
+    pub chat: String,
+    // когда и кем закреплено
+    pub date: String,
+    pub pinned_by: String,
+    pub id: i64,
+    // None — сообщения нет в экспорте (удалено или до начала выгрузки)
+    pub author: Option<String>,
+    pub text: String,
+}
+
+crate::cache::codec!(PinnedMessage { chat, date, pinned_by, id, author, text });
+
+impl PinnedMessage {
+    pub fn resolve(
+        index: &MessageIndex,
+        chat: &str,
+        date: String,
+        pinned_by: &str,
+        id: i64,
+    ) -> Self {
+        let (author, text) = match index.get(id) {
+            Some((author, quote)) => (Some(author.to_string()), quote.to_string()),
+            None => (None, String::new()),
+        };
+        Self {
+            chat: chat.to_string(),
+            date,
+            pinned_by: pinned_by.to_string(),
+            id,
+            author,
+            text,
+        }
+    }
+}
+
+fn write_pin_line<W: Write>(w: &mut W, p: &PinnedMessage) -> io::Result<()> {
+    write!(w, "- {} {} закрепляет #{}", p.date, p.pinned_by, p.id)?;
+    match &p.author {
+        Some(author) if p.text.is_empty() => writeln!(w, " ({author}): (без текста)"),
+        Some(author) => writeln!(w, " ({author}): «{}»", p.text),
+        None => writeln!(w, ": (нет в экспорте)"),
+    }
+}
+
+pub fn write_text<W: Write>(w: &mut W, pins: &[PinnedMessage]) -> io::Result<()> {
+    writeln!(w, "История закреплённых сообщений: {}", pins.len())?;
+    for p in pins {
+        write_pin_line(w, p)?;
+    }
+    Ok(())
+}
+
+/// Отдельный файл с историей закрепов; в экспорте аккаунта — по чатам.
+pub fn write_pins_file(path: &str, pins: &[PinnedMessage]) -> io::Result<()> {
+    let mut w = BufWriter::new(File::create(path)?);
+    let mut chat: Option<&str> = None;
+    for p in pins {
+        if chat != Some(p.chat.as_str()) {
+            if chat.is_some() {
+                writeln!(w)?;
+            }
+            writeln!(w, "=== {} ===", p.chat)?;
+            chat = Some(&p.chat);
+        }
+        write_pin_line(&mut w, p)?;
+    }
+    w.flush()
+}
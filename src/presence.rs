@@ -0,0 +1,82 @@
+//
+// ===================== ПЕРВОЕ И ПОСЛЕДНЕЕ СООБЩЕНИЕ =====================
+//
+// Когда автор впервые и в последний раз писал в чат и сколько у него
+// дней с сообщениями. Кто молчит дольше SILENT_DAYS к концу истории —
+// помечается: скорее всего, ушёл или забросил чат.
+//
+
+use ahash::{AHashMap, AHashSet};
+use chrono::{NaiveDate, NaiveDateTime};
+
+use std::io::{self, Write};
+
+const SILENT_DAYS: i64 = 30;
+
+pub struct Presence {
+    pub first: NaiveDateTime,
+    pub last: NaiveDateTime,
+    pub days: AHashSet<NaiveDate>,
+}
+
+crate::cache::codec!(Presence { first, last, days });
+
+#[derive(Default)]
+pub struct PresenceStats {
+    pub per_author: AHashMap<String, Presence>,
+}
+
+crate::cache::codec!(PresenceStats { per_author });
+
+impl PresenceStats {
+    pub fn observe(&mut self, author: &str, dt: NaiveDateTime) {
+        match self.per_author.get_mut(author) {
+            Some(p) => {
+                p.first = p.first.min(dt);
+                p.last = p.last.max(dt);
+                p.days.insert(dt.date());
+            }
+            None => {
+                let p = Presence { first: dt, last: dt, days: AHashSet::from([dt.date()]) };
+                self.per_author.insert(author.to_string(), p);
+            }
+        }
+    }
+
+    /// Последнее сообщение в истории вообще.
+    pub fn history_end(&self) -> Option<NaiveDateTime> {
+        self.per_author.values().map(|p| p.last).max()
+    }
+
+    /// Авторы по дате первого сообщения.
+    pub fn authors(&self) -> Vec<(&str, &Presence)> {
+        let mut v: Vec<_> = self.per_author.iter().map(|(a, p)| (a.as_str(), p)).collect();
+        v.sort_by(|a, b| a.1.first.cmp(&b.1.first).then_with(|| a.0.cmp(b.0)));
+        v
+    }
+
+    pub fn write_text<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(w, "Первое и последнее сообщение (активных дней):")?;
+        let Some(end) = self.history_end() else {
+            return Ok(());
+        };
+        for (author, p) in self.authors() {
+            let silent = (end.date() - p.last.date()).num_days();
+            let mark = if silent > SILENT_DAYS {
+                format!(", молчит {silent} дн.")
+            } else {
+                String::new()
+            };
+            writeln!(
+                w,
+                "- {}: {} — {} ({}{})",
+                author,
+                p.first.format("%Y-%m-%d"),
+                p.last.format("%Y-%m-%d"),
+                p.days.len(),
+                mark
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,138 @@
+//
+// ===================== ВОПРОСЫ =====================
+//
+// Вопрос — сообщение, в тексте которого есть "?". Отвеченным считается
+// вопрос, на который потом ответил (reply) другой участник. id уникальны
+// только в пределах чата, поэтому открытые вопросы сбрасываются между чатами.
+//
+
+use ahash::AHashMap;
+use simd_json::BorrowedValue;
+use simd_json::borrowed::Object;
+
+use std::io::{self, Write};
+
+use crate::model::Message;
+use crate::{Metric, for_each_text_segment, get_i64_field, percent_of};
+
+const TOP_QUESTION_AUTHORS: usize = 20;
+
+#[derive(Default, Clone, Copy)]
+pub struct QuestionCounts {
+    pub asked: usize,
+    pub answered: usize,
+}
+
+crate::cache::codec!(QuestionCounts { asked, answered });
+
+#[derive(Default)]
+pub struct QuestionStats {
+    // сообщений с текстом
+    pub messages: usize,
+    // с "?" где угодно / в конце текста
+    pub containing: usize,
+    pub ending: usize,
+    pub answered: usize,
+    pub per_author: AHashMap<String, QuestionCounts>,
+    // id ещё не отвеченного вопроса -> автор
+    open: AHashMap<i64, String>,
+}
+
+crate::cache::codec!(QuestionStats { messages, containing, ending, answered, per_author, open });
+
+impl QuestionStats {
+    pub fn new_chat(&mut self) {
+        self.open.clear();
+    }
+
+    /// Любое сообщение: не ответ ли это на открытый вопрос.
+    fn observe_reply(&mut self, author: &str, msg_obj: &Object) {
+        let Some(reply_to) = get_i64_field(msg_obj, "reply_to_message_id") else {
+            return;
+        };
+        if self.open.get(&reply_to).is_none_or(|asker| asker == author) {
+            return;
+        }
+        if let Some(asker) = self.open.remove(&reply_to) {
+            self.answered += 1;
+            self.per_author.entry(asker).or_default().answered += 1;
+        }
+    }
+
+    /// Сообщение с текстом.
+    fn observe_text(&mut self, author: &str, msg_obj: &Object, text_val: &BorrowedValue) {
+        self.messages += 1;
+        let mut has_question = false;
+        let mut last = None;
+        for_each_text_segment(text_val, |s| {
+            has_question |= s.contains('?');
+            if let Some(c) = s.trim_end().chars().next_back() {
+                last = Some(c);
+            }
+        });
+        if !has_question {
+            return;
+        }
+        self.containing += 1;
+        if last == Some('?') {
+            self.ending += 1;
+        }
+        match self.per_author.get_mut(author) {
+            Some(c) => c.asked += 1,
+            None => self.per_author.entry(author.to_string()).or_default().asked += 1,
+        }
+        if let Some(id) = get_i64_field(msg_obj, "id") {
+            self.open.insert(id, author.to_string());
+        }
+    }
+
+    pub fn authors_by_questions(&self) -> Vec<(&str, QuestionCounts)> {
+        let mut v: Vec<_> = self
+            .per_author
+            .iter()
+            .filter(|(_, c)| c.asked > 0)
+            .map(|(a, &c)| (a.as_str(), c))
+            .collect();
+        v.sort_by_key(|p| std::cmp::Reverse(p.1.asked));
+        v
+    }
+}
+
+impl Metric for QuestionStats {
+    // ответ на вопрос считается, даже если в ответе нет текста (стикер, голосовое)
+    fn observe(&mut self, msg: &Message) {
+        let Some(msg_obj) = msg.raw else {
+            return;
+        };
+        let author = msg.author();
+        self.observe_reply(&author, msg_obj);
+        if let Some(text_val) = msg.text_value() {
+            self.observe_text(&author, msg_obj, text_val);
+        }
+    }
+
+    fn report(&self, w: &mut dyn Write) -> io::Result<()> {
+        writeln!(
+            w,
+            "Вопросы: {} ({:.1}% сообщений с текстом), заканчиваются на «?»: {}, \
+             получили ответ: {} ({:.1}%)",
+            self.containing,
+            percent_of(self.containing, self.messages),
+            self.ending,
+            self.answered,
+            percent_of(self.answered, self.containing)
+        )?;
+        writeln!(w, "  по участникам (вопросов / с ответом):")?;
+        for (author, c) in self.authors_by_questions().into_iter().take(TOP_QUESTION_AUTHORS) {
+            writeln!(
+                w,
+                "  - {}: {} / {} ({:.1}%)",
+                author,
+                c.asked,
+                c.answered,
+                percent_of(c.answered, c.asked)
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,179 @@
+//
+// ===================== РЕАКЦИИ =====================
+//
+// "reactions": [{"type": "emoji", "count": 3, "emoji": "👍",
+//                "recent": [{"from": "...", "from_id": "...", "date": "..."}]}]
+// Кто поставил реакцию, известно только по "recent" — для популярных
+// сообщений это неполный список, поэтому "поставлено" считается по нему.
+//
+
+use ahash::AHashMap;
+use simd_json::borrowed::Object;
+use simd_json::{BorrowedValue, StaticNode};
+
+use std::io::{self, Write};
+
+use crate::replies::make_quote;
+use crate::{author_name, get_str_field, top_by_count};
+
+const TOP_REACTIONS: usize = 10;
+const TOP_MESSAGES: usize = 10;
+const EXCERPT_CHARS: usize = 80;
+
+pub struct TopMessage {
+    pub reactions: usize,
+    pub id: i64,
+    pub author: String,
+    pub date: String,
+    pub excerpt: String,
+}
+
+crate::cache::codec!(TopMessage { reactions, id, author, date, excerpt });
+
+#[derive(Default)]
+pub struct ReactionStats {
+    pub total: usize,
+    pub messages_with_reactions: usize,
+    // эмодзи (или custom:<id>) -> сколько раз поставили
+    pub by_emoji: AHashMap<String, usize>,
+    // автор сообщения -> сколько реакций получил
+    pub received: AHashMap<String, usize>,
+    // кто ставил (по "recent") -> сколько
+    pub given: AHashMap<String, usize>,
+    // "лучшее" чата: не больше TOP_MESSAGES, по убыванию реакций
+    pub top_messages: Vec<TopMessage>,
+}
+
+crate::cache::codec!(ReactionStats {
+    total, messages_with_reactions, by_emoji, received, given, top_messages,
+});
+
+fn count_of(obj: &Object) -> usize {
+    match obj.get("count") {
+        Some(BorrowedValue::Static(StaticNode::U64(n))) => *n as usize,
+        Some(BorrowedValue::Static(StaticNode::I64(n))) => (*n).max(0) as usize,
+        _ => 1,
+    }
+}
+
+fn reaction_key(obj: &Object) -> String {
+    match get_str_field(obj, "type").unwrap_or("") {
+        "custom_emoji" => match get_str_field(obj, "document_id") {
+            Some(id) => format!("custom:{id}"),
+            None => "custom".to_string(),
+        },
+        "paid" => "⭐ (платная)".to_string(),
+        _ => get_str_field(obj, "emoji").unwrap_or("?").to_string(),
+    }
+}
+
+impl ReactionStats {
+    /// Возвращает число реакций на сообщение.
+    pub fn observe(&mut self, author: &str, reactions_val: &BorrowedValue) -> usize {
+        let BorrowedValue::Array(list) = reactions_val else {
+            return 0;
+        };
+
+        let mut on_message = 0usize;
+        for r in list.iter() {
+            let BorrowedValue::Object(obj) = r else {
+                continue;
+            };
+            let count = count_of(obj);
+            on_message += count;
+            *self.by_emoji.entry(reaction_key(obj)).or_insert(0) += count;
+
+            if let Some(BorrowedValue::Array(recent)) = obj.get("recent") {
+                for who in recent.iter() {
+                    if let BorrowedValue::Object(who) = who {
+                        let name = author_name(who, "from", "from_id");
+                        *self.given.entry(name.into_owned()).or_insert(0) += 1;
+                    }
+                }
+            }
+        }
+
+        if on_message > 0 {
+            self.total += on_message;
+            self.messages_with_reactions += 1;
+            *self.received.entry(author.to_string()).or_insert(0) += on_message;
+        }
+        on_message
+    }
+
+    /// Кандидат в топ самых популярных сообщений; цитата строится,
+    /// только если сообщение действительно попадает в топ.
+    pub fn offer_top(
+        &mut self,
+        reactions: usize,
+        id: i64,
+        author: &str,
+        date: &str,
+        text_val: Option<&BorrowedValue>,
+    ) {
+        if reactions == 0 {
+            return;
+        }
+        let full = self.top_messages.len() >= TOP_MESSAGES;
+        if full && self.top_messages.last().is_some_and(|m| m.reactions >= reactions) {
+            return;
+        }
+
+        let excerpt = match text_val {
+            Some(v) => make_quote(v, EXCERPT_CHARS).into_string(),
+            None => String::new(),
+        };
+        let pos = self.top_messages.partition_point(|m| m.reactions >= reactions);
+        self.top_messages.insert(
+            pos,
+            TopMessage {
+                reactions,
+                id,
+                author: author.to_string(),
+                date: date.to_string(),
+                excerpt,
+            },
+        );
+        self.top_messages.truncate(TOP_MESSAGES);
+    }
+
+    pub fn write_text<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(
+            w,
+            "Реакции: {} на {} сообщениях",
+            self.total, self.messages_with_reactions
+        )?;
+        if self.total == 0 {
+            return Ok(());
+        }
+
+        writeln!(w, "  популярные:")?;
+        for (emoji, count) in top_by_count(&self.by_emoji, TOP_REACTIONS) {
+            writeln!(w, "  - {}: {}", emoji, count)?;
+        }
+        writeln!(w, "  больше всех получили:")?;
+        for (name, count) in top_by_count(&self.received, TOP_REACTIONS) {
+            writeln!(w, "  - {}: {}", name, count)?;
+        }
+        writeln!(w, "  больше всех ставили (по последним реакциям):")?;
+        for (name, count) in top_by_count(&self.given, TOP_REACTIONS) {
+            writeln!(w, "  - {}: {}", name, count)?;
+        }
+
+        writeln!(w)?;
+        writeln!(w, "Самые популярные сообщения (по реакциям):")?;
+        for (i, m) in self.top_messages.iter().enumerate() {
+            writeln!(
+                w,
+                "{:>2}. {} реакций — {}, {} (#{}): «{}»",
+                i + 1,
+                m.reactions,
+                m.author,
+                m.date,
+                m.id,
+                m.excerpt
+            )?;
+        }
+        Ok(())
+    }
+}
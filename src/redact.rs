@@ -0,0 +1,68 @@
+//
+// ===================== МАСКИРОВКА ЛИЧНЫХ ДАННЫХ (--redact) =====================
+//
+// Телефоны, почта и ссылки в тексте лога заменяются метками [телефон],
+// [email], [ссылка]. Статистика считается по исходному тексту — маскируется
+// только то, что уходит в лог (текст, цитаты ответов, вопросы опросов).
+//
+// Телефон — международный с "+" (10–15 цифр, с пробелами, дефисами и
+// скобками) или 10–11 цифр группами 3-3-2-2 с необязательной 8/7 впереди.
+// Десятизначные номера заказов тоже попадут под маску — для выгрузки,
+// которую отдают наружу, лучше перестараться. Даты и время не похожи.
+//
+
+use regex::Regex;
+
+use std::borrow::Cow;
+
+const EMAIL: &str = r"[\w.%+-]+@[\w-]+(?:\.[\w-]+)*\.\w{2,}";
+// без хвостовой пунктуации: "см. www.site.ru, там" -> "см. [ссылка], там"
+const LINK: &str = r"(?i)\b(?:https?://|tg://|www\.|t\.me/)[^\s«»<>]*[^\s«»<>.,;:!?)]";
+const PHONE: &str = concat!(
+    r"\+\d(?:[\s\-()]{0,2}\d){9,14}",
+    r"|(?:\b[78][\s-]?\(?|\(|\b)\d{3}\)?[\s-]?\d{3}[\s-]?\d{2}[\s-]?\d{2}\b"
+);
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RedactKind {
+    /// Телефонные номера
+    Phones,
+    /// Адреса электронной почты
+    Emails,
+    /// Ссылки (http(s)://, www., t.me/)
+    Links,
+}
+
+pub struct Redactor {
+    // по порядку: почта раньше ссылок, иначе домен из адреса станет ссылкой
+    rules: Vec<(Regex, &'static str)>,
+}
+
+impl Redactor {
+    pub fn new(kinds: &[RedactKind]) -> Self {
+        let mut rules = Vec::new();
+        let mut add = |pattern: &str, label| {
+            rules.push((Regex::new(pattern).expect("шаблон маскировки"), label));
+        };
+        if kinds.contains(&RedactKind::Emails) {
+            add(EMAIL, "[email]");
+        }
+        if kinds.contains(&RedactKind::Links) {
+            add(LINK, "[ссылка]");
+        }
+        if kinds.contains(&RedactKind::Phones) {
+            add(PHONE, "[телефон]");
+        }
+        Self { rules }
+    }
+
+    pub fn apply<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let mut out = Cow::Borrowed(text);
+        for (re, label) in &self.rules {
+            if let Cow::Owned(s) = re.replace_all(&out, *label) {
+                out = Cow::Owned(s);
+            }
+        }
+        out
+    }
+}
@@ -0,0 +1,97 @@
+//
+// ===================== ИНДЕКС СООБЩЕНИЙ ДЛЯ ОТВЕТОВ =====================
+//
+// id сообщения -> автор (+ короткая цитата), чтобы по reply_to_message_id
+// показать, кому отвечают. Имена авторов хранятся один раз, в записи —
+// только индекс, так что индекс терпим по памяти и в потоковом режиме.
+// id уникальны только в пределах чата — между чатами индекс чистится.
+//
+
+use ahash::AHashMap;
+use simd_json::BorrowedValue;
+
+use crate::for_each_text_segment;
+
+struct Indexed {
+    author: u32,
+    quote: Box<str>,
+}
+
+pub struct MessageIndex {
+    names: Vec<String>,
+    name_ids: AHashMap<String, u32>,
+    by_id: AHashMap<i64, Indexed>,
+    quote_chars: usize,
+}
+
+impl MessageIndex {
+    /// `quote_chars == 0` — цитаты не храним, только автора.
+    pub fn new(quote_chars: usize) -> Self {
+        Self {
+            names: Vec::new(),
+            name_ids: AHashMap::new(),
+            by_id: AHashMap::new(),
+            quote_chars,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.by_id.clear();
+    }
+
+    pub fn insert(&mut self, id: i64, author: &str, text_val: Option<&BorrowedValue>) {
+        let author = match self.name_ids.get(author) {
+            Some(&idx) => idx,
+            None => {
+                let idx = self.names.len() as u32;
+                self.names.push(author.to_string());
+                self.name_ids.insert(author.to_string(), idx);
+                idx
+            }
+        };
+        let quote = match text_val {
+            Some(v) if self.quote_chars > 0 => make_quote(v, self.quote_chars),
+            _ => Box::from(""),
+        };
+        self.by_id.insert(id, Indexed { author, quote });
+    }
+
+    /// (автор, цитата) сообщения с данным id, если оно встречалось.
+    pub fn get(&self, id: i64) -> Option<(&str, &str)> {
+        let m = self.by_id.get(&id)?;
+        Some((self.names[m.author as usize].as_str(), &m.quote))
+    }
+}
+
+/// Первые `limit` символов текста в одну строку, с многоточием при обрезке.
+pub fn make_quote(v: &BorrowedValue, limit: usize) -> Box<str> {
+    let mut out = String::new();
+    let mut taken = 0usize;
+    let mut truncated = false;
+    let mut pending_space = false;
+
+    for_each_text_segment(v, |seg| {
+        for c in seg.chars() {
+            if taken >= limit {
+                truncated = true;
+                return;
+            }
+            if c.is_whitespace() {
+                pending_space = !out.is_empty();
+                continue;
+            }
+            if pending_space {
+                out.push(' ');
+                taken += 1;
+                pending_space = false;
+            }
+            out.push(c);
+            taken += 1;
+        }
+    });
+
+    if truncated {
+        out.push('…');
+    }
+    out.into_boxed_str()
+}
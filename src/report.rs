@@ -0,0 +1,382 @@
+//
+// ===================== HTML-ОТЧЁТ =====================
+//
+// Один самодостаточный файл: стили и графики (SVG) встроены, внешних
+// ресурсов нет — можно просто переслать участникам чата.
+// Подсказки при наведении — через <title> у столбцов.
+//
+// Поверх статичных SVG — небольшой встроенный скрипт (report/dashboard.js):
+// он берёт статистику из встроенного JSON и перерисовывает участников,
+// месяцы, часы и дни недели с подсказками и включением авторов. Без
+// JavaScript отчёт остаётся прежним.
+//
+
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use simd_json::prelude::*;
+
+use crate::heatmap::{WeekHourGrid, grid_max};
+use crate::stat_json::build_stats_json;
+use crate::stats::approx_note;
+use crate::{Stats, WEEKDAYS_SHORT, percent_of, sorted_by_count, top_by_count};
+
+/// Сколько участников показываем отдельно, остальные — одной строкой.
+const REPORT_TOP_AUTHORS: usize = 15;
+
+const STYLE: &str = "
+body { font-family: -apple-system, 'Segoe UI', Roboto, sans-serif; margin: 0;
+       background: #f4f6f8; color: #222; }
+main { max-width: 960px; margin: 0 auto; padding: 24px; }
+h1 { margin: 0 0 4px; }
+.sub { color: #666; margin-bottom: 24px; }
+section { background: #fff; border-radius: 8px; padding: 16px 20px; margin-bottom: 20px;
+          box-shadow: 0 1px 3px rgba(0,0,0,.08); }
+h2 { font-size: 18px; margin: 0 0 12px; }
+.cards { display: flex; flex-wrap: wrap; gap: 12px; }
+.card { flex: 1 1 140px; background: #eef3f8; border-radius: 6px; padding: 10px 12px; }
+.card b { display: block; font-size: 22px; }
+svg text { font-size: 12px; fill: #333; }
+svg rect.bar { fill: #4a90d9; }
+svg rect.bar:hover { fill: #f5a623; }
+.legend { display: flex; flex-wrap: wrap; gap: 6px; margin-bottom: 10px; }
+.legend button { border: 1px solid #4a90d9; background: #eef3f8; border-radius: 12px;
+                 padding: 2px 10px; cursor: pointer; font: inherit; font-size: 13px; }
+.legend button.off { opacity: .4; text-decoration: line-through; }
+.tip { position: absolute; display: none; pointer-events: none; background: #222; color: #fff;
+       padding: 4px 8px; border-radius: 4px; font-size: 12px; white-space: nowrap; }
+";
+
+const DASHBOARD_JS: &str = include_str!("report/dashboard.js");
+
+pub fn esc(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Содержимое <svg> и его размеры; обёртка — своя для отчёта и для --charts.
+pub struct Chart {
+    pub width: usize,
+    pub height: usize,
+    pub body: String,
+}
+
+impl Chart {
+    /// В отчёте график тянется по ширине страницы.
+    fn inline(&self) -> String {
+        format!(
+            "<svg viewBox=\"0 0 {} {}\" width=\"100%\" role=\"img\">{}</svg>",
+            self.width, self.height, self.body
+        )
+    }
+}
+
+/// Горизонтальные столбцы: подпись слева, значение справа.
+pub fn svg_hbars(items: &[(String, usize)], total: usize) -> Chart {
+    let row_h = 22;
+    let label_w = 220;
+    let bar_w = 600;
+    let max = items.iter().map(|(_, c)| *c).max().unwrap_or(0).max(1);
+    let height = items.len() * row_h + 4;
+
+    let mut svg = String::new();
+    for (i, (label, count)) in items.iter().enumerate() {
+        let y = i * row_h;
+        let w = (*count as f64 / max as f64 * bar_w as f64).round() as usize;
+        let pct = percent_of(*count, total);
+        let _ = write!(
+            svg,
+            "<text x=\"{}\" y=\"{}\" text-anchor=\"end\">{}</text>\
+             <rect class=\"bar\" x=\"{label_w}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"3\">\
+             <title>{}: {count} ({pct:.1}%)</title></rect>\
+             <text x=\"{}\" y=\"{}\">{count} ({pct:.1}%)</text>",
+            label_w - 8,
+            y + 15,
+            esc(label),
+            y + 3,
+            w.max(1),
+            row_h - 6,
+            esc(label),
+            label_w + w + 6,
+            y + 15,
+        );
+    }
+    Chart { width: label_w + bar_w + 120, height, body: svg }
+}
+
+/// Вертикальные столбцы (гистограмма по часам и т.п.).
+pub fn svg_vbars(labels: &[String], values: &[usize]) -> Chart {
+    let col_w = 34;
+    let chart_h = 180;
+    let max = values.iter().copied().max().unwrap_or(0).max(1);
+    let width = labels.len() * col_w;
+
+    let mut svg = String::new();
+    for (i, (label, &v)) in labels.iter().zip(values).enumerate() {
+        let h = (v as f64 / max as f64 * chart_h as f64).round() as usize;
+        let x = i * col_w;
+        let _ = write!(
+            svg,
+            "<rect class=\"bar\" x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"2\">\
+             <title>{}: {v}</title></rect>\
+             <text x=\"{}\" y=\"{}\" text-anchor=\"middle\">{}</text>",
+            x + 3,
+            chart_h - h,
+            col_w - 6,
+            h.max(1),
+            esc(label),
+            x + col_w / 2,
+            chart_h + 16,
+            esc(label),
+        );
+    }
+    Chart { width, height: chart_h + 24, body: svg }
+}
+
+/// Сообщения по месяцам: линия с заливкой, подписи — не чаще 12 на ось.
+pub fn svg_timeline(months: &[(String, usize)]) -> Chart {
+    let axis_w = 48;
+    let plot_w = 800;
+    let plot_h = 200;
+    let max = months.iter().map(|(_, c)| *c).max().unwrap_or(0).max(1);
+    let step = months.len().div_ceil(12).max(1);
+    // один месяц — точка посередине
+    let x = |i: usize| match months.len() {
+        0 | 1 => axis_w + plot_w / 2,
+        n => axis_w + i * plot_w / (n - 1),
+    };
+    let y = |c: usize| plot_h - (c as f64 / max as f64 * plot_h as f64).round() as usize;
+
+    let mut svg = String::new();
+    let _ = write!(
+        svg,
+        "<line x1=\"{axis_w}\" y1=\"{plot_h}\" x2=\"{}\" y2=\"{plot_h}\" stroke=\"#999\"/>\
+         <text x=\"{}\" y=\"12\" text-anchor=\"end\">{max}</text>\
+         <text x=\"{}\" y=\"{plot_h}\" text-anchor=\"end\">0</text>",
+        axis_w + plot_w,
+        axis_w - 6,
+        axis_w - 6,
+    );
+    let points: Vec<String> =
+        months.iter().enumerate().map(|(i, (_, c))| format!("{},{}", x(i), y(*c))).collect();
+    let points = points.join(" ");
+    let _ = write!(
+        svg,
+        "<polygon points=\"{},{plot_h} {points} {},{plot_h}\" fill=\"#4a90d9\" \
+         fill-opacity=\"0.25\"/>\
+         <polyline points=\"{points}\" fill=\"none\" stroke=\"#4a90d9\" stroke-width=\"2\"/>",
+        x(0),
+        x(months.len().saturating_sub(1)),
+    );
+    for (i, (label, count)) in months.iter().enumerate() {
+        let _ = write!(
+            svg,
+            "<circle class=\"point\" cx=\"{}\" cy=\"{}\" r=\"3\" fill=\"#4a90d9\">\
+             <title>{}: {count}</title></circle>",
+            x(i),
+            y(*count),
+            esc(label)
+        );
+        if i % step == 0 {
+            let _ = write!(
+                svg,
+                "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\">{}</text>",
+                x(i),
+                plot_h + 16,
+                esc(label)
+            );
+        }
+    }
+    Chart { width: axis_w + plot_w + 24, height: plot_h + 24, body: svg }
+}
+
+/// Сетка 7×24: чем насыщеннее клетка, тем больше сообщений.
+pub fn svg_heatmap(grid: &WeekHourGrid) -> Chart {
+    let cell = 30;
+    let label_w = 30;
+    let max = grid_max(grid).max(1);
+
+    let mut svg = String::new();
+    for hour in 0..24 {
+        let _ = write!(
+            svg,
+            "<text x=\"{}\" y=\"14\" text-anchor=\"middle\">{hour:02}</text>",
+            label_w + hour * cell + cell / 2
+        );
+    }
+    for (day, row) in grid.iter().enumerate() {
+        let y = 20 + day * cell;
+        let _ = write!(
+            svg,
+            "<text x=\"0\" y=\"{}\">{}</text>",
+            y + cell / 2 + 4,
+            WEEKDAYS_SHORT[day]
+        );
+        for (hour, &count) in row.iter().enumerate() {
+            let opacity = count as f64 / max as f64;
+            let _ = write!(
+                svg,
+                "<rect x=\"{}\" y=\"{y}\" width=\"{}\" height=\"{}\" rx=\"3\" \
+                 fill=\"#4a90d9\" fill-opacity=\"{:.2}\">\
+                 <title>{} {hour:02}:00 — {count}</title></rect>",
+                label_w + hour * cell + 1,
+                cell - 2,
+                cell - 2,
+                0.06 + opacity * 0.94,
+                WEEKDAYS_SHORT[day]
+            );
+        }
+    }
+    Chart { width: label_w + 24 * cell, height: 7 * cell + 20, body: svg }
+}
+
+/// Участники по числу сообщений; после первых 15 — одна строка "остальные".
+pub fn author_items(stats: &Stats) -> Vec<(String, usize)> {
+    let authors = sorted_by_count(&stats.per_author);
+    let mut items: Vec<(String, usize)> = authors
+        .iter()
+        .take(REPORT_TOP_AUTHORS)
+        .map(|(n, c)| (n.to_string(), *c))
+        .collect();
+    if authors.len() > REPORT_TOP_AUTHORS {
+        let rest: usize = authors[REPORT_TOP_AUTHORS..].iter().map(|(_, c)| c).sum();
+        let others = authors.len() - REPORT_TOP_AUTHORS;
+        items.push((format!("остальные ({others})"), rest));
+    }
+    items
+}
+
+fn section(html: &mut String, title: &str, body: &str) {
+    let _ = write!(html, "<section><h2>{}</h2>{body}</section>", esc(title));
+}
+
+/// Блок, который скрипт перерисует интерактивным графиком `kind`.
+fn interactive(kind: &str, chart: &Chart) -> String {
+    format!("<div data-chart=\"{kind}\">{}</div>", chart.inline())
+}
+
+/// Статистика для скрипта; "<" экранируется, чтобы строка из чата не
+/// закрыла <script>.
+fn stats_data(stats: &Stats) -> String {
+    build_stats_json(stats, true).encode().replace('<', "\\u003c")
+}
+
+pub fn build_report(stats: &Stats) -> String {
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        "<!DOCTYPE html><html lang=\"ru\"><head><meta charset=\"utf-8\">\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\
+         <title>{}</title><style>{STYLE}</style></head><body><main>\
+         <h1>{}</h1><div class=\"sub\">Статистика экспорта Telegram</div>",
+        esc(&stats.chat_name),
+        esc(&stats.chat_name),
+    );
+
+    // ===== карточки с итогами =====
+    let mut cards = String::from("<div class=\"cards\">");
+    for (label, value) in [
+        ("сообщений", stats.total_messages),
+        ("участников", stats.per_author.len()),
+        ("с медиа", stats.messages_with_any_media),
+        ("со ссылками", stats.link_messages),
+        ("пересланных", stats.forwarded_messages),
+        ("опросов", stats.poll_messages),
+    ] {
+        let _ = write!(cards, "<div class=\"card\"><b>{value}</b>{label}</div>");
+    }
+    cards.push_str("</div>");
+    section(&mut html, "Итого", &cards);
+
+    // ===== доли участников =====
+    section(
+        &mut html,
+        "Доли участников",
+        &interactive("authors", &svg_hbars(&author_items(stats), stats.total_messages)),
+    );
+
+    // ===== лента по месяцам =====
+    let months = stats.timeline.months();
+    if !months.is_empty() {
+        section(
+            &mut html,
+            "Сообщения по месяцам",
+            &interactive("months", &svg_timeline(&months)),
+        );
+    }
+
+    // ===== активность по часам =====
+    let hour_labels: Vec<String> = (0..24).map(|h| format!("{h:02}")).collect();
+    section(
+        &mut html,
+        "Активность по часам",
+        &interactive("hours", &svg_vbars(&hour_labels, &stats.hour_hist)),
+    );
+
+    // ===== активность по дням недели =====
+    let weekday_labels: Vec<String> = WEEKDAYS_SHORT.map(String::from).to_vec();
+    section(
+        &mut html,
+        "Активность по дням недели",
+        &interactive("weekdays", &svg_vbars(&weekday_labels, &stats.weekday_hist)),
+    );
+    section(
+        &mut html,
+        "День недели × час",
+        &svg_heatmap(&stats.week_hour).inline(),
+    );
+
+    // ===== медиа =====
+    let media: Vec<(String, usize)> = [
+        ("фотографии", stats.photo_messages),
+        ("видео", stats.video_messages),
+        ("голосовые", stats.voice_messages),
+        ("аудио", stats.audio_messages),
+        ("GIF / анимации", stats.gif_messages),
+        ("стикеры", stats.sticker_messages),
+        ("файлы", stats.file_messages),
+        ("опросы", stats.poll_messages),
+    ]
+    .into_iter()
+    .map(|(l, c)| (l.to_string(), c))
+    .collect();
+    section(
+        &mut html,
+        "Медиа",
+        &svg_hbars(&media, stats.messages_with_any_media).inline(),
+    );
+
+    // ===== топ слов =====
+    let words: Vec<(String, usize)> = top_by_count(&stats.word_freq, stats.top_words)
+        .into_iter()
+        .map(|(w, c)| (w.to_string(), c))
+        .collect();
+    let total_words: usize = stats.word_freq.values().sum();
+    let title = format!("Топ слов{}", approx_note(stats));
+    section(&mut html, &title, &svg_hbars(&words, total_words).inline());
+
+    let _ = writeln!(
+        html,
+        "</main><script type=\"application/json\" id=\"stats-data\">{}</script>\
+         <script>{DASHBOARD_JS}</script></body></html>",
+        stats_data(stats)
+    );
+    html
+}
+
+pub fn write_report(path: &str, stats: &Stats) -> io::Result<()> {
+    let mut w = BufWriter::new(File::create(path)?);
+    w.write_all(build_report(stats).as_bytes())?;
+    w.flush()
+}
@@ -0,0 +1,182 @@
+// Интерактивные графики HTML-отчёта. Данные — встроенный JSON статистики
+// (#stats-data), библиотек нет: файл должен открываться без сети.
+// Без JavaScript в блоках остаются статичные SVG.
+(function () {
+  "use strict";
+
+  const NS = "http://www.w3.org/2000/svg";
+  const TOP_AUTHORS = 15;
+  const WEEKDAYS = ["пн", "вт", "ср", "чт", "пт", "сб", "вс"];
+  const data = JSON.parse(document.getElementById("stats-data").textContent);
+
+  const tip = document.createElement("div");
+  tip.className = "tip";
+  document.body.appendChild(tip);
+
+  function svgEl(name, attrs, parent) {
+    const node = document.createElementNS(NS, name);
+    for (const [k, v] of Object.entries(attrs)) {
+      node.setAttribute(k, v);
+    }
+    if (parent) {
+      parent.appendChild(node);
+    }
+    return node;
+  }
+
+  function text(parent, x, y, label, anchor) {
+    const t = svgEl("text", { x: x, y: y, "text-anchor": anchor || "start" }, parent);
+    t.textContent = label;
+    return t;
+  }
+
+  // подсказка идёт за курсором
+  function hover(node, label) {
+    node.addEventListener("mousemove", (e) => {
+      tip.textContent = label;
+      tip.style.display = "block";
+      tip.style.left = e.pageX + 12 + "px";
+      tip.style.top = e.pageY + 12 + "px";
+    });
+    node.addEventListener("mouseleave", () => {
+      tip.style.display = "none";
+    });
+  }
+
+  function percent(n, total) {
+    return total > 0 ? ((n * 100) / total).toFixed(1) : "0.0";
+  }
+
+  function chart(width, height) {
+    return svgEl("svg", { viewBox: "0 0 " + width + " " + height, width: "100%", role: "img" });
+  }
+
+  // ===== участники: кнопки включают и выключают авторов =====
+  function authors(box) {
+    const sorted = Object.entries(data.per_author || {}).sort((a, b) => b[1] - a[1]);
+    const entries = sorted.slice(0, TOP_AUTHORS);
+    if (sorted.length > TOP_AUTHORS) {
+      const rest = sorted.slice(TOP_AUTHORS).reduce((s, e) => s + e[1], 0);
+      entries.push(["остальные (" + (sorted.length - TOP_AUTHORS) + ")", rest]);
+    }
+    const hidden = new Set();
+    const legend = document.createElement("div");
+    legend.className = "legend";
+    const area = document.createElement("div");
+
+    function draw() {
+      const shown = entries.filter((e) => !hidden.has(e[0]));
+      const total = shown.reduce((s, e) => s + e[1], 0);
+      const max = Math.max(1, ...shown.map((e) => e[1]));
+      const rowH = 22, labelW = 220, barW = 600;
+      const svg = chart(labelW + barW + 120, shown.length * rowH + 4);
+      shown.forEach(([name, count], i) => {
+        const y = i * rowH;
+        const w = Math.max(1, Math.round((count / max) * barW));
+        const pct = percent(count, total);
+        text(svg, labelW - 8, y + 15, name, "end");
+        const bar = svgEl("rect", {
+          class: "bar", x: labelW, y: y + 3, width: w, height: rowH - 6, rx: 3,
+        }, svg);
+        hover(bar, name + ": " + count + " (" + pct + "% от показанных)");
+        text(svg, labelW + w + 6, y + 15, count + " (" + pct + "%)");
+      });
+      area.replaceChildren(svg);
+    }
+
+    for (const [name] of entries) {
+      const button = document.createElement("button");
+      button.type = "button";
+      button.textContent = name;
+      button.addEventListener("click", () => {
+        if (hidden.has(name)) {
+          hidden.delete(name);
+        } else {
+          hidden.add(name);
+        }
+        button.classList.toggle("off", hidden.has(name));
+        draw();
+      });
+      legend.appendChild(button);
+    }
+    box.replaceChildren(legend, area);
+    draw();
+  }
+
+  // ===== вертикальные столбцы с подсказками =====
+  function columns(box, labels, values, describe) {
+    const colW = 34, chartH = 180;
+    const total = values.reduce((s, v) => s + v, 0);
+    const max = Math.max(1, ...values);
+    const svg = chart(labels.length * colW, chartH + 24);
+    labels.forEach((label, i) => {
+      const v = values[i] || 0;
+      const h = Math.max(1, Math.round((v / max) * chartH));
+      const bar = svgEl("rect", {
+        class: "bar", x: i * colW + 3, y: chartH - h, width: colW - 6, height: h, rx: 2,
+      }, svg);
+      hover(bar, describe(label) + ": " + v + " (" + percent(v, total) + "%)");
+      text(svg, i * colW + colW / 2, chartH + 16, label, "middle");
+    });
+    box.replaceChildren(svg);
+  }
+
+  // ===== сообщения по месяцам: линия, подсказка у ближайшей точки =====
+  function months(box) {
+    const entries = Object.entries(data.months || {}).sort((a, b) => a[0].localeCompare(b[0]));
+    if (entries.length === 0) {
+      return;
+    }
+    const axisW = 48, plotW = 800, plotH = 200;
+    const max = Math.max(1, ...entries.map((e) => e[1]));
+    const step = Math.max(1, Math.ceil(entries.length / 12));
+    const x = (i) =>
+      entries.length > 1 ? axisW + (i * plotW) / (entries.length - 1) : axisW + plotW / 2;
+    const y = (c) => plotH - Math.round((c / max) * plotH);
+    const svg = chart(axisW + plotW + 24, plotH + 24);
+    svgEl("line", { x1: axisW, y1: plotH, x2: axisW + plotW, y2: plotH, stroke: "#999" }, svg);
+    text(svg, axisW - 6, 12, String(max), "end");
+    text(svg, axisW - 6, plotH, "0", "end");
+    const points = entries.map((e, i) => x(i) + "," + y(e[1])).join(" ");
+    svgEl("polygon", {
+      points: x(0) + "," + plotH + " " + points + " " + x(entries.length - 1) + "," + plotH,
+      fill: "#4a90d9", "fill-opacity": 0.25,
+    }, svg);
+    svgEl("polyline", { points: points, fill: "none", stroke: "#4a90d9", "stroke-width": 2 }, svg);
+    const marker = svgEl("circle", { r: 5, fill: "#f5a623", visibility: "hidden" }, svg);
+    entries.forEach(([label], i) => {
+      if (i % step === 0) {
+        text(svg, x(i), plotH + 16, label, "middle");
+      }
+    });
+    // невидимые полосы на всю высоту: навести проще, чем на точку
+    const slot = entries.length > 1 ? plotW / (entries.length - 1) : plotW;
+    entries.forEach(([label, count], i) => {
+      const zone = svgEl("rect", {
+        x: x(i) - slot / 2, y: 0, width: slot, height: plotH, fill: "transparent",
+      }, svg);
+      hover(zone, label + ": " + count);
+      zone.addEventListener("mouseenter", () => {
+        marker.setAttribute("cx", x(i));
+        marker.setAttribute("cy", y(count));
+        marker.setAttribute("visibility", "visible");
+      });
+      zone.addEventListener("mouseleave", () => marker.setAttribute("visibility", "hidden"));
+    });
+    box.replaceChildren(svg);
+  }
+
+  const hourLabels = Array.from({ length: 24 }, (_, h) => String(h).padStart(2, "0"));
+  const draw = {
+    authors: authors,
+    months: months,
+    hours: (box) => columns(box, hourLabels, data.hour_hist || [], (h) => h + ":00–" + h + ":59"),
+    weekdays: (box) => columns(box, WEEKDAYS, data.weekday_hist || [], (d) => d),
+  };
+  for (const box of document.querySelectorAll("[data-chart]")) {
+    const render = draw[box.dataset.chart];
+    if (render) {
+      render(box);
+    }
+  }
+})();
@@ -0,0 +1,198 @@
+//
+// ===================== СКРИПТЫ (--script) =====================
+//
+// Свои счётчики без пересборки tgjsps: скрипт — любая исполняемая
+// программа (#!/usr/bin/env python3, lua, rhai-run...). Он получает на
+// stdin сообщения, прошедшие фильтры, по одному JSON в строке:
+//
+//   {"id": 5, "date": "2024-01-01T10:00:00", "author": "Alice",
+//    "from_id": "user1", "text": "...", "media": "photo",
+//    "forwarded": false, "reply_to": 3}
+//
+// а всё, что он напечатает в stdout до выхода, становится разделом в
+// конце текстовой статистики. Служебные сообщения скрипту не передаются.
+//
+// --filter-script — тот же JSON, но на каждую строку скрипт отвечает
+// строкой "1" (оставить) или "0" (отбросить) и только потом получает
+// следующую. Фильтр стоит после встроенных (--since, --author, --no-bots)
+// и до --grep, лога и статистики. Обмен построчный, так что скрипт должен
+// сбрасывать stdout после каждого ответа (flush=True, io.write + flush).
+//
+// Встроенного интерпретатора (rhai, Lua) нет: tgjsps собирается без него,
+// а внешняя программа на любом языке даёт и свои счётчики, и свои фильтры.
+//
+
+use simd_json::json;
+use simd_json::prelude::*;
+
+use chrono_tz::Tz;
+
+use crate::Metric;
+use crate::model::Message;
+
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::thread::{self, JoinHandle};
+
+fn spawn(path: &Path) -> io::Result<Child> {
+    Command::new(path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| {
+            io::Error::new(e.kind(), format!("скрипт «{}» не запустился: {e}", path.display()))
+        })
+}
+
+fn fail(path: &Path, what: impl std::fmt::Display) -> io::Error {
+    io::Error::other(format!("скрипт «{}»: {what}", path.display()))
+}
+
+/// Сообщение строкой JSON, как его видят скрипты.
+fn message_line(msg: &Message, timezone: Option<Tz>) -> String {
+    let date = msg.date(timezone).map(|dt| dt.format("%Y-%m-%dT%H:%M:%S").to_string());
+    json!({
+        "id": msg.id,
+        "date": date,
+        "author": msg.author().as_ref(),
+        "from_id": msg.from_id,
+        "text": msg.plain_text(),
+        "media": msg.media_kind(),
+        "forwarded": msg.is_forwarded(),
+        "reply_to": msg.reply_to_message_id,
+    })
+    .encode()
+}
+
+pub struct ScriptMetric {
+    path: PathBuf,
+    timezone: Option<Tz>,
+    child: Child,
+    stdin: Option<BufWriter<ChildStdin>>,
+    // stdout читается в отдельном потоке, чтобы скрипт не встал на записи
+    reader: Option<JoinHandle<io::Result<String>>>,
+    // первая ошибка записи: observe вернуть её не может
+    error: Option<io::Error>,
+    report: String,
+}
+
+impl ScriptMetric {
+    pub fn spawn(path: &Path, timezone: Option<Tz>) -> io::Result<Self> {
+        let mut child = spawn(path)?;
+        let stdin = child.stdin.take().map(BufWriter::new);
+        let mut stdout = child.stdout.take().expect("stdout скрипта");
+        let reader = thread::spawn(move || {
+            let mut buf = Vec::new();
+            stdout.read_to_end(&mut buf)?;
+            Ok(String::from_utf8_lossy(&buf).into_owned())
+        });
+        Ok(ScriptMetric {
+            path: path.to_path_buf(),
+            timezone,
+            child,
+            stdin,
+            reader: Some(reader),
+            error: None,
+            report: String::new(),
+        })
+    }
+
+    fn fail(&self, what: impl std::fmt::Display) -> io::Error {
+        fail(&self.path, what)
+    }
+}
+
+impl Metric for ScriptMetric {
+    fn observe(&mut self, msg: &Message) {
+        let Some(stdin) = self.stdin.as_mut() else {
+            return;
+        };
+        if let Err(e) = writeln!(stdin, "{}", message_line(msg, self.timezone)) {
+            // скрипт закрыл stdin — дальше не пишем, итог проверит finish
+            self.stdin = None;
+            if e.kind() != io::ErrorKind::BrokenPipe {
+                self.error = Some(e);
+            }
+        }
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        if let Some(mut stdin) = self.stdin.take()
+            && let Err(e) = stdin.flush()
+            && e.kind() != io::ErrorKind::BrokenPipe
+        {
+            self.error.get_or_insert(e);
+        }
+        let status = self.child.wait()?;
+        if let Some(reader) = self.reader.take() {
+            self.report = reader.join().map_err(|_| self.fail("поток чтения упал"))??;
+        }
+        if let Some(e) = self.error.take() {
+            return Err(self.fail(e));
+        }
+        if !status.success() {
+            return Err(self.fail(format!("завершился с ошибкой ({status})")));
+        }
+        Ok(())
+    }
+
+    fn report(&self, w: &mut dyn Write) -> io::Result<()> {
+        w.write_all(self.report.trim_end().as_bytes())?;
+        writeln!(w)
+    }
+}
+
+/// --filter-script: решает по каждому сообщению, оставить ли его.
+pub struct ScriptFilter {
+    path: PathBuf,
+    timezone: Option<Tz>,
+    child: Child,
+    stdin: BufWriter<ChildStdin>,
+    stdout: BufReader<ChildStdout>,
+    answer: String,
+}
+
+impl ScriptFilter {
+    pub fn spawn(path: &Path, timezone: Option<Tz>) -> io::Result<Self> {
+        let mut child = spawn(path)?;
+        let stdin = BufWriter::new(child.stdin.take().expect("stdin скрипта"));
+        let stdout = BufReader::new(child.stdout.take().expect("stdout скрипта"));
+        Ok(ScriptFilter {
+            path: path.to_path_buf(),
+            timezone,
+            child,
+            stdin,
+            stdout,
+            answer: String::new(),
+        })
+    }
+
+    /// Отправить сообщение и дождаться ответа "1" или "0".
+    pub fn accepts(&mut self, msg: &Message) -> io::Result<bool> {
+        writeln!(self.stdin, "{}", message_line(msg, self.timezone))
+            .and_then(|()| self.stdin.flush())
+            .map_err(|e| fail(&self.path, e))?;
+        self.answer.clear();
+        if self.stdout.read_line(&mut self.answer)? == 0 {
+            return Err(fail(&self.path, "завершился, не ответив на сообщение"));
+        }
+        match self.answer.trim() {
+            "1" => Ok(true),
+            "0" => Ok(false),
+            other => Err(fail(&self.path, format!("ответ «{other}», ожидается 1 или 0"))),
+        }
+    }
+
+    /// Конец входа: закрыть stdin и проверить, что скрипт завершился без ошибки.
+    pub fn finish(self) -> io::Result<()> {
+        let ScriptFilter { path, mut child, stdin, stdout, .. } = self;
+        drop(stdin);
+        drop(stdout);
+        let status = child.wait()?;
+        if !status.success() {
+            return Err(fail(&path, format!("завершился с ошибкой ({status})")));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,171 @@
+//
+// ===================== ТОНАЛЬНОСТЬ (--sentiment) =====================
+//
+// Оценка сообщения — сумма оценок слов из словаря (src/sentiment/lexicon.txt
+// или --lexicon FILE с теми же строками "слово оценка"). Слова и словаря,
+// и текста сводятся к основе; "не"/"not" перед словом меняют знак.
+// Это грубая оценка: сарказм и контекст словарь не видит.
+//
+
+use ahash::AHashMap;
+use chrono::NaiveDateTime;
+
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::{fast_tokenize, percent_of, stem::stem, trim_ascii_punct};
+
+const BUILTIN_LEXICON: &str = include_str!("sentiment/lexicon.txt");
+const NEGATIONS: [&str; 5] = ["не", "ни", "not", "no", "never"];
+
+/// Сообщений, сумма оценок, положительных, отрицательных.
+#[derive(Default, Clone, Copy)]
+pub struct Tally {
+    pub messages: usize,
+    pub sum: i64,
+    pub positive: usize,
+    pub negative: usize,
+}
+
+crate::cache::codec!(Tally { messages, sum, positive, negative });
+
+impl Tally {
+    fn add(&mut self, score: i64) {
+        self.messages += 1;
+        self.sum += score;
+        if score > 0 {
+            self.positive += 1;
+        } else if score < 0 {
+            self.negative += 1;
+        }
+    }
+
+    pub fn average(&self) -> f64 {
+        if self.messages == 0 {
+            0.0
+        } else {
+            self.sum as f64 / self.messages as f64
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct SentimentStats {
+    // основа слова -> оценка
+    lexicon: AHashMap<String, i64>,
+    pub overall: Tally,
+    pub per_author: AHashMap<String, Tally>,
+    // "ГГГГ-ММ" -> итог за месяц
+    pub by_month: BTreeMap<String, Tally>,
+}
+
+crate::cache::codec!(SentimentStats { lexicon, overall, per_author, by_month });
+
+impl SentimentStats {
+    /// Встроенный словарь или свой файл.
+    pub fn new(lexicon: Option<&Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        let (text, source) = match lexicon {
+            Some(path) => {
+                let text = std::fs::read_to_string(path).map_err(|e| {
+                    format!("Не удалось прочитать словарь тональности «{}»: {e}", path.display())
+                })?;
+                (text, path.display().to_string())
+            }
+            None => (BUILTIN_LEXICON.to_string(), "встроенный".to_string()),
+        };
+        let mut stats = SentimentStats::default();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let parsed = line
+                .rsplit_once(char::is_whitespace)
+                .and_then(|(word, score)| Some((word.trim(), score.parse::<i64>().ok()?)));
+            let Some((word, score)) = parsed else {
+                let line_no = i + 1;
+                return Err(format!(
+                    "Словарь тональности «{source}», строка {line_no}: нужно «слово оценка»"
+                )
+                .into());
+            };
+            stats.lexicon.insert(stem(&word.to_lowercase()), score);
+        }
+        Ok(stats)
+    }
+
+    pub fn lexicon_len(&self) -> usize {
+        self.lexicon.len()
+    }
+
+    /// Оценка текста по словарю.
+    pub fn score(&self, text: &str) -> i64 {
+        let mut score = 0;
+        let mut negate = false;
+        fast_tokenize(text, |raw| {
+            let token = trim_ascii_punct(raw).to_lowercase();
+            if NEGATIONS.contains(&token.as_str()) {
+                negate = true;
+                return;
+            }
+            if let Some(&s) = self.lexicon.get(&stem(&token)) {
+                score += if negate { -s } else { s };
+            }
+            negate = false;
+        });
+        score
+    }
+
+    pub fn observe(&mut self, author: &str, date: Option<NaiveDateTime>, text: &str) {
+        let score = self.score(text);
+        self.overall.add(score);
+        match self.per_author.get_mut(author) {
+            Some(t) => t.add(score),
+            None => self.per_author.entry(author.to_string()).or_default().add(score),
+        }
+        if let Some(dt) = date {
+            let month = dt.format("%Y-%m").to_string();
+            self.by_month.entry(month).or_default().add(score);
+        }
+    }
+
+    /// Авторы по убыванию средней оценки.
+    pub fn authors_by_average(&self) -> Vec<(&str, Tally)> {
+        let mut v: Vec<_> = self.per_author.iter().map(|(a, &t)| (a.as_str(), t)).collect();
+        v.sort_by(|a, b| b.1.average().total_cmp(&a.1.average()));
+        v
+    }
+
+    pub fn write_text<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let t = &self.overall;
+        writeln!(
+            w,
+            "Тональность (словарь: {} слов): в среднем {:+.2} на сообщение, \
+             позитивных {:.1}%, негативных {:.1}%",
+            self.lexicon.len(),
+            t.average(),
+            percent_of(t.positive, t.messages),
+            percent_of(t.negative, t.messages)
+        )?;
+        writeln!(w, "  по участникам (среднее / позитивных / негативных):")?;
+        for (author, t) in self.authors_by_average() {
+            writeln!(
+                w,
+                "  - {}: {:+.2} / {:.1}% / {:.1}% ({} сообщ.)",
+                author,
+                t.average(),
+                percent_of(t.positive, t.messages),
+                percent_of(t.negative, t.messages),
+                t.messages
+            )?;
+        }
+        if !self.by_month.is_empty() {
+            writeln!(w, "  по месяцам:")?;
+            for (month, t) in &self.by_month {
+                writeln!(w, "  - {}: {:+.2} ({} сообщ.)", month, t.average(), t.messages)?;
+            }
+        }
+        Ok(())
+    }
+}
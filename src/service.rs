@@ -0,0 +1,253 @@
+//
+// ===================== СЛУЖЕБНЫЕ СООБЩЕНИЯ =====================
+//
+// type: "service" + action: вступления, выходы, закрепы, переименования...
+// Считаются всегда (дёшево), в лог попадают только с --service.
+//
+// Рост числа участников: приходы и уходы по месяцам, если у события
+// известна дата. Сколько людей было в чате до начала истории, экспорт
+// не говорит, поэтому численность считается от нуля — это прирост.
+//
+// История оформления: смены названия и фото чата по порядку.
+//
+
+use ahash::AHashMap;
+use chrono::{Datelike, NaiveDateTime};
+use simd_json::BorrowedValue;
+use simd_json::borrowed::Object;
+
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use crate::durations::{duration_seconds, format_duration};
+use crate::{author_name, get_i64_field, get_str_field, sorted_by_count};
+
+/// Пришло и ушло участников за месяц.
+#[derive(Default, Clone, Copy)]
+pub struct MemberFlow {
+    pub joined: usize,
+    pub left: usize,
+}
+
+crate::cache::codec!(MemberFlow { joined, left });
+
+/// Месяц "ГГГГ-ММ", движение за месяц и прирост с начала истории.
+pub struct GrowthPoint {
+    pub month: String,
+    pub flow: MemberFlow,
+    pub members: i64,
+}
+
+/// Смена названия или фото чата.
+pub struct ChatChange {
+    pub chat: String,
+    pub date: String,
+    pub actor: String,
+    // edit_group_title / edit_group_photo / delete_group_photo
+    pub action: &'static str,
+    // новое название или путь к файлу фото
+    pub value: Option<String>,
+}
+
+crate::cache::codec!(ChatChange { chat, date, actor, action, value });
+
+impl ChatChange {
+    /// None — событие не про оформление чата.
+    pub fn from_service(chat: &str, date: String, msg_obj: &Object) -> Option<Self> {
+        let (action, field) = match get_str_field(msg_obj, "action")? {
+            "edit_group_title" => ("edit_group_title", "title"),
+            "edit_group_photo" => ("edit_group_photo", "photo"),
+            "delete_group_photo" => ("delete_group_photo", ""),
+            _ => return None,
+        };
+        Some(Self {
+            chat: chat.to_string(),
+            date,
+            actor: author_name(msg_obj, "actor", "actor_id").into_owned(),
+            action,
+            value: get_str_field(msg_obj, field).map(str::to_string),
+        })
+    }
+}
+
+#[derive(Default)]
+pub struct ServiceStats {
+    pub total: usize,
+    pub by_action: AHashMap<String, usize>,
+
+    // добавлено участников (invite_members, по числу members)
+    pub invited: usize,
+    // вступили сами: по ссылке или по заявке
+    pub joined: usize,
+    // вышли сами / удалены кем-то
+    pub left: usize,
+    pub removed: usize,
+    pub pinned: usize,
+    pub title_changes: usize,
+
+    // (год, месяц) -> пришло/ушло
+    pub by_month: BTreeMap<(i32, u32), MemberFlow>,
+    // смены названия и фото (при verbose)
+    pub changes: Vec<ChatChange>,
+}
+
+crate::cache::codec!(ServiceStats {
+    total, by_action, invited, joined, left, removed, pinned, title_changes, by_month, changes,
+});
+
+fn members<'a>(obj: &'a Object) -> Vec<&'a str> {
+    match obj.get("members") {
+        Some(BorrowedValue::Array(list)) => list
+            .iter()
+            .map(|m| match m {
+                BorrowedValue::String(s) => s.as_ref(),
+                _ => "Unknown",
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+impl ServiceStats {
+    pub fn observe(&mut self, msg_obj: &Object, date: Option<NaiveDateTime>) {
+        let action = get_str_field(msg_obj, "action").unwrap_or("unknown");
+        let actor = author_name(msg_obj, "actor", "actor_id");
+        let actor = actor.as_ref();
+
+        self.total += 1;
+        *self.by_action.entry(action.to_string()).or_insert(0) += 1;
+
+        let mut flow = MemberFlow::default();
+        match action {
+            "invite_members" => {
+                flow.joined = members(msg_obj).len().max(1);
+                self.invited += flow.joined;
+            }
+            "join_group_by_link" | "join_group_by_request" => {
+                flow.joined = 1;
+                self.joined += 1;
+            }
+            "remove_members" => {
+                for m in members(msg_obj) {
+                    if m == actor {
+                        self.left += 1;
+                    } else {
+                        self.removed += 1;
+                    }
+                    flow.left += 1;
+                }
+            }
+            "pin_message" => self.pinned += 1,
+            "edit_group_title" => self.title_changes += 1,
+            _ => {}
+        }
+
+        if let Some(dt) = date
+            && flow.joined + flow.left > 0
+        {
+            let m = self.by_month.entry((dt.year(), dt.month())).or_default();
+            m.joined += flow.joined;
+            m.left += flow.left;
+        }
+    }
+
+    /// Прирост участников по месяцам, без пропусков между первым и последним.
+    pub fn growth(&self) -> Vec<GrowthPoint> {
+        let months = &self.by_month;
+        let (Some(&first), Some(&last)) = (months.keys().next(), months.keys().next_back()) else {
+            return Vec::new();
+        };
+        let mut out = Vec::new();
+        let mut members = 0i64;
+        let (mut y, mut m) = first;
+        while (y, m) <= last {
+            let flow = months.get(&(y, m)).copied().unwrap_or_default();
+            members += flow.joined as i64 - flow.left as i64;
+            out.push(GrowthPoint { month: format!("{y}-{m:02}"), flow, members });
+            (y, m) = if m == 12 { (y + 1, 1) } else { (y, m + 1) };
+        }
+        out
+    }
+
+    pub fn write_changes<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(w, "История названия и фото чата: {}", self.changes.len())?;
+        let mut chat: Option<&str> = None;
+        for c in &self.changes {
+            if chat != Some(c.chat.as_str()) {
+                writeln!(w, "[{}]", c.chat)?;
+                chat = Some(&c.chat);
+            }
+            let what = match (c.action, &c.value) {
+                ("edit_group_title", Some(title)) => format!("название «{title}»"),
+                ("edit_group_title", None) => "название".to_string(),
+                ("edit_group_photo", Some(photo)) => format!("новое фото ({photo})"),
+                ("edit_group_photo", None) => "новое фото".to_string(),
+                _ => "фото удалено".to_string(),
+            };
+            writeln!(w, "- {} {}: {}", c.date, c.actor, what)?;
+        }
+        Ok(())
+    }
+
+    pub fn write_growth<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(w, "Рост числа участников по месяцам (пришли / ушли, прирост с начала):")?;
+        for p in self.growth() {
+            writeln!(w, "  {} +{} / -{}, {:+}", p.month, p.flow.joined, p.flow.left, p.members)?;
+        }
+        Ok(())
+    }
+
+    pub fn write_text<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(w, "Служебные события: {}", self.total)?;
+        writeln!(w, "  добавлено участников: {}", self.invited)?;
+        writeln!(w, "  вступили по ссылке/заявке: {}", self.joined)?;
+        writeln!(w, "  вышли сами: {}", self.left)?;
+        writeln!(w, "  удалены: {}", self.removed)?;
+        writeln!(w, "  закреплено сообщений: {}", self.pinned)?;
+        writeln!(w, "  смен названия: {}", self.title_changes)?;
+        writeln!(w, "  по типам:")?;
+        for (action, count) in sorted_by_count(&self.by_action) {
+            writeln!(w, "  - {}: {}", action, count)?;
+        }
+        Ok(())
+    }
+}
+
+/// Человекочитаемое описание события для строки `*** ... ***`.
+pub fn describe(msg_obj: &Object) -> String {
+    let action = get_str_field(msg_obj, "action").unwrap_or("unknown");
+    let actor = author_name(msg_obj, "actor", "actor_id");
+    let actor = actor.as_ref();
+    let title = get_str_field(msg_obj, "title").unwrap_or("");
+    let list = members(msg_obj);
+
+    match action {
+        "invite_members" => format!("{actor} добавляет {}", list.join(", ")),
+        "join_group_by_link" => format!("{actor} вступает по ссылке"),
+        "join_group_by_request" => format!("{actor} вступает по заявке"),
+        "remove_members" if list == [actor] => format!("{actor} выходит из чата"),
+        "remove_members" => format!("{actor} удаляет {}", list.join(", ")),
+        "pin_message" => match get_i64_field(msg_obj, "message_id") {
+            Some(id) => format!("{actor} закрепляет сообщение #{id}"),
+            None => format!("{actor} закрепляет сообщение"),
+        },
+        "edit_group_title" => format!("{actor} меняет название на «{title}»"),
+        "edit_group_photo" => format!("{actor} меняет фото чата"),
+        "delete_group_photo" => format!("{actor} удаляет фото чата"),
+        "create_group" | "create_channel" => format!("{actor} создаёт чат «{title}»"),
+        "phone_call" | "group_call" => describe_call(action, actor, msg_obj),
+        "migrate_to_supergroup" | "migrate_from_group" => "чат становится супергруппой".into(),
+        other => format!("{actor}: {other}"),
+    }
+}
+
+fn describe_call(action: &str, actor: &str, msg_obj: &Object) -> String {
+    let what = if action == "group_call" { "групповой звонок" } else { "звонок" };
+    match duration_seconds(msg_obj) {
+        Some(secs) if secs > 0 => format!("{actor}: {what}, {}", format_duration(secs)),
+        _ => match get_str_field(msg_obj, "discard_reason") {
+            Some(reason) => format!("{actor}: {what} не состоялся ({reason})"),
+            None => format!("{actor}: {what} не состоялся"),
+        },
+    }
+}
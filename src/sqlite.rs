@@ -0,0 +1,139 @@
+//
+// ===================== ВЫГРУЗКА В SQLITE =====================
+//
+// Все прошедшие фильтры сообщения + итоговая статистика в одну базу,
+// чтобы потом гонять по истории произвольный SQL. Всё пишется одной
+// транзакцией — так на порядки быстрее, чем по INSERT на сообщение.
+//
+
+use rusqlite::{Connection, params};
+
+use crate::{MessageRecord, Stats, WEEKDAYS, sorted_by_count};
+
+const SCHEMA: &str = "
+CREATE TABLE messages (
+    chat       TEXT NOT NULL,
+    id         INTEGER NOT NULL,
+    date       TEXT,
+    author     TEXT NOT NULL,
+    from_id    TEXT NOT NULL,
+    text       TEXT NOT NULL,
+    media_type TEXT,
+    reply_to   INTEGER,
+    PRIMARY KEY (chat, id)
+);
+CREATE TABLE stats (key TEXT PRIMARY KEY, value INTEGER NOT NULL);
+CREATE TABLE author_stats (author TEXT PRIMARY KEY, messages INTEGER NOT NULL);
+CREATE TABLE hour_hist (hour INTEGER PRIMARY KEY, messages INTEGER NOT NULL);
+CREATE TABLE day_hist (day INTEGER PRIMARY KEY, messages INTEGER NOT NULL);
+CREATE TABLE weekday_hist (
+    weekday  INTEGER PRIMARY KEY,
+    name     TEXT NOT NULL,
+    messages INTEGER NOT NULL
+);
+CREATE TABLE word_freq (word TEXT PRIMARY KEY, count INTEGER NOT NULL);
+";
+
+// индексы строим после заливки — так быстрее
+const INDEXES: &str = "
+CREATE INDEX messages_date ON messages (date);
+CREATE INDEX messages_author ON messages (author);
+CREATE INDEX messages_from_id ON messages (from_id);
+CREATE INDEX messages_reply_to ON messages (reply_to);
+";
+
+const INSERT_MESSAGE: &str = "INSERT OR REPLACE INTO messages \
+     (chat, id, date, author, from_id, text, media_type, reply_to) \
+     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)";
+
+pub struct SqliteSink {
+    conn: Connection,
+}
+
+impl SqliteSink {
+    /// Существующий файл перезаписывается.
+    pub fn create(path: &str) -> rusqlite::Result<Self> {
+        let _ = std::fs::remove_file(path);
+        let conn = Connection::open(path)?;
+        conn.execute_batch("PRAGMA journal_mode = OFF; PRAGMA synchronous = OFF;")?;
+        conn.execute_batch(SCHEMA)?;
+        conn.execute_batch("BEGIN")?;
+        Ok(Self { conn })
+    }
+
+    pub fn insert(&mut self, chat: &str, rec: &MessageRecord) -> rusqlite::Result<()> {
+        let mut stmt = self.conn.prepare_cached(INSERT_MESSAGE)?;
+        stmt.execute(params![
+            chat,
+            rec.id,
+            rec.date,
+            rec.author,
+            rec.from_id,
+            rec.text,
+            rec.media_type,
+            rec.reply_to,
+        ])?;
+        Ok(())
+    }
+
+    /// Дописывает агрегаты, строит индексы и фиксирует транзакцию.
+    pub fn finish(self, stats: &Stats, verbose: bool) -> rusqlite::Result<()> {
+        let conn = &self.conn;
+
+        {
+            let mut stmt = conn.prepare("INSERT INTO stats (key, value) VALUES (?1, ?2)")?;
+            for (key, value) in [
+                ("total_messages", stats.total_messages),
+                ("messages_with_any_media", stats.messages_with_any_media),
+                ("photo_messages", stats.photo_messages),
+                ("video_messages", stats.video_messages),
+                ("voice_messages", stats.voice_messages),
+                ("audio_messages", stats.audio_messages),
+                ("gif_messages", stats.gif_messages),
+                ("sticker_messages", stats.sticker_messages),
+                ("file_messages", stats.file_messages),
+                ("poll_messages", stats.poll_messages),
+                ("forwarded_messages", stats.forwarded_messages),
+                ("link_messages", stats.link_messages),
+                ("unique_authors", stats.per_author.len()),
+            ] {
+                stmt.execute(params![key, value as i64])?;
+            }
+
+            let mut stmt =
+                conn.prepare("INSERT INTO author_stats (author, messages) VALUES (?1, ?2)")?;
+            for (author, count) in sorted_by_count(&stats.per_author) {
+                stmt.execute(params![author, count as i64])?;
+            }
+        }
+
+        if verbose {
+            let mut stmt =
+                conn.prepare("INSERT INTO hour_hist (hour, messages) VALUES (?1, ?2)")?;
+            for (hour, &count) in stats.hour_hist.iter().enumerate() {
+                stmt.execute(params![hour as i64, count as i64])?;
+            }
+
+            let mut stmt = conn.prepare("INSERT INTO day_hist (day, messages) VALUES (?1, ?2)")?;
+            for (day, &count) in stats.day_hist.iter().enumerate().skip(1) {
+                stmt.execute(params![day as i64, count as i64])?;
+            }
+
+            // 1 — понедельник, как в ISO 8601
+            let mut stmt = conn.prepare(
+                "INSERT INTO weekday_hist (weekday, name, messages) VALUES (?1, ?2, ?3)",
+            )?;
+            for (day, (name, &count)) in WEEKDAYS.iter().zip(&stats.weekday_hist).enumerate() {
+                stmt.execute(params![day as i64 + 1, name, count as i64])?;
+            }
+
+            let mut stmt = conn.prepare("INSERT INTO word_freq (word, count) VALUES (?1, ?2)")?;
+            for (word, &count) in &stats.word_freq {
+                stmt.execute(params![word, count as i64])?;
+            }
+        }
+
+        conn.execute_batch(INDEXES)?;
+        conn.execute_batch("COMMIT")
+    }
+}
@@ -0,0 +1,182 @@
+//
+// ===================== СТАТИСТИКА В CSV =====================
+//
+// Каждая таблица — отдельный CSV (stat_<таблица>.csv) при --txt,
+// либо все таблицы подряд в stdout, разделённые пустой строкой
+// и строкой-заголовком вида `# authors`.
+//
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use crate::{Stats, WEEKDAYS, percent_of, sorted_by_count, top_by_count};
+
+struct Table {
+    name: &'static str,
+    header: &'static [&'static str],
+    rows: Vec<Vec<String>>,
+}
+
+fn build_tables(stats: &Stats, verbose: bool) -> Vec<Table> {
+    let mut tables = Vec::new();
+
+    tables.push(Table {
+        name: "authors",
+        header: &["author", "messages", "percent"],
+        rows: sorted_by_count(&stats.per_author)
+            .into_iter()
+            .map(|(name, count)| {
+                vec![
+                    name.to_string(),
+                    count.to_string(),
+                    format!("{:.1}", percent_of(count, stats.total_messages)),
+                ]
+            })
+            .collect(),
+    });
+
+    if verbose {
+        tables.push(Table {
+            name: "hours",
+            header: &["hour", "messages"],
+            rows: stats
+                .hour_hist
+                .iter()
+                .enumerate()
+                .map(|(h, c)| vec![h.to_string(), c.to_string()])
+                .collect(),
+        });
+
+        tables.push(Table {
+            name: "days",
+            header: &["day", "messages"],
+            rows: stats
+                .day_hist
+                .iter()
+                .enumerate()
+                .skip(1)
+                .map(|(d, c)| vec![d.to_string(), c.to_string()])
+                .collect(),
+        });
+
+        tables.push(Table {
+            name: "weekdays",
+            header: &["weekday", "name", "messages"],
+            rows: WEEKDAYS
+                .iter()
+                .zip(stats.weekday_hist)
+                .enumerate()
+                .map(|(d, (name, c))| vec![(d + 1).to_string(), name.to_string(), c.to_string()])
+                .collect(),
+        });
+
+        tables.push(Table {
+            name: "months",
+            header: &["month", "messages"],
+            rows: stats
+                .timeline
+                .months()
+                .into_iter()
+                .map(|(m, c)| vec![m, c.to_string()])
+                .collect(),
+        });
+
+        tables.push(Table {
+            name: "years",
+            header: &["year", "messages"],
+            rows: stats
+                .timeline
+                .years()
+                .into_iter()
+                .map(|(y, c)| vec![y.to_string(), c.to_string()])
+                .collect(),
+        });
+
+        tables.push(Table {
+            name: "author_words",
+            header: &["author", "text_messages", "words", "words_per_message"],
+            rows: stats
+                .lengths
+                .authors_by_words()
+                .into_iter()
+                .map(|(name, messages, words)| {
+                    let avg = if messages > 0 { words as f64 / messages as f64 } else { 0.0 };
+                    vec![
+                        name.to_string(),
+                        messages.to_string(),
+                        words.to_string(),
+                        format!("{avg:.2}"),
+                    ]
+                })
+                .collect(),
+        });
+
+        tables.push(Table {
+            name: "words",
+            header: &["word", "count"],
+            rows: top_by_count(&stats.word_freq, stats.top_words)
+                .into_iter()
+                .map(|(word, count)| vec![word.to_string(), count.to_string()])
+                .collect(),
+        });
+    }
+
+    tables
+}
+
+fn write_field<W: Write>(w: &mut W, field: &str) -> io::Result<()> {
+    if field.contains([',', '"', '\n', '\r']) {
+        w.write_all(b"\"")?;
+        w.write_all(field.replace('"', "\"\"").as_bytes())?;
+        w.write_all(b"\"")
+    } else {
+        w.write_all(field.as_bytes())
+    }
+}
+
+pub fn write_row<W: Write, S: AsRef<str>>(w: &mut W, row: &[S]) -> io::Result<()> {
+    for (i, field) in row.iter().enumerate() {
+        if i > 0 {
+            w.write_all(b",")?;
+        }
+        write_field(w, field.as_ref())?;
+    }
+    w.write_all(b"\n")
+}
+
+fn write_table<W: Write>(w: &mut W, table: &Table) -> io::Result<()> {
+    write_row(w, table.header)?;
+    for row in &table.rows {
+        write_row(w, row)?;
+    }
+    Ok(())
+}
+
+/// Все таблицы в один поток, секциями.
+pub fn write_stats_csv<W: Write>(w: &mut W, stats: &Stats, verbose: bool) -> io::Result<()> {
+    for (i, table) in build_tables(stats, verbose).iter().enumerate() {
+        if i > 0 {
+            writeln!(w)?;
+        }
+        writeln!(w, "# {}", table.name)?;
+        write_table(w, table)?;
+    }
+    Ok(())
+}
+
+/// Каждая таблица в свой файл `<prefix>_<таблица>.csv`; возвращает пути.
+pub fn write_stats_csv_files(
+    prefix: &str,
+    stats: &Stats,
+    verbose: bool,
+) -> io::Result<Vec<String>> {
+    let mut paths = Vec::new();
+    for table in build_tables(stats, verbose) {
+        let path = format!("{prefix}_{}.csv", table.name);
+        let mut w = BufWriter::new(File::create(&path)?);
+        write_table(&mut w, &table)?;
+        w.flush()?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
@@ -0,0 +1,678 @@
+//
+// ===================== СТАТИСТИКА В JSON =====================
+//
+// Те же данные, что и в текстовом выводе, но структурой: для скриптов
+// и дашбордов. Разделы, которые считаются только при -v, тоже только при -v.
+//
+
+use simd_json::owned::Object;
+use simd_json::prelude::*;
+use simd_json::{OwnedValue, json};
+
+use std::io::{self, Write};
+
+use crate::durations::DurationStats;
+use crate::questions::QuestionStats;
+use crate::sentiment::{SentimentStats, Tally};
+use crate::style::{ShoutCounts, ShoutStats};
+use crate::timeline::DayRange;
+use crate::unique::UniqueStats;
+use crate::vocab::{signature_words, vocabulary_richness};
+use crate::interactions::InteractionStats;
+use crate::lengths::{Distribution, LENGTH_BUCKETS, LengthStats};
+use crate::{
+    Stats, TOP_COPYPASTA, copypasta, sorted_by_count, spam_repeats, spam_scores, top_by_count,
+};
+
+fn count_map(map: &ahash::AHashMap<String, usize>) -> OwnedValue {
+    let mut obj = Object::with_capacity(map.len());
+    for (k, c) in sorted_by_count(map) {
+        obj.insert(k.to_string(), OwnedValue::from(c as u64));
+    }
+    OwnedValue::from(obj)
+}
+
+fn duration_json(d: &DurationStats) -> OwnedValue {
+    let mut v = json!({
+        "count": d.count as u64,
+        "total_seconds": d.total_seconds,
+        "avg_seconds": d.average()
+    });
+    if let Some(o) = v.as_object_mut() {
+        let mut per_author = Object::with_capacity(d.per_author.len());
+        for (author, n, secs) in d.authors_by_time() {
+            per_author.insert(author.to_string(), json!({ "count": n as u64, "seconds": secs }));
+        }
+        o.insert("per_author".into(), OwnedValue::from(per_author));
+    }
+    v
+}
+
+fn distribution_json(d: &Distribution) -> OwnedValue {
+    json!({
+        "count": d.count(),
+        "total": d.sum(),
+        "min": d.min() as u64,
+        "median": d.median() as u64,
+        "mean": d.mean(),
+        "p95": d.percentile(0.95) as u64,
+        "max": d.max() as u64
+    })
+}
+
+fn tally_json(t: &Tally) -> OwnedValue {
+    json!({
+        "messages": t.messages as u64,
+        "average": t.average(),
+        "positive": t.positive as u64,
+        "negative": t.negative as u64
+    })
+}
+
+fn sentiment_json(s: &SentimentStats) -> OwnedValue {
+    let mut o = tally_json(&s.overall);
+    let mut per_author = Object::with_capacity(s.per_author.len());
+    for (author, t) in &s.per_author {
+        per_author.insert(author.clone(), tally_json(t));
+    }
+    let mut by_month = Object::with_capacity(s.by_month.len());
+    for (month, t) in &s.by_month {
+        by_month.insert(month.clone(), tally_json(t));
+    }
+    if let Some(so) = o.as_object_mut() {
+        so.insert("lexicon_words".into(), OwnedValue::from(s.lexicon_len() as u64));
+        so.insert("per_author".into(), OwnedValue::from(per_author));
+        so.insert("by_month".into(), OwnedValue::from(by_month));
+    }
+    o
+}
+
+fn shout_counts_json(c: &ShoutCounts) -> OwnedValue {
+    json!({
+        "messages": c.messages as u64,
+        "caps_messages": c.caps as u64,
+        "exclamations": c.exclamations as u64,
+        "questions": c.questions as u64
+    })
+}
+
+fn shouting_json(s: &ShoutStats) -> OwnedValue {
+    let mut o = shout_counts_json(&s.total);
+    let mut per_author = Object::with_capacity(s.per_author.len());
+    for (author, c) in &s.per_author {
+        per_author.insert(author.clone(), shout_counts_json(c));
+    }
+    if let Some(so) = o.as_object_mut() {
+        so.insert("per_author".into(), OwnedValue::from(per_author));
+    }
+    o
+}
+
+fn questions_json(q: &QuestionStats) -> OwnedValue {
+    let mut per_author = Object::with_capacity(q.per_author.len());
+    for (author, c) in q.authors_by_questions() {
+        per_author.insert(
+            author.to_string(),
+            json!({ "asked": c.asked as u64, "answered": c.answered as u64 }),
+        );
+    }
+    let mut o = json!({
+        "messages": q.messages as u64,
+        "questions": q.containing as u64,
+        "ending_with_question_mark": q.ending as u64,
+        "answered": q.answered as u64
+    });
+    if let Some(qo) = o.as_object_mut() {
+        qo.insert("per_author".into(), OwnedValue::from(per_author));
+    }
+    o
+}
+
+fn uniques_json(u: &UniqueStats) -> OwnedValue {
+    let mut words = Object::with_capacity(u.words_per_author.len());
+    for (author, n) in u.authors_by_words() {
+        words.insert(author.to_string(), OwnedValue::from(n));
+    }
+    let mut o = json!({
+        "links": u.links.estimate(),
+        "reply_targets": u.reply_targets.estimate()
+    });
+    if let Some(uo) = o.as_object_mut() {
+        uo.insert("words_per_author".into(), OwnedValue::from(words));
+    }
+    o
+}
+
+fn latency_json(i: &InteractionStats) -> OwnedValue {
+    let mut per_author = Object::new();
+    for (author, d) in i.repliers() {
+        per_author.insert(
+            author.to_string(),
+            json!({
+                "replies": d.count(),
+                "median_seconds": d.median(),
+                "p90_seconds": d.percentile(0.9)
+            }),
+        );
+    }
+    let pairs: Vec<OwnedValue> = i
+        .pairs()
+        .into_iter()
+        .map(|(from, to, d)| {
+            json!({ "from": from, "to": to, "replies": d.count(), "median_seconds": d.median() })
+        })
+        .collect();
+    json!({ "per_author": per_author, "pairs": pairs })
+}
+
+fn lengths_json(l: &LengthStats) -> OwnedValue {
+    let buckets: Vec<OwnedValue> = LENGTH_BUCKETS
+        .iter()
+        .map(|&(lo, hi)| {
+            let count = l.chars.count_in(lo, hi);
+            // у последней корзины верхней границы нет
+            let to = if hi == u32::MAX { OwnedValue::null() } else { OwnedValue::from(hi as u64) };
+            json!({ "from": lo as u64, "to": to, "count": count })
+        })
+        .collect();
+    let mut per_author = Object::with_capacity(l.per_author.len());
+    for (author, c, w) in l.authors() {
+        per_author.insert(
+            author.to_string(),
+            json!({ "chars": distribution_json(c), "words": distribution_json(w) }),
+        );
+    }
+    let mut v = json!({
+        "chars": distribution_json(&l.chars),
+        "words": distribution_json(&l.words)
+    });
+    if let Some(o) = v.as_object_mut() {
+        o.insert("char_buckets".into(), OwnedValue::from(buckets));
+        o.insert("per_author".into(), OwnedValue::from(per_author));
+    }
+    v
+}
+
+pub fn build_stats_json(stats: &Stats, verbose: bool) -> OwnedValue {
+    let mut root = json!({
+        "chat": stats.chat_name.as_str(),
+        "total_messages": stats.total_messages as u64,
+        "messages_with_any_media": stats.messages_with_any_media as u64,
+        "media": {
+            "photo": stats.photo_messages as u64,
+            "video": stats.video_messages as u64,
+            "voice": stats.voice_messages as u64,
+            "audio": stats.audio_messages as u64,
+            "gif": stats.gif_messages as u64,
+            "sticker": stats.sticker_messages as u64,
+            "file": stats.file_messages as u64
+        },
+        "polls": stats.poll_messages as u64,
+        "forwarded": stats.forwarded_messages as u64,
+        "with_links": stats.link_messages as u64,
+        "unique_authors": stats.per_author.len() as u64
+    });
+
+    let obj = root.as_object_mut().expect("json! строит объект");
+    obj.insert("per_author".into(), count_map(&stats.per_author));
+    obj.insert("voice_duration".into(), duration_json(&stats.voice_durations));
+    obj.insert("video_duration".into(), duration_json(&stats.video_durations));
+    obj.insert("round_video_duration".into(), duration_json(&stats.round_video_durations));
+
+    let ms = &stats.media_sizes;
+    if ms.files + ms.missing > 0 {
+        let mut by_kind = Object::with_capacity(ms.by_kind.len());
+        for (kind, (files, bytes)) in ms.kinds_by_size() {
+            by_kind.insert(kind.to_string(), json!({ "files": files as u64, "bytes": bytes }));
+        }
+        let mut per_author = Object::with_capacity(ms.per_author.len());
+        for (author, bytes) in ms.authors_by_size() {
+            per_author.insert(author.to_string(), OwnedValue::from(bytes));
+        }
+        let mut sizes = json!({
+            "files": ms.files as u64,
+            "total_bytes": ms.total_bytes,
+            "missing": ms.missing as u64
+        });
+        if let Some(so) = sizes.as_object_mut() {
+            so.insert("by_kind".into(), OwnedValue::from(by_kind));
+            so.insert("per_author".into(), OwnedValue::from(per_author));
+        }
+        obj.insert("media_sizes".into(), sizes);
+    }
+
+    let sv = &stats.service;
+    let mut service = json!({
+        "total": sv.total as u64,
+        "invited": sv.invited as u64,
+        "joined": sv.joined as u64,
+        "left": sv.left as u64,
+        "removed": sv.removed as u64,
+        "pinned": sv.pinned as u64,
+        "title_changes": sv.title_changes as u64
+    });
+    if let Some(so) = service.as_object_mut() {
+        so.insert("by_action".into(), count_map(&sv.by_action));
+    }
+    obj.insert("service".into(), service);
+
+    let c = &stats.calls;
+    let mut calls = json!({
+        "total": c.total as u64,
+        "group_calls": c.group_calls as u64,
+        "answered": c.answered as u64,
+        "missed": c.missed as u64,
+        "total_seconds": c.total_seconds,
+        "avg_seconds": c.total_seconds.checked_div(c.answered as u64).unwrap_or(0)
+    });
+    if let Some(co) = calls.as_object_mut() {
+        co.insert("by_reason".into(), count_map(&c.by_reason));
+        co.insert("per_author".into(), count_map(&c.per_author));
+    }
+    obj.insert("calls".into(), calls);
+
+    if let Some(pattern) = &stats.grep_pattern {
+        let mut grep = json!({
+            "pattern": pattern.as_str(),
+            "matches": stats.grep_matches as u64
+        });
+        if let Some(g) = grep.as_object_mut() {
+            g.insert("per_author".into(), count_map(&stats.grep_per_author));
+        }
+        obj.insert("grep".into(), grep);
+    }
+
+    if !stats.wordlists.is_empty() {
+        let mut lists = Object::with_capacity(stats.wordlists.len());
+        for list in &stats.wordlists {
+            let mut lo = json!({ "total": list.total as u64 });
+            if let Some(o) = lo.as_object_mut() {
+                o.insert("per_author".into(), count_map(&list.per_author));
+                o.insert("entries".into(), count_map(&list.per_entry));
+            }
+            lists.insert(list.name.clone(), lo);
+        }
+        obj.insert("wordlists".into(), OwnedValue::from(lists));
+    }
+
+    if !stats.counters.is_empty() {
+        let mut counters = Object::with_capacity(stats.counters.0.len());
+        for c in &stats.counters.0 {
+            let mut co = json!({ "pattern": c.pattern.as_str(), "total": c.total as u64 });
+            if let Some(o) = co.as_object_mut() {
+                o.insert("per_author".into(), count_map(&c.per_author));
+            }
+            counters.insert(c.name.clone(), co);
+        }
+        obj.insert("counters".into(), OwnedValue::from(counters));
+    }
+
+    if let Some(s) = &stats.sentiment {
+        obj.insert("sentiment".into(), sentiment_json(s));
+    }
+
+    if verbose {
+        let top_words: Vec<OwnedValue> = top_by_count(&stats.word_freq, stats.top_words)
+            .into_iter()
+            .map(|(word, count)| json!({ "word": word, "count": count as u64 }))
+            .collect();
+        obj.insert("top_words".into(), OwnedValue::from(top_words));
+        // --approx: счётчики слов и спама — нижние оценки
+        if let Some(capacity) = stats.approx {
+            obj.insert(
+                "approx".into(),
+                json!({ "capacity": capacity as u64, "counts": "lower_bound" }),
+            );
+        }
+
+        let richness: Vec<OwnedValue> = vocabulary_richness(stats)
+            .into_iter()
+            .map(|r| {
+                json!({
+                    "author": r.author,
+                    "unique_words": r.unique as u64,
+                    "total_words": r.total as u64,
+                    "ratio": r.ratio
+                })
+            })
+            .collect();
+        obj.insert("vocabulary_richness".into(), OwnedValue::from(richness));
+
+        let signatures = signature_words(stats);
+        let mut so = Object::with_capacity(signatures.len());
+        for (author, top) in signatures {
+            let words: Vec<OwnedValue> = top
+                .into_iter()
+                .map(|s| json!({ "word": s.word, "count": s.count as u64, "score": s.score }))
+                .collect();
+            so.insert(author.to_string(), OwnedValue::from(words));
+        }
+        obj.insert("signature_words".into(), OwnedValue::from(so));
+        obj.insert("unique_estimates".into(), uniques_json(&stats.uniques));
+
+        let hours: Vec<OwnedValue> =
+            stats.hour_hist.iter().map(|&c| OwnedValue::from(c as u64)).collect();
+        obj.insert("hour_hist".into(), OwnedValue::from(hours));
+
+        // индекс 0 — первое число месяца
+        let days: Vec<OwnedValue> =
+            stats.day_hist[1..].iter().map(|&c| OwnedValue::from(c as u64)).collect();
+        obj.insert("day_hist".into(), OwnedValue::from(days));
+
+        let weekdays: Vec<OwnedValue> =
+            stats.weekday_hist.iter().map(|&c| OwnedValue::from(c as u64)).collect();
+        obj.insert("weekday_hist".into(), OwnedValue::from(weekdays));
+
+        // [день недели с понедельника][час]
+        let week_hour: Vec<OwnedValue> = stats
+            .week_hour
+            .iter()
+            .map(|row| OwnedValue::from(row.iter().map(|&c| c as u64).collect::<Vec<_>>()))
+            .collect();
+        obj.insert("weekday_hour".into(), OwnedValue::from(week_hour));
+
+        let mut months = Object::new();
+        for (month, n) in stats.timeline.months() {
+            months.insert(month, OwnedValue::from(n as u64));
+        }
+        let mut years = Object::new();
+        for (year, n) in stats.timeline.years() {
+            years.insert(year.to_string(), OwnedValue::from(n as u64));
+        }
+        obj.insert("months".into(), OwnedValue::from(months));
+        obj.insert("years".into(), OwnedValue::from(years));
+
+        if let Some(s) = stats.timeline.streaks() {
+            let range = |r: DayRange| {
+                json!({ "from": r.from.to_string(), "to": r.to.to_string(), "days": r.days })
+            };
+            obj.insert(
+                "streaks".into(),
+                json!({
+                    "active_days": stats.timeline.days.len() as u64,
+                    "longest": range(s.longest),
+                    "current": range(s.current),
+                    "longest_silence": s.silence.map_or_else(OwnedValue::null, range)
+                }),
+            );
+        }
+
+        let growth: Vec<OwnedValue> = stats
+            .service
+            .growth()
+            .into_iter()
+            .map(|p| {
+                json!({
+                    "month": p.month,
+                    "joined": p.flow.joined as u64,
+                    "left": p.flow.left as u64,
+                    "members": p.members
+                })
+            })
+            .collect();
+        obj.insert("member_growth".into(), OwnedValue::from(growth));
+
+        if let Some(end) = stats.presence.history_end() {
+            let mut seen = Object::with_capacity(stats.presence.per_author.len());
+            for (author, p) in stats.presence.authors() {
+                seen.insert(
+                    author.to_string(),
+                    json!({
+                        "first": p.first.format("%Y-%m-%dT%H:%M:%S").to_string(),
+                        "last": p.last.format("%Y-%m-%dT%H:%M:%S").to_string(),
+                        "active_days": p.days.len() as u64,
+                        "days_since_last": (end.date() - p.last.date()).num_days()
+                    }),
+                );
+            }
+            obj.insert("first_last_seen".into(), OwnedValue::from(seen));
+        }
+
+        obj.insert("lengths".into(), lengths_json(&stats.lengths));
+        obj.insert("shouting".into(), shouting_json(&stats.shouting));
+
+        let mut style = Object::with_capacity(stats.style.per_author.len());
+        for (author, c) in stats.style.authors() {
+            style.insert(
+                author.to_string(),
+                json!({
+                    "messages": c.messages as u64,
+                    "punctuation_per_100_chars": c.punctuation_per_100(),
+                    "ellipses_per_message": c.per_message(c.ellipses),
+                    "emoji_per_message": c.per_message(c.emoji),
+                    "smileys_per_message": c.per_message(c.smileys)
+                }),
+            );
+        }
+        obj.insert("style".into(), OwnedValue::from(style));
+        obj.insert("questions".into(), questions_json(&stats.questions));
+        obj.insert("reply_latency".into(), latency_json(&stats.interactions));
+
+        let reply_pairs: Vec<OwnedValue> = stats
+            .interactions
+            .strongest_pairs()
+            .into_iter()
+            .map(|p| {
+                json!({
+                    "a": p.a,
+                    "b": p.b,
+                    "replies": p.total(),
+                    "a_to_b": p.a_to_b,
+                    "b_to_a": p.b_to_a
+                })
+            })
+            .collect();
+        obj.insert("reply_pairs".into(), OwnedValue::from(reply_pairs));
+
+        let spam: Vec<OwnedValue> = spam_scores(stats)
+            .into_iter()
+            .take(stats.spam.top)
+            .map(|(author, extra)| {
+                let mut entry = json!({ "author": author, "extra_repeats": extra as u64 });
+                if stats.spam.details
+                    && let Some(eo) = entry.as_object_mut()
+                {
+                    let repeats: Vec<OwnedValue> = spam_repeats(stats, author)
+                        .into_iter()
+                        .map(|(text, n)| json!({ "text": text, "count": n as u64 }))
+                        .collect();
+                    eo.insert("repeats".into(), OwnedValue::from(repeats));
+                }
+                entry
+            })
+            .collect();
+        obj.insert("spam_scores".into(), OwnedValue::from(spam));
+
+        let copied: Vec<OwnedValue> = copypasta(stats)
+            .into_iter()
+            .take(TOP_COPYPASTA)
+            .map(|c| json!({ "text": c.text, "count": c.count as u64, "authors": c.authors }))
+            .collect();
+        obj.insert("copypasta".into(), OwnedValue::from(copied));
+
+        let f = &stats.forwards;
+        let forward_spam: Vec<OwnedValue> = f
+            .forward_spammers(&stats.per_author)
+            .into_iter()
+            .map(|(author, fwd, total)| {
+                json!({ "author": author, "forwards": fwd as u64, "messages": total as u64 })
+            })
+            .collect();
+        let repeated: Vec<OwnedValue> = f
+            .repeated(stats.spam.min_repeats)
+            .into_iter()
+            .map(|(source, text, n)| json!({ "source": source, "text": text, "count": n as u64 }))
+            .collect();
+        obj.insert(
+            "forward_spam".into(),
+            json!({
+                "ratio": f.spam_ratio,
+                "authors": forward_spam,
+                "repeated": repeated
+            }),
+        );
+
+        let r = &stats.reactions;
+        let mut reactions = json!({
+            "total": r.total as u64,
+            "messages_with_reactions": r.messages_with_reactions as u64
+        });
+        if let Some(ro) = reactions.as_object_mut() {
+            ro.insert("by_emoji".into(), count_map(&r.by_emoji));
+            ro.insert("received".into(), count_map(&r.received));
+            ro.insert("given".into(), count_map(&r.given));
+            let top: Vec<OwnedValue> = r
+                .top_messages
+                .iter()
+                .map(|m| {
+                    json!({
+                        "reactions": m.reactions as u64,
+                        "id": m.id,
+                        "author": m.author.as_str(),
+                        "date": m.date.as_str(),
+                        "text": m.excerpt.as_str()
+                    })
+                })
+                .collect();
+            ro.insert("top_messages".into(), OwnedValue::from(top));
+        }
+        obj.insert("reactions".into(), reactions);
+
+        let mut entities = json!({
+            "messages_with_entities": stats.entities.messages_with_entities as u64
+        });
+        if let Some(eo) = entities.as_object_mut() {
+            eo.insert("by_type".into(), count_map(&stats.entities.by_type));
+        }
+        obj.insert("entities".into(), entities);
+
+        let l = &stats.links;
+        let mut links = json!({ "total_urls": l.total_urls as u64 });
+        if let Some(lo) = links.as_object_mut() {
+            lo.insert("domains".into(), count_map(&l.domains));
+            let mut per_author = Object::with_capacity(l.per_author.len());
+            for (author, _) in l.authors_by_links() {
+                per_author.insert(author.to_string(), count_map(&l.per_author[author]));
+            }
+            lo.insert("per_author".into(), OwnedValue::from(per_author));
+        }
+        obj.insert("links".into(), links);
+
+        let f = &stats.forwards;
+        let mut forwards = json!({ "total": f.total as u64 });
+        if let Some(fo) = forwards.as_object_mut() {
+            fo.insert("sources".into(), count_map(&f.sources));
+            let mut per_author = Object::with_capacity(f.per_author.len());
+            for (author, _) in f.forwarders() {
+                per_author.insert(author.to_string(), count_map(&f.per_author[author]));
+            }
+            fo.insert("per_author".into(), OwnedValue::from(per_author));
+        }
+        obj.insert("forwards".into(), forwards);
+
+        let longest: Vec<OwnedValue> = stats
+            .longest_videos
+            .items
+            .iter()
+            .map(|m| {
+                json!({
+                    "seconds": m.seconds,
+                    "kind": m.kind,
+                    "id": m.id,
+                    "author": m.author.as_str(),
+                    "date": m.date.as_str(),
+                    "file": m.file.as_str()
+                })
+            })
+            .collect();
+        obj.insert("longest_videos".into(), OwnedValue::from(longest));
+
+        let st = &stats.stickers;
+        let mut stickers = json!({});
+        if let Some(so) = stickers.as_object_mut() {
+            so.insert("by_emoji".into(), count_map(&st.by_emoji));
+            let mut per_author = Object::with_capacity(st.per_author.len());
+            for (author, emoji) in &st.per_author {
+                per_author.insert(author.clone(), count_map(emoji));
+            }
+            so.insert("per_author".into(), OwnedValue::from(per_author));
+            so.insert("by_file".into(), count_map(&st.by_file));
+        }
+        obj.insert("stickers".into(), stickers);
+
+        let ce = &stats.custom_emoji;
+        let mut custom_emoji = json!({ "total": ce.total as u64 });
+        if let Some(co) = custom_emoji.as_object_mut() {
+            let by_id: Vec<OwnedValue> = sorted_by_count(&ce.by_id)
+                .into_iter()
+                .map(|(id, count)| {
+                    let emoji = ce.fallback.get(id).map(String::as_str).unwrap_or("");
+                    json!({ "document_id": id, "emoji": emoji, "count": count as u64 })
+                })
+                .collect();
+            co.insert("by_id".into(), OwnedValue::from(by_id));
+            co.insert("per_author".into(), count_map(&ce.per_author));
+        }
+        obj.insert("custom_emoji".into(), custom_emoji);
+
+        let mut commands = json!({ "total": stats.commands.total as u64 });
+        if let Some(co) = commands.as_object_mut() {
+            co.insert("by_command".into(), count_map(&stats.commands.by_command));
+            co.insert("per_author".into(), count_map(&stats.commands.per_author));
+        }
+        obj.insert("commands".into(), commands);
+
+        let m = &stats.mentions;
+        let mut mentions = json!({ "total": m.total as u64 });
+        if let Some(mo) = mentions.as_object_mut() {
+            mo.insert("mentioned".into(), count_map(&m.mentioned));
+            mo.insert("mentioners".into(), count_map(&m.mentioners));
+            let pairs: Vec<OwnedValue> = m
+                .top_pairs()
+                .into_iter()
+                .map(|(from, to, c)| json!({ "from": from, "to": to, "count": c as u64 }))
+                .collect();
+            mo.insert("pairs".into(), OwnedValue::from(pairs));
+        }
+        obj.insert("mentions".into(), mentions);
+
+        let pins: Vec<OwnedValue> = stats
+            .pins
+            .iter()
+            .map(|p| {
+                json!({
+                    "chat": p.chat.as_str(),
+                    "date": p.date.as_str(),
+                    "pinned_by": p.pinned_by.as_str(),
+                    "id": p.id,
+                    "author": p.author.as_deref(),
+                    "text": p.text.as_str()
+                })
+            })
+            .collect();
+        obj.insert("pins".into(), OwnedValue::from(pins));
+
+        let changes: Vec<OwnedValue> = stats
+            .service
+            .changes
+            .iter()
+            .map(|c| {
+                json!({
+                    "chat": c.chat.as_str(),
+                    "date": c.date.as_str(),
+                    "actor": c.actor.as_str(),
+                    "action": c.action,
+                    "value": c.value.as_deref()
+                })
+            })
+            .collect();
+        obj.insert("chat_changes".into(), OwnedValue::from(changes));
+    }
+
+    root
+}
+
+pub fn write_stats_json<W: Write>(w: &mut W, stats: &Stats, verbose: bool) -> io::Result<()> {
+    build_stats_json(stats, verbose).write_pp(w)?;
+    writeln!(w)
+}
@@ -0,0 +1,1296 @@
+use simd_json::prelude::*;
+use simd_json::BorrowedValue;
+
+use chrono::{Datelike, Timelike};
+use chrono_tz::Tz;
+use ahash::{AHashMap, AHashSet};
+use memchr::memchr3;
+
+use crate::calls::CallStats;
+use crate::commands::CommandStats;
+use crate::context::SearchContext;
+use crate::counters::Counters;
+use crate::custom_emoji::CustomEmojiStats;
+use crate::durations::{DurationStats, LongestMedia};
+use crate::entities::EntityStats;
+use crate::filter::{Filter, TextMatcher};
+use crate::forwards::ForwardStats;
+use crate::interactions::InteractionStats;
+use crate::lengths::LengthStats;
+use crate::links::LinkStats;
+use crate::media::MediaSizeStats;
+use crate::mentions::MentionStats;
+use crate::metric::Metrics;
+use crate::model::{MediaType, Message, MessageKind};
+use crate::output::{
+    LogStyle, MessageRecord, OutputFormat, write_jsonl_line, write_log_line, write_service_line,
+    write_stats,
+};
+use crate::parser::{
+    KNOWN_MESSAGE_FIELDS, STDIO, author_name, chat_output_path, for_each_text_segment,
+    get_i64_field, get_msg_date, get_str_field, get_unixtime, msg_has_link, msg_ref, text_is_empty,
+};
+use crate::pins::PinnedMessage;
+use crate::presence::PresenceStats;
+use crate::questions::QuestionStats;
+use crate::reactions::ReactionStats;
+use crate::replies::MessageIndex;
+use crate::service::{ChatChange, ServiceStats};
+use crate::stat_json::build_stats_json;
+use crate::stickers::StickerStats;
+use crate::stopwords::Stopwords;
+use crate::style::{ShoutStats, StyleStats};
+use crate::table::Term;
+use crate::timeline::Timeline;
+use crate::timings::{Timings, timed};
+use crate::{
+    Metric, Options, anonymize, append, approx, arrow_out, authors, cache, extract, heatmap,
+    html_chat, links, log_out, manifest, parallel, pins, script, sentiment, sqlite, stem, unique,
+    wordlist,
+};
+
+use log::debug;
+
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+//
+// ===================== СТАТИСТИКА =====================
+//
+
+/// Итог разбора экспорта. Поля внутренние; снаружи — методы ниже,
+/// [`Stats::write_text`] и [`Stats::to_json`].
+#[derive(Default)]
+pub struct Stats {
+    pub(crate) chat_name: String,
+
+    pub(crate) total_messages: usize,
+    pub(crate) messages_with_any_media: usize,
+
+    pub(crate) photo_messages: usize,
+    pub(crate) video_messages: usize,
+    pub(crate) voice_messages: usize,
+    pub(crate) audio_messages: usize,
+    pub(crate) gif_messages: usize,
+    pub(crate) sticker_messages: usize,
+    pub(crate) file_messages: usize,
+
+    pub(crate) poll_messages: usize,
+    pub(crate) forwarded_messages: usize,
+    pub(crate) link_messages: usize,
+
+    pub(crate) per_author: AHashMap<String, usize>,
+
+    // длительность голосовых, в целом и по авторам
+    pub(crate) voice_durations: DurationStats,
+    // видеофайлы и кружки (video_message)
+    pub(crate) video_durations: DurationStats,
+    pub(crate) round_video_durations: DurationStats,
+    pub(crate) longest_videos: LongestMedia,
+
+    // --media-dir: размеры файлов на диске
+    pub(crate) media_sizes: MediaSizeStats,
+    // --media-manifest: (файлов в манифесте, из них отсутствуют)
+    pub(crate) manifest_counts: Option<(usize, usize)>,
+    // --extract-media: (скопировано, пропущено)
+    pub(crate) extract_counts: Option<(usize, usize)>,
+    // --html-chat: сколько страниц-месяцев записано
+    pub(crate) html_pages: Option<usize>,
+    // --split-by, --max-output-size: сколько файлов лога получилось
+    pub(crate) split_files: Option<usize>,
+    // --append: сколько сообщений дописано в лог
+    pub(crate) appended: Option<usize>,
+    // несколько -i: сколько экспортов склеено и сколько повторов отброшено
+    pub(crate) merged: Option<(usize, usize)>,
+
+    // топ слов и его длина (--top-words)
+    pub(crate) word_freq: AHashMap<String, usize>,
+    pub(crate) top_words: usize,
+    // --approx: предел счётчиков в словарях слов и спама; None — точно
+    pub(crate) approx: Option<usize>,
+    pub(crate) word_freq_per_author: AHashMap<String, AHashMap<String, usize>>,
+
+    // активность
+    pub(crate) hour_hist: [usize; 24], // по часам
+    pub(crate) day_hist: [usize; 32],  // по дню месяца (1..31)
+    pub(crate) weekday_hist: [usize; 7], // по дню недели, с понедельника
+    pub(crate) week_hour: heatmap::WeekHourGrid, // день недели × час
+    // сообщения по календарным дням: месяцы, годы (только при verbose)
+    pub(crate) timeline: Timeline,
+    // первое/последнее сообщение и активные дни по авторам (только при verbose)
+    pub(crate) presence: PresenceStats,
+
+    // длина сообщений в символах и словах (только при verbose)
+    pub(crate) lengths: LengthStats,
+    // капс и «!» по авторам (только при verbose)
+    pub(crate) shouting: ShoutStats,
+    // пунктуация, многоточия, эмодзи, скобки (только при verbose)
+    pub(crate) style: StyleStats,
+    // вопросы и сколько из них получили ответ (только при verbose)
+    pub(crate) questions: QuestionStats,
+    // кто кому отвечает и как быстро (только при verbose)
+    pub(crate) interactions: InteractionStats,
+
+    // спам: автор -> (текст -> количество)
+    pub(crate) spam_map: AHashMap<String, AHashMap<String, usize>>,
+    pub(crate) spam: SpamConfig,
+
+    // реакции (только при verbose)
+    pub(crate) reactions: ReactionStats,
+
+    // служебные сообщения: вступления, выходы, закрепы...
+    pub(crate) service: ServiceStats,
+    // звонки (phone_call / group_call)
+    pub(crate) calls: CallStats,
+
+    // команды ботам (только при verbose)
+    pub(crate) commands: CommandStats,
+    // типы разметки из text_entities (только при verbose)
+    pub(crate) entities: EntityStats,
+    // эмодзи и файлы стикеров (только при verbose)
+    pub(crate) stickers: StickerStats,
+    // премиум-эмодзи по document_id (только при verbose)
+    pub(crate) custom_emoji: CustomEmojiStats,
+    // домены ссылок (только при verbose)
+    pub(crate) links: LinkStats,
+    // откуда пересылают (только при verbose)
+    pub(crate) forwards: ForwardStats,
+    // упоминания и кто кого упоминает (только при verbose)
+    pub(crate) mentions: MentionStats,
+    // разные слова, ссылки и адресаты ответов, оценки HLL (только при verbose)
+    pub(crate) uniques: unique::UniqueStats,
+
+    // история закрепов (при verbose или --pins)
+    pub(crate) pins: Vec<PinnedMessage>,
+
+    // --grep: шаблон и число совпадений
+    pub(crate) grep_pattern: Option<String>,
+    pub(crate) grep_matches: usize,
+    pub(crate) grep_per_author: AHashMap<String, usize>,
+
+    // --wordlist: тематические словари
+    pub(crate) wordlists: Vec<wordlist::Wordlist>,
+    // --counter: именованные регулярные выражения
+    pub(crate) counters: Counters,
+    // --sentiment: словарная тональность
+    pub(crate) sentiment: Option<sentiment::SentimentStats>,
+
+    // свои метрики библиотеки
+    pub(crate) metrics: Metrics,
+}
+
+cache::codec!(Stats {
+    chat_name, total_messages, messages_with_any_media, photo_messages, video_messages,
+    voice_messages, audio_messages, gif_messages, sticker_messages, file_messages, poll_messages,
+    forwarded_messages, link_messages, per_author, voice_durations, video_durations,
+    round_video_durations, longest_videos, media_sizes, manifest_counts, extract_counts, html_pages,
+    split_files, appended, merged, word_freq, top_words, approx, word_freq_per_author, hour_hist,
+    day_hist, weekday_hist, week_hour, timeline, presence, lengths, shouting, style, questions,
+    interactions, spam_map, spam, reactions, service, calls, commands, entities, stickers,
+    custom_emoji, links, forwards, mentions, uniques, pins, grep_pattern, grep_matches,
+    grep_per_author, wordlists, counters, sentiment, metrics,
+});
+
+impl Stats {
+    /// Имя чата; при нескольких чатах — «все чаты экспорта (N)».
+    pub fn chat_name(&self) -> &str {
+        &self.chat_name
+    }
+
+    /// Сообщений, прошедших фильтры (без служебных).
+    pub fn total_messages(&self) -> usize {
+        self.total_messages
+    }
+
+    /// Сообщения по авторам, от самого активного.
+    pub fn per_author(&self) -> Vec<(&str, usize)> {
+        sorted_by_count(&self.per_author)
+    }
+
+    /// Топ слов длиной [`Options::top_words`]; пуст без [`Options::verbose`].
+    pub fn top_words(&self) -> Vec<(&str, usize)> {
+        top_by_count(&self.word_freq, self.top_words)
+    }
+
+    /// Сообщения по часам суток, 0..23.
+    pub fn hour_hist(&self) -> &[usize; 24] {
+        &self.hour_hist
+    }
+
+    /// Сообщения по дням недели, с понедельника.
+    pub fn weekday_hist(&self) -> &[usize; 7] {
+        &self.weekday_hist
+    }
+
+    /// Свои метрики из [`crate::Analyzer::metric`], в порядке добавления.
+    pub fn metrics(&self) -> &[Box<dyn Metric>] {
+        &self.metrics.0
+    }
+
+    /// Статистика текстом, как её печатает tgjsps; `verbose` — как -v.
+    pub fn write_text(&self, w: &mut impl Write, verbose: bool) -> io::Result<()> {
+        write_stats(w, self, verbose, Term::default())
+    }
+
+    /// Статистика JSON-документом, как в --stat-format json.
+    pub fn to_json(&self, verbose: bool) -> String {
+        build_stats_json(self, verbose).encode()
+    }
+}
+
+impl Stats {
+    // встроенные метрики -v, которым важен порядок сообщений: ответы на
+    // вопросы, имена по from_id для упоминаний
+    fn message_metrics(&mut self) -> [&mut dyn Metric; 3] {
+        [&mut self.stickers, &mut self.questions, &mut self.mentions]
+    }
+
+    // встроенные метрики -v по одному тексту: с --threads считаются по частям
+    fn text_metrics(&mut self) -> [&mut dyn Metric; 7] {
+        [
+            &mut self.lengths,
+            &mut self.shouting,
+            &mut self.style,
+            &mut self.entities,
+            &mut self.links,
+            &mut self.custom_emoji,
+            &mut self.commands,
+        ]
+    }
+}
+
+//
+// ===================== ОБРАБОТКА СООБЩЕНИЯ =====================
+//
+
+pub(crate) struct Processor {
+    pub(crate) stats: Stats,
+    // None — текущий чат не выбран (--chat), его сообщения пропускаются
+    pub(crate) out: Option<log_out::LogOut>,
+    // false — лог никуда не пишется (tgjsps stats, report)
+    pub(crate) write_log: bool,
+    // --split-by: период файла лога и все созданные файлы периодов
+    pub(crate) split_by: Option<log_out::SplitBy>,
+    pub(crate) max_output_size: Option<u64>,
+    pub(crate) compress: bool,
+    // --append: состояния всех логов и текущего чата
+    pub(crate) append: Option<Vec<append::AppendState>>,
+    pub(crate) append_state: Option<append::AppendState>,
+    // статистика прошлых запусков загружена в stats
+    pub(crate) append_resumed: bool,
+    // все файлы лога: с --split-by и --max-output-size их больше, чем чатов
+    pub(crate) split_files: AHashSet<String>,
+    pub(crate) verbose: bool,
+    // --threads: отложенная текстовая статистика; None — считается сразу
+    pub(crate) text_jobs: Option<parallel::TextJobs>,
+    // не учитываются в топе слов и словаре авторов
+    pub(crate) stopwords: Stopwords,
+    // --stem: считать основы слов, а не словоформы
+    pub(crate) stem: bool,
+    // --timings: время по этапам
+    pub(crate) timings: Timings,
+
+    pub(crate) output_path: String,
+    pub(crate) chat_selector: Option<String>,
+    pub(crate) chats_seen: usize,
+    pub(crate) outputs: Vec<String>,
+
+    pub(crate) filter: Filter,
+    // --filter-script: внешний фильтр после встроенных
+    pub(crate) filter_script: Option<script::ScriptFilter>,
+    // search -C: соседние сообщения вокруг совпадений
+    pub(crate) context: Option<SearchContext>,
+
+    pub(crate) sqlite: Option<sqlite::SqliteSink>,
+    pub(crate) arrow: Option<arrow_out::ArrowSink>,
+    // --links: выгрузка всех URL
+    pub(crate) links_out: Option<links::LinkDump>,
+    // --media-dir: где лежат файлы медиа
+    pub(crate) media_dir: Option<PathBuf>,
+    pub(crate) manifest: Option<manifest::MediaManifest>,
+    pub(crate) extractor: Option<extract::MediaExtractor>,
+    pub(crate) html_chat: Option<html_chat::HtmlChat>,
+
+    pub(crate) output_format: OutputFormat,
+    pub(crate) log_style: LogStyle,
+
+    // --timezone: в каком поясе считать часы/дни и печатать даты
+    pub(crate) timezone: Option<Tz>,
+
+    // id -> автор и текст, чтобы показать, что именно закрепили
+    pub(crate) pin_index: Option<MessageIndex>,
+
+    // --anonymize: псевдонимы вместо имён
+    pub(crate) anonymizer: Option<anonymize::Anonymizer>,
+    // from_id -> последнее имя (без --by-name)
+    pub(crate) author_names: Option<authors::AuthorNames>,
+    // -vv: незнакомые поля сообщений, о которых уже сказали
+    pub(crate) unknown_fields: AHashSet<String>,
+}
+
+impl Processor {
+    /// Подсчёт без выходов: лог никуда не пишется, выгрузок нет. CLI
+    /// добавляет свои выходы поверх.
+    pub(crate) fn new(opts: &Options) -> Result<Processor, Box<dyn std::error::Error>> {
+        if let Some(dir) = &opts.media_dir
+            && !dir.is_dir()
+        {
+            return Err(format!("Папка медиа «{}» не найдена", dir.display()).into());
+        }
+        let grep = match &opts.grep {
+            Some(p) => Some(TextMatcher::new(p, opts.regex)?),
+            None => None,
+        };
+        Ok(Processor {
+            stats: Stats {
+                grep_pattern: opts.grep.clone(),
+                wordlists: opts
+                    .wordlists
+                    .iter()
+                    .map(|p| wordlist::Wordlist::load(p))
+                    .collect::<Result<_, _>>()?,
+                counters: Counters::new(&opts.counters)?,
+                sentiment: if opts.sentiment {
+                    Some(sentiment::SentimentStats::new(opts.lexicon.as_deref())?)
+                } else {
+                    None
+                },
+                forwards: ForwardStats::new(opts.forward_spam_ratio),
+                top_words: opts.top_words,
+                approx: opts.approx,
+                spam: opts.spam.clone(),
+                ..Stats::default()
+            },
+            out: None,
+            write_log: false,
+            split_by: None,
+            max_output_size: None,
+            compress: false,
+            append: None,
+            append_state: None,
+            append_resumed: false,
+            split_files: AHashSet::new(),
+            verbose: opts.verbose,
+            text_jobs: parallel::TextJobs::new(opts.threads, opts.verbose),
+            stopwords: Stopwords::new(opts.stopwords, opts.stopwords_file.as_deref())?,
+            stem: opts.stem,
+            timings: Timings::default(),
+            output_path: STDIO.to_string(),
+            chat_selector: opts.chat.clone(),
+            chats_seen: 0,
+            outputs: Vec::new(),
+            filter: Filter {
+                since: opts.since,
+                until: opts.until,
+                only_authors: opts.only_authors.clone(),
+                exclude_authors: opts.exclude_authors.clone(),
+                no_bots: opts.no_bots || !opts.bots.is_empty(),
+                bots: opts.bots.clone(),
+                grep,
+            },
+            filter_script: None,
+            context: None,
+            sqlite: None,
+            arrow: None,
+            links_out: None,
+            media_dir: opts.media_dir.clone(),
+            manifest: None,
+            extractor: None,
+            html_chat: None,
+            output_format: OutputFormat::Text,
+            log_style: LogStyle::default(),
+            timezone: opts.timezone,
+            pin_index: opts.pins.then(|| MessageIndex::new(pins::PIN_QUOTE_CHARS)),
+            anonymizer: opts.anonymize.then(anonymize::Anonymizer::default),
+            author_names: (!opts.by_name).then(authors::AuthorNames::default),
+            unknown_fields: AHashSet::new(),
+        })
+    }
+
+    /// Конец входа: закрыть последний чат и проверить, что хоть что-то
+    /// разобрано.
+    pub(crate) fn finish(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.finish_chat()?;
+        if let Some(filter) = self.filter_script.take() {
+            filter.finish()?;
+        }
+        for metric in &mut self.stats.metrics.0 {
+            metric.finish()?;
+        }
+        if self.chats_seen == 0 {
+            return Err("В корне нет ни \"messages\", ни \"chats.list\"".into());
+        }
+        if self.outputs.is_empty() {
+            let sel = self.chat_selector.as_deref().unwrap_or("");
+            return Err(format!("Чат «{sel}» не найден в экспорте").into());
+        }
+        if self.outputs.len() > 1 {
+            self.stats.chat_name = format!("все чаты экспорта ({})", self.outputs.len());
+        }
+        Ok(())
+    }
+
+    /// Начало очередного чата: решаем, обрабатывать ли его и куда писать.
+    pub(crate) fn begin_chat(&mut self, name: &str, id: &str, nested: bool) -> io::Result<()> {
+        self.finish_chat()?;
+        self.chats_seen += 1;
+        if let Some(index) = self.log_style.replies.as_mut() {
+            index.clear();
+        }
+        if let Some(index) = self.pin_index.as_mut() {
+            index.clear();
+        }
+        self.stats.questions.new_chat();
+        self.stats.interactions.new_chat();
+        if let Some(context) = self.context.as_mut() {
+            context.new_chat();
+        }
+
+        // --chat сравнивается с настоящим именем, а в файлы и статистику идёт псевдоним
+        let shown = match self.anonymizer.as_mut() {
+            Some(anon) => anon.chat(id, name),
+            None => name.to_string(),
+        };
+
+        let path = if !nested {
+            self.log_path(self.output_path.clone())
+        } else {
+            match &self.chat_selector {
+                Some(sel) if (sel == name || sel == id) && self.outputs.is_empty() => {
+                    self.log_path(self.output_path.clone())
+                }
+                Some(_) => {
+                    debug!("Чат «{name}» пропущен: не выбран в --chat");
+                    return Ok(());
+                }
+                None => {
+                    let fmt = self.output_format;
+                    if self.output_path == STDIO {
+                        // все чаты подряд в один поток
+                        return self.open_log(shown, id, STDIO.to_string());
+                    }
+                    let mut path = self.log_path(chat_output_path(&self.output_path, &shown, fmt));
+                    if self.outputs.contains(&path) {
+                        let unique = format!("{shown}_{id}");
+                        path = self.log_path(chat_output_path(&self.output_path, &unique, fmt));
+                    }
+                    path
+                }
+            }
+        };
+
+        self.open_log(shown, id, path)
+    }
+
+    pub(crate) fn open_log(&mut self, shown: String, id: &str, path: String) -> io::Result<()> {
+        self.stats.chat_name = shown;
+        if !self.write_log {
+            self.out = Some(log_out::LogOut::discard());
+            self.outputs.push(path);
+            return Ok(());
+        }
+        self.append_state = match self.append {
+            Some(_) => Some(append::AppendState::load(&path, id)?),
+            None => None,
+        };
+        let resumes = self.append_state.as_ref().is_some_and(|s| s.resumes());
+        if resumes && !self.append_resumed {
+            return Err(io::Error::other(format!(
+                "{path}.state есть, а статистики прошлых запусков нет; удалите лог и .state, \
+                 чтобы начать заново"
+            )));
+        }
+        let (split, max_size) = (self.split_by, self.max_output_size);
+        let opened = &mut self.split_files;
+        let out = log_out::LogOut::create(&path, split, max_size, resumes, opened)?;
+        self.out = Some(out);
+        self.outputs.push(path);
+        Ok(())
+    }
+
+    /// --compress: лог пишется в .gz рядом с обычным путём.
+    pub(crate) fn log_path(&self, path: String) -> String {
+        if self.compress && !path.ends_with(".gz") { format!("{path}.gz") } else { path }
+    }
+
+    /// -vv: незнакомые поля сообщений, каждое — один раз за запуск.
+    pub(crate) fn note_unknown_fields(&mut self, obj: &simd_json::borrowed::Object) {
+        for key in obj.keys() {
+            if !KNOWN_MESSAGE_FIELDS.contains(&key.as_ref())
+                && self.unknown_fields.insert(key.to_string())
+            {
+                debug!("Незнакомое поле сообщения «{key}» (впервые в {})", msg_ref(obj));
+            }
+        }
+    }
+
+    pub(crate) fn finish_chat(&mut self) -> io::Result<()> {
+        if let Some(state) = self.append_state.take()
+            && let Some(states) = self.append.as_mut()
+        {
+            states.push(state);
+        }
+        match self.out.take() {
+            Some(out) => timed(&mut self.timings.output, || out.finish()),
+            None => Ok(()),
+        }
+    }
+
+    pub(crate) fn process_message(&mut self, msg_val: &BorrowedValue) -> io::Result<()> {
+        self.timings.messages += 1;
+        let BorrowedValue::Object(obj) = msg_val else {
+            debug!("Пропущено сообщение: не объект");
+            return Ok(());
+        };
+        if self.out.is_none() {
+            return Ok(());
+        }
+        if log::log_enabled!(log::Level::Debug) {
+            self.note_unknown_fields(obj);
+        }
+        // копия сообщения — только если в нём что-то меняется
+        let renamed = self.author_names.as_ref().and_then(|names| names.rewrite(obj));
+        let rewritten = match self.anonymizer.as_mut() {
+            Some(anon) => {
+                let mut obj = renamed.unwrap_or_else(|| obj.as_ref().clone());
+                anon.rewrite(&mut obj);
+                Some(obj)
+            }
+            None => renamed,
+        };
+        let Some(obj) = rewritten else {
+            return self.handle_message(msg_val);
+        };
+        let msg = BorrowedValue::from(obj);
+        self.handle_message(&msg)?;
+        // потокам --threads нужна переписанная копия, а не оригинал
+        if let Some(jobs) = self.text_jobs.as_mut() {
+            jobs.attach(msg.into_static());
+        }
+        Ok(())
+    }
+
+    pub(crate) fn handle_message(&mut self, msg_val: &BorrowedValue) -> io::Result<()> {
+        let stats = &mut self.stats;
+        let Some(out) = self.out.as_mut() else {
+            return Ok(());
+        };
+        let verbose = self.verbose;
+
+        let msg_obj = match msg_val {
+            BorrowedValue::Object(obj) => obj,
+            _ => return Ok(()),
+        };
+
+        let msg = match Message::from_value(msg_val) {
+            Ok(msg) => msg,
+            Err(e) => {
+                debug!("Пропущено сообщение {}: {e}", msg_ref(msg_obj));
+                return Ok(());
+            }
+        };
+        match msg.kind {
+            MessageKind::Message => {}
+            MessageKind::Service => return self.process_service(msg_obj),
+            MessageKind::Other => {
+                let msg_type = get_str_field(msg_obj, "type").unwrap_or("");
+                debug!("Пропущено сообщение {}: тип «{msg_type}»", msg_ref(msg_obj));
+                return Ok(());
+            }
+        }
+
+        // дата нужна фильтру по диапазону, гистограммам активности,
+        // --with-date, --links, выгрузкам медиа, тональности и --split-by
+        let date = if verbose
+            || stats.sentiment.is_some()
+            || self.split_by.is_some()
+            || self.filter.needs_date()
+            || self.log_style.needs_date()
+            || self.links_out.is_some()
+            || self.manifest.is_some()
+            || self.extractor.is_some()
+            || self.html_chat.is_some()
+        {
+            msg.date(self.timezone)
+        } else {
+            None
+        };
+        let name = msg.author();
+        let name = name.as_ref();
+        let from_id = msg.from_id.unwrap_or("no_id");
+
+        // индексируем до фильтров: отвечать могут и на отфильтрованное
+        if let Some(id) = msg.id {
+            if let Some(index) = self.log_style.replies.as_mut() {
+                index.insert(id, name, msg_obj.get("text"));
+            }
+            if let Some(index) = self.pin_index.as_mut() {
+                index.insert(id, name, msg_obj.get("text"));
+            }
+        }
+
+        if !self.filter.accepts_date(date) {
+            debug!("Пропущено сообщение {}: дата вне --since/--until", msg_ref(msg_obj));
+            return Ok(());
+        }
+
+        if !self.filter.accepts_author(name, from_id) {
+            debug!("Пропущено сообщение {}: автор «{name}» отфильтрован", msg_ref(msg_obj));
+            return Ok(());
+        }
+
+        // --append: старые сообщения уже в логе и в сохранённой статистике
+        if self.append_state.as_ref().is_some_and(|state| !state.is_new(msg_obj)) {
+            return Ok(());
+        }
+
+        if let Some(script) = self.filter_script.as_mut()
+            && !script.accepts(&msg)?
+        {
+            debug!("Пропущено сообщение {}: отброшено --filter-script", msg_ref(msg_obj));
+            return Ok(());
+        }
+
+        if let Some(matcher) = &self.filter.grep {
+            let matches = matcher.count_matches(&msg.plain_text());
+            if matches == 0 {
+                debug!("Пропущено сообщение {}: нет совпадений с --grep", msg_ref(msg_obj));
+                let Some(context) = self.context.as_mut() else {
+                    return Ok(());
+                };
+                let has_text = msg_obj.get("text").is_some_and(|t| !text_is_empty(t));
+                return context.skipped(out, date, name, &mut self.split_files, |mut w| {
+                    write_log_line(&mut w, &self.log_style, &msg, msg_obj, name, date, has_text)
+                });
+            }
+            stats.grep_matches += matches;
+            *stats.grep_per_author.entry(name.to_string()).or_insert(0) += matches;
+        }
+
+        stats.total_messages += 1;
+
+        *stats.per_author.entry(name.to_string()).or_insert(0) += 1;
+
+        stats.counters.observe(&msg);
+        for metric in &mut stats.metrics.0 {
+            metric.observe(&msg);
+        }
+
+        if msg.is_forwarded() {
+            stats.forwarded_messages += 1;
+            if verbose {
+                stats.forwards.observe(name, msg_obj, stats.spam.min_chars);
+            }
+        }
+
+        if verbose {
+            for metric in stats.message_metrics() {
+                metric.observe(&msg);
+            }
+            stats.interactions.observe(name, get_unixtime(msg_obj), date, msg_obj);
+            stats.uniques.observe_reply(&stats.chat_name, msg_obj);
+        }
+
+        // ===== дата -> активность (ТОЛЬКО при verbose) =====
+        if verbose && let Some(dt) = date {
+            let h = dt.hour() as usize;
+            if h < 24 {
+                stats.hour_hist[h] += 1;
+            }
+            let d = dt.day() as usize;
+            if d < stats.day_hist.len() {
+                stats.day_hist[d] += 1;
+            }
+            let wd = dt.weekday().num_days_from_monday() as usize;
+            stats.weekday_hist[wd] += 1;
+            if h < 24 {
+                stats.week_hour[wd][h] += 1;
+            }
+            stats.timeline.observe(dt.date());
+            stats.presence.observe(name, dt);
+        }
+
+        // ===== текст =====
+        let mut has_any_text = false;
+
+        if let Some(text_val) = msg_obj.get("text")
+            && !text_is_empty(text_val)
+        {
+            has_any_text = true;
+            if !stats.wordlists.is_empty() || stats.sentiment.is_some() {
+                let full = msg.plain_text();
+                for list in &mut stats.wordlists {
+                    list.observe(name, &full);
+                }
+                if let Some(s) = stats.sentiment.as_mut() {
+                    s.observe(name, date, &full);
+                }
+            }
+            if msg_has_link(msg_obj) {
+                stats.link_messages += 1;
+                if let Some(dump) = self.links_out.as_mut() {
+                    dump.observe(name, date, msg_obj)?;
+                }
+            }
+
+            // тяжёлый путь (только verbose): без лишних String для слов, но со спамом
+            if verbose {
+                // с --threads порядок-независимая часть считается в конце чата
+                match self.text_jobs.as_mut() {
+                    Some(jobs) => jobs.defer(),
+                    None => {
+                        observe_text(stats, &self.stopwords, self.stem, &msg, msg_obj, text_val)
+                    }
+                }
+            }
+        }
+
+        if verbose && let Some(reactions_val) = msg_obj.get("reactions") {
+            let count = stats.reactions.observe(name, reactions_val);
+            let date_str = match date {
+                Some(dt) => dt.format("%Y-%m-%d %H:%M").to_string(),
+                None => String::new(),
+            };
+            stats.reactions.offer_top(
+                count,
+                get_i64_field(msg_obj, "id").unwrap_or(0),
+                name,
+                &date_str,
+                msg_obj.get("text"),
+            );
+        }
+
+        // ======== медиа ========
+        let mut has_any_media = false;
+
+        if msg.photo.is_some() {
+            stats.photo_messages += 1;
+            has_any_media = true;
+        }
+
+        if let Some(mt) = msg.media_type {
+            match mt {
+                MediaType::VoiceMessage => {
+                    stats.voice_messages += 1;
+                    has_any_media = true;
+                    stats.voice_durations.observe(name, msg_obj);
+                }
+                MediaType::VideoFile => {
+                    stats.video_messages += 1;
+                    has_any_media = true;
+                    stats.video_durations.observe(name, msg_obj);
+                    stats.longest_videos.offer("видео", name, date, msg_obj);
+                }
+                MediaType::VideoMessage => {
+                    stats.round_video_durations.observe(name, msg_obj);
+                    stats.longest_videos.offer("кружок", name, date, msg_obj);
+                }
+                MediaType::AudioFile => {
+                    stats.audio_messages += 1;
+                    has_any_media = true;
+                }
+                MediaType::Animation => {
+                    stats.gif_messages += 1;
+                    has_any_media = true;
+                }
+                MediaType::Sticker => {
+                    stats.sticker_messages += 1;
+                    has_any_media = true;
+                }
+                MediaType::Other => {}
+            }
+        }
+
+        if msg.file.is_some() && msg.media_type.is_none() {
+            stats.file_messages += 1;
+            has_any_media = true;
+        }
+
+        if msg.poll.is_some() {
+            stats.poll_messages += 1;
+            has_any_media = true;
+        }
+
+        if has_any_media {
+            stats.messages_with_any_media += 1;
+        }
+
+        if let Some(kind) = msg.media_kind() {
+            if let Some(dir) = &self.media_dir {
+                stats.media_sizes.observe(dir, name, kind, msg_obj);
+            }
+            if let Some(manifest) = self.manifest.as_mut() {
+                manifest.observe(&stats.chat_name, name, kind, date, msg_obj)?;
+            }
+            if let Some(ex) = self.extractor.as_mut() {
+                ex.observe(name, kind, date, msg_obj)?;
+            }
+        }
+
+        let needs_record = self.sqlite.is_some()
+            || self.arrow.is_some()
+            || self.output_format == OutputFormat::Jsonl;
+        let rec = needs_record.then(|| MessageRecord::new(&msg, msg_obj, name));
+
+        if let Some(rec) = &rec {
+            if let Some(db) = self.sqlite.as_mut() {
+                db.insert(&stats.chat_name, rec).map_err(io::Error::other)?;
+            }
+            if let Some(arrow) = self.arrow.as_mut() {
+                arrow.insert(&stats.chat_name, rec).map_err(io::Error::other)?;
+            }
+        }
+
+        if let Some(html) = self.html_chat.as_mut() {
+            html.message(&stats.chat_name, &msg, name, date, msg_obj)?;
+        }
+
+        if let Some(state) = self.append_state.as_mut()
+            && !state.fresh(msg_obj)
+        {
+            return Ok(());
+        }
+        if let Some(context) = self.context.as_mut() {
+            context.before_match(out, &mut self.split_files)?;
+        }
+        out.select(date, name, &mut self.split_files)?;
+        match (self.output_format, &rec) {
+            (OutputFormat::Jsonl, Some(rec)) => write_jsonl_line(out, &self.log_style, rec),
+            _ => write_log_line(out, &self.log_style, &msg, msg_obj, name, date, has_any_text),
+        }
+    }
+
+    /// Служебное сообщение: в общую статистику не идёт, считается отдельно.
+    pub(crate) fn process_service(
+        &mut self,
+        msg_obj: &simd_json::borrowed::Object,
+    ) -> io::Result<()> {
+        let Some(out) = self.out.as_mut() else {
+            return Ok(());
+        };
+
+        let date = if self.verbose
+            || self.split_by.is_some()
+            || self.filter.needs_date()
+            || self.log_style.needs_date()
+            || self.pin_index.is_some()
+            || self.html_chat.is_some()
+        {
+            get_msg_date(msg_obj, self.timezone)
+        } else {
+            None
+        };
+        let actor = author_name(msg_obj, "actor", "actor_id");
+        let actor = actor.as_ref();
+        let actor_id = get_str_field(msg_obj, "actor_id").unwrap_or("no_id");
+
+        if !self.filter.accepts_date(date) || !self.filter.accepts_author(actor, actor_id) {
+            return Ok(());
+        }
+        // --grep отбирает сообщения по тексту, а у служебных его нет
+        if self.filter.grep.is_some() {
+            return Ok(());
+        }
+        if self.append_state.as_ref().is_some_and(|state| !state.is_new(msg_obj)) {
+            return Ok(());
+        }
+
+        self.stats.service.observe(msg_obj, date);
+        if let Some(action @ ("phone_call" | "group_call")) = get_str_field(msg_obj, "action") {
+            self.stats.calls.observe(action, actor, msg_obj);
+        }
+        if let Some(index) = &self.pin_index
+            && get_str_field(msg_obj, "action") == Some("pin_message")
+            && let Some(id) = get_i64_field(msg_obj, "message_id")
+        {
+            let date_str = match date {
+                Some(dt) => dt.format("%Y-%m-%d %H:%M").to_string(),
+                None => String::new(),
+            };
+            let pin = PinnedMessage::resolve(index, &self.stats.chat_name, date_str, actor, id);
+            self.stats.pins.push(pin);
+        }
+        if self.verbose {
+            let date_str = match date {
+                Some(dt) => dt.format("%Y-%m-%d %H:%M").to_string(),
+                None => String::new(),
+            };
+            if let Some(change) = ChatChange::from_service(&self.stats.chat_name, date_str, msg_obj)
+            {
+                self.stats.service.changes.push(change);
+            }
+        }
+
+        if self.log_style.service
+            && self.append_state.as_mut().is_none_or(|state| state.fresh(msg_obj))
+        {
+            out.select(date, actor, &mut self.split_files)?;
+            write_service_line(out, &self.log_style, msg_obj, date)?;
+        }
+        if let Some(html) = self.html_chat.as_mut() {
+            html.service(&self.stats.chat_name, date, msg_obj)?;
+        }
+        Ok(())
+    }
+}
+
+//
+// ===================== СВОЙ ТОКЕНИЗАТОР + СЛОВА + СПАМ =====================
+//
+
+/// Быстрая токенизация по ASCII-пробелам/табам/переводам строки.
+/// Колбэк получает &str без аллокаций.
+pub(crate) fn fast_tokenize(text: &str, mut cb: impl FnMut(&str)) {
+    let bytes = text.as_bytes();
+    let len = bytes.len();
+    let mut i = 0;
+
+    while i < len {
+        // пропускаем ASCII-пробелы
+        while i < len && is_ascii_ws(bytes[i]) {
+            i += 1;
+        }
+        if i >= len {
+            break;
+        }
+
+        let start = i;
+
+        // ищем следующий пробельный байт space / \n / \t
+        let slice = &bytes[i..];
+        let rel = memchr3(b' ', b'\n', b'\t', slice).unwrap_or(slice.len());
+        let end = i + rel;
+
+        i = end;
+
+        let token = &text[start..end];
+        cb(token);
+    }
+}
+
+#[inline]
+pub(crate) fn is_ascii_ws(b: u8) -> bool {
+    matches!(b, b' ' | b'\n' | b'\t' | b'\r')
+}
+
+/// Обрезаем ASCII-пунктуацию по краям, всё не-ASCII считаем частью слова.
+pub(crate) fn trim_ascii_punct(s: &str) -> &str {
+    let bytes = s.as_bytes();
+    let mut start = 0;
+    let mut end = bytes.len();
+
+    // слева
+    while start < end {
+        let b = bytes[start];
+        if b < 0x80 && !is_ascii_word_char(b) {
+            start += 1;
+        } else {
+            break;
+        }
+    }
+
+    // справа
+    while end > start {
+        let b = bytes[end - 1];
+        if b < 0x80 && !is_ascii_word_char(b) {
+            end -= 1;
+        } else {
+            break;
+        }
+    }
+
+    &s[start..end]
+}
+
+#[inline]
+pub(crate) fn is_ascii_word_char(b: u8) -> bool {
+    b.is_ascii_alphanumeric()
+        || b == b'#'
+        || b == b'@'
+        || b == b'_'
+}
+
+// считаем слова по сегментам текста, без общего String
+pub(crate) fn update_word_stats(
+    stats: &mut Stats,
+    stopwords: &Stopwords,
+    stem: bool,
+    author: &str,
+    text_val: &BorrowedValue,
+) {
+    // словарь автора достаётся один раз на сообщение и возвращается в конце
+    // вместе со старым ключом: имя автора копируется только для нового
+    let (key, mut author_words) = match stats.word_freq_per_author.remove_entry(author) {
+        Some((key, words)) => (Some(key), words),
+        None => (None, AHashMap::new()),
+    };
+    let sketch = stats.uniques.words_of(author);
+    let mut lower = String::new();
+    for_each_text_segment(text_val, |segment| {
+        fast_tokenize(segment, |raw| {
+            let token = trim_ascii_punct(raw);
+            if token.len() < 3 {
+                return;
+            }
+
+            if token.starts_with("http://") || token.starts_with("https://") {
+                return;
+            }
+
+            lowercase_into(token, &mut lower);
+            if lower.is_empty() || stopwords.contains(&lower) {
+                return;
+            }
+            let stemmed;
+            let word = if stem {
+                stemmed = stem::stem(&lower);
+                stemmed.as_str()
+            } else {
+                lower.as_str()
+            };
+
+            count_word(&mut stats.word_freq, word, stats.approx);
+            count_word(&mut author_words, word, stats.approx);
+            sketch.add(word);
+        });
+    });
+    if !author_words.is_empty() {
+        let key = key.unwrap_or_else(|| author.to_string());
+        stats.word_freq_per_author.insert(key, author_words);
+    }
+}
+
+/// Строка выделяется, только когда слово встретилось впервые.
+pub(crate) fn count_word(freq: &mut AHashMap<String, usize>, word: &str, approx: Option<usize>) {
+    match freq.get_mut(word) {
+        Some(n) => *n += 1,
+        None => {
+            freq.insert(word.to_string(), 1);
+            approx::trim(freq, approx);
+        }
+    }
+}
+
+/// `str::to_lowercase` в переиспользуемый буфер. Σ в конце слова
+/// зависит от контекста — такие токены отдаём самой to_lowercase.
+pub(crate) fn lowercase_into(token: &str, out: &mut String) {
+    out.clear();
+    if token.is_ascii() {
+        out.push_str(token);
+        out.make_ascii_lowercase();
+    } else if token.contains('Σ') {
+        out.push_str(&token.to_lowercase());
+    } else {
+        out.extend(token.chars().flat_map(char::to_lowercase));
+    }
+}
+
+/// Статистика по тексту, которой не важен порядок сообщений: слова, спам,
+/// длины, стиль, разметка, ссылки, команды. С --threads считается по частям.
+pub(crate) fn observe_text(
+    stats: &mut Stats,
+    stopwords: &Stopwords,
+    stem: bool,
+    msg: &Message,
+    msg_obj: &simd_json::borrowed::Object,
+    text_val: &BorrowedValue,
+) {
+    let name = msg.author();
+    let name = name.as_ref();
+    // слова по сегментам текста
+    update_word_stats(stats, stopwords, stem, name, text_val);
+    // спам по целому тексту
+    track_spam(stats, name, text_val);
+    stats.uniques.observe_links(msg_obj);
+    for metric in stats.text_metrics() {
+        metric.observe(msg);
+    }
+}
+
+// строим полный текст ТОЛЬКО для спама
+pub(crate) fn build_full_text(v: &BorrowedValue) -> String {
+    let mut out = String::new();
+    for_each_text_segment(v, |s| {
+        out.push_str(s);
+    });
+    out
+}
+
+/// Поиск повторяющихся сообщений (спама).
+#[derive(Clone, Debug)]
+pub struct SpamConfig {
+    /// Тексты короче (в символах) не считаются
+    pub min_chars: usize,
+    /// Со скольких одинаковых сообщений текст — повтор
+    pub min_repeats: usize,
+    /// Сколько авторов показывать
+    pub top: usize,
+    /// Печатать сами тексты (--spam-details)
+    pub details: bool,
+}
+
+cache::codec!(SpamConfig { min_chars, min_repeats, top, details });
+
+impl Default for SpamConfig {
+    fn default() -> Self {
+        Self { min_chars: 5, min_repeats: 2, top: TOP_SPAMMERS, details: false }
+    }
+}
+
+/// Текст для поиска повторов: без регистра и крайних пробелов;
+/// None — короче `min_chars`, чтобы повтор что-то значил.
+pub(crate) fn spam_key(text_val: &BorrowedValue, min_chars: usize) -> Option<String> {
+    let full = build_full_text(text_val);
+    let norm = full.trim().to_lowercase();
+    if norm.chars().count() < min_chars {
+        return None;
+    }
+    Some(norm)
+}
+
+pub(crate) fn track_spam(stats: &mut Stats, author: &str, text_val: &BorrowedValue) {
+    let Some(norm) = spam_key(text_val, stats.spam.min_chars) else {
+        return;
+    };
+
+    let entry = stats.spam_map.entry(author.to_string()).or_default();
+    *entry.entry(norm).or_insert(0) += 1;
+    approx::trim(entry, stats.approx);
+}
+
+//
+// ===================== ОБЩИЕ ВЫБОРКИ ДЛЯ ВСЕХ ФОРМАТОВ =====================
+//
+
+pub(crate) const TOP_WORDS: usize = 20;
+pub(crate) const TOP_SPAMMERS: usize = 10;
+pub(crate) const TOP_COPYPASTA: usize = 10;
+pub(crate) const COPYPASTA_QUOTE_CHARS: usize = 80;
+
+/// Дни недели в порядке Datelike::weekday (с понедельника).
+pub(crate) const WEEKDAYS: [&str; 7] = [
+    "понедельник",
+    "вторник",
+    "среда",
+    "четверг",
+    "пятница",
+    "суббота",
+    "воскресенье",
+];
+pub(crate) const WEEKDAYS_SHORT: [&str; 7] = ["пн", "вт", "ср", "чт", "пт", "сб", "вс"];
+
+/// Пары (ключ, счётчик) по убыванию счётчика.
+pub(crate) fn sorted_by_count(map: &AHashMap<String, usize>) -> Vec<(&str, usize)> {
+    let mut v: Vec<_> = map.iter().map(|(k, &c)| (k.as_str(), c)).collect();
+    v.sort_by_key(|b| std::cmp::Reverse(b.1));
+    v
+}
+
+/// Первые `n` пар по убыванию счётчика. Словарь слов бывает на миллионы
+/// записей, а показываем два десятка: select_nth_unstable отсекает хвост
+/// за линейное время, сортируются только оставшиеся `n`.
+pub(crate) fn top_by_count(map: &AHashMap<String, usize>, n: usize) -> Vec<(&str, usize)> {
+    let v: Vec<_> = map.iter().map(|(k, &c)| (k.as_str(), c)).collect();
+    top_n_by(v, n, |a, b| b.1.cmp(&a.1))
+}
+
+/// Первые `n` элементов в порядке `cmp` без полной сортировки.
+pub(crate) fn top_n_by<T>(
+    mut v: Vec<T>,
+    n: usize,
+    mut cmp: impl FnMut(&T, &T) -> std::cmp::Ordering,
+) -> Vec<T> {
+    if n == 0 {
+        v.clear();
+    } else if v.len() > n {
+        v.select_nth_unstable_by(n - 1, &mut cmp);
+        v.truncate(n);
+    }
+    v.sort_by(cmp);
+    v
+}
+
+/// Прибавить счётчики `from` к `into`.
+pub(crate) fn add_counts(into: &mut AHashMap<String, usize>, from: AHashMap<String, usize>) {
+    for (key, count) in from {
+        *into.entry(key).or_insert(0) += count;
+    }
+}
+
+pub(crate) fn percent_of(count: usize, total: usize) -> f64 {
+    if total > 0 {
+        (count as f64 / total as f64) * 100.0
+    } else {
+        0.0
+    }
+}
+
+/// Пометка к заголовкам топов, посчитанных с --approx.
+pub(crate) fn approx_note(stats: &Stats) -> &'static str {
+    if stats.approx.is_some() { ", --approx: не меньше указанного" } else { "" }
+}
+
+/// Автор -> число "лишних" повторов одинакового текста, по убыванию.
+pub(crate) fn spam_scores(stats: &Stats) -> Vec<(&str, usize)> {
+    let mut scores: Vec<(&str, usize)> = Vec::new();
+    for (author, msgs) in &stats.spam_map {
+        let mut extra = 0usize;
+        for &count in msgs.values() {
+            if count >= stats.spam.min_repeats {
+                extra += count - 1;
+            }
+        }
+        if extra > 0 {
+            scores.push((author.as_str(), extra));
+        }
+    }
+    scores.sort_by_key(|b| std::cmp::Reverse(b.1));
+    scores
+}
+
+/// Текст, который отправляли разные люди.
+pub(crate) struct Copypasta<'a> {
+    pub(crate) text: &'a str,
+    // сколько раз всего
+    pub(crate) count: usize,
+    pub(crate) authors: Vec<&'a str>,
+}
+
+/// Тексты от двух и больше авторов: по числу авторов, затем по числу раз.
+/// Собирается из того же spam_map, только в разрезе текста.
+pub(crate) fn copypasta(stats: &Stats) -> Vec<Copypasta<'_>> {
+    let mut by_text: AHashMap<&str, Copypasta> = AHashMap::new();
+    for (author, msgs) in &stats.spam_map {
+        for (text, &count) in msgs {
+            let c = by_text.entry(text.as_str()).or_insert_with(|| Copypasta {
+                text,
+                count: 0,
+                authors: Vec::new(),
+            });
+            c.count += count;
+            c.authors.push(author);
+        }
+    }
+    let mut v: Vec<Copypasta> = by_text.into_values().filter(|c| c.authors.len() > 1).collect();
+    for c in &mut v {
+        c.authors.sort_unstable();
+    }
+    v.sort_by(|a, b| {
+        b.authors
+            .len()
+            .cmp(&a.authors.len())
+            .then_with(|| b.count.cmp(&a.count))
+            .then_with(|| a.text.cmp(b.text))
+    });
+    v
+}
+
+/// Повторяющиеся тексты автора (текст, сколько раз), по убыванию.
+pub(crate) fn spam_repeats<'a>(stats: &'a Stats, author: &str) -> Vec<(&'a str, usize)> {
+    let Some(msgs) = stats.spam_map.get(author) else {
+        return Vec::new();
+    };
+    let mut v = sorted_by_count(msgs);
+    v.retain(|p| p.1 >= stats.spam.min_repeats);
+    v
+}
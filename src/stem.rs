@@ -0,0 +1,237 @@
+//
+// ===================== СТЕММИНГ =====================
+//
+// --stem: "сообщение/сообщения/сообщений" считаются одним словом.
+// Русский — алгоритм Snowball (Russian stemmer) без изменений.
+// Английский — облегчённый Porter2: только шаги 1a/1b (множественное число,
+// -ed, -ing), словообразовательные суффиксы не трогаем.
+// Язык определяется по буквам слова; прочее возвращается как есть.
+//
+
+/// Основа слова в нижнем регистре.
+pub fn stem(word: &str) -> String {
+    if word.chars().any(is_cyrillic) {
+        let w: Vec<char> = word.chars().map(|c| if c == 'ё' { 'е' } else { c }).collect();
+        russian(w).into_iter().collect()
+    } else if word.bytes().all(|b| b.is_ascii_lowercase() || b == b'\'') {
+        english(word)
+    } else {
+        word.to_string()
+    }
+}
+
+fn is_cyrillic(c: char) -> bool {
+    ('а'..='я').contains(&c) || c == 'ё'
+}
+
+//
+// ---------- русский (Snowball) ----------
+//
+
+fn ru_vowel(c: char) -> bool {
+    matches!(c, 'а' | 'е' | 'и' | 'о' | 'у' | 'ы' | 'э' | 'ю' | 'я')
+}
+
+const GERUND_1: &[&str] = &["в", "вши", "вшись"];
+const GERUND_2: &[&str] = &["ив", "ивши", "ившись", "ыв", "ывши", "ывшись"];
+const ADJECTIVE: &[&str] = &[
+    "ее", "ие", "ые", "ое", "ими", "ыми", "ей", "ий", "ый", "ой", "ем", "им", "ым", "ом",
+    "его", "ого", "ему", "ому", "их", "ых", "ую", "юю", "ая", "яя", "ою", "ею",
+];
+const PARTICIPLE_1: &[&str] = &["ем", "нн", "вш", "ющ", "щ"];
+const PARTICIPLE_2: &[&str] = &["ивш", "ывш", "ующ"];
+const REFLEXIVE: &[&str] = &["ся", "сь"];
+const VERB_1: &[&str] = &[
+    "ла", "на", "ете", "йте", "ли", "й", "л", "ем", "н", "ло", "но", "ет", "ют", "ны", "ть",
+    "ешь", "нно",
+];
+const VERB_2: &[&str] = &[
+    "ила", "ыла", "ена", "ейте", "уйте", "ите", "или", "ыли", "ей", "уй", "ил", "ыл", "им",
+    "ым", "ен", "ило", "ыло", "ено", "ят", "ует", "уют", "ит", "ыт", "ены", "ить", "ыть",
+    "ишь", "ую", "ю",
+];
+const NOUN: &[&str] = &[
+    "а", "ев", "ов", "ие", "ье", "е", "иями", "ями", "ами", "еи", "ии", "и", "ией", "ей", "ой",
+    "ий", "й", "иям", "ям", "ием", "ем", "ам", "ом", "о", "у", "ах", "иях", "ях", "ы", "ь",
+    "ию", "ью", "ю", "ия", "ья", "я",
+];
+const DERIVATIONAL: &[&str] = &["ост", "ость"];
+const SUPERLATIVE: &[&str] = &["ейш", "ейше"];
+
+fn ends_with(w: &[char], suffix: &str) -> bool {
+    let n = suffix.chars().count();
+    n <= w.len() && w[w.len() - n..].iter().copied().eq(suffix.chars())
+}
+
+/// Самое длинное окончание из `list`, целиком лежащее в w[from..]; его длина.
+fn longest(w: &[char], from: usize, list: &[&str]) -> Option<usize> {
+    list.iter()
+        .filter(|s| ends_with(w, s))
+        .map(|s| s.chars().count())
+        .filter(|&n| w.len() - n >= from)
+        .max()
+}
+
+/// Как в Snowball: выбирается самое длинное окончание из обеих групп; для
+/// первой группы перед ним должна стоять "а" или "я" (тоже внутри w[from..]).
+fn strip_grouped(w: &mut Vec<char>, from: usize, group1: &[&str], group2: &[&str]) -> bool {
+    let g1 = longest(w, from, group1);
+    let g2 = longest(w, from, group2);
+    let n = match (g1, g2) {
+        (Some(a), Some(b)) if b >= a => b,
+        (_, Some(b)) if g1.is_none() => b,
+        (Some(a), _) => {
+            let pos = w.len() - a;
+            if pos == 0 || pos - 1 < from || !matches!(w[pos - 1], 'а' | 'я') {
+                return false;
+            }
+            a
+        }
+        _ => return false,
+    };
+    w.truncate(w.len() - n);
+    true
+}
+
+fn strip(w: &mut Vec<char>, from: usize, list: &[&str]) -> bool {
+    match longest(w, from, list) {
+        Some(n) => {
+            w.truncate(w.len() - n);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Начало области после первой согласной, идущей за гласной (R1 от `from`).
+fn region_after(w: &[char], from: usize) -> usize {
+    (from + 1..w.len())
+        .find(|&i| !ru_vowel(w[i]) && ru_vowel(w[i - 1]))
+        .map_or(w.len(), |i| i + 1)
+}
+
+fn russian(mut w: Vec<char>) -> Vec<char> {
+    let Some(first_vowel) = w.iter().position(|&c| ru_vowel(c)) else {
+        return w;
+    };
+    let rv = first_vowel + 1;
+    let r1 = region_after(&w, 0);
+    let r2 = region_after(&w, r1);
+
+    // шаг 1
+    if !strip_grouped(&mut w, rv, GERUND_1, GERUND_2) {
+        strip(&mut w, rv, REFLEXIVE);
+        if strip(&mut w, rv, ADJECTIVE) {
+            strip_grouped(&mut w, rv, PARTICIPLE_1, PARTICIPLE_2);
+        } else if !strip_grouped(&mut w, rv, VERB_1, VERB_2) {
+            strip(&mut w, rv, NOUN);
+        }
+    }
+
+    // шаг 2
+    if w.len() > rv && w.last() == Some(&'и') {
+        w.pop();
+    }
+
+    // шаг 3
+    strip(&mut w, r2.max(rv), DERIVATIONAL);
+
+    // шаг 4
+    // "нн" -> "н" (в том числе после превосходной степени), иначе убираем "ь"
+    let superlative = strip(&mut w, rv, SUPERLATIVE);
+    let double_n = w.len() >= rv + 2 && ends_with(&w, "нн");
+    if double_n || (!superlative && w.len() > rv && w.last() == Some(&'ь')) {
+        w.pop();
+    }
+    w
+}
+
+//
+// ---------- английский (Porter2, шаги 1a/1b) ----------
+//
+
+fn en_vowel(b: u8) -> bool {
+    matches!(b, b'a' | b'e' | b'i' | b'o' | b'u' | b'y')
+}
+
+fn english(word: &str) -> String {
+    let mut w = word.trim_start_matches('\'').to_string();
+    for s in ["'s'", "'s", "'"] {
+        if let Some(rest) = w.strip_suffix(s) {
+            w.truncate(rest.len());
+            break;
+        }
+    }
+    if w.len() <= 2 {
+        return w;
+    }
+
+    // 1a: sses -> ss, ied/ies -> i/ie, s -> "" (если раньше есть гласная)
+    if w.ends_with("sses") {
+        w.truncate(w.len() - 2);
+    } else if w.ends_with("ied") || w.ends_with("ies") {
+        let keep = if w.len() > 4 { w.len() - 2 } else { w.len() - 1 };
+        w.truncate(keep);
+    } else if w.ends_with('s') && !w.ends_with("us") && !w.ends_with("ss") {
+        let b = w.as_bytes();
+        if b[..b.len() - 2].iter().any(|&c| en_vowel(c)) {
+            w.pop();
+        }
+    }
+
+    // 1b: eed/eedly -> ee (в R1), ed/edly/ing/ingly -> "" (если в основе есть гласная)
+    let r1 = en_r1(w.as_bytes());
+    for s in ["eedly", "eed"] {
+        if w.ends_with(s) {
+            if w.len() - s.len() >= r1 {
+                w.truncate(w.len() - s.len() + 2);
+            }
+            return w;
+        }
+    }
+    for s in ["ingly", "edly", "ing", "ed"] {
+        let Some(base) = w.strip_suffix(s) else {
+            continue;
+        };
+        if !base.bytes().any(en_vowel) {
+            break;
+        }
+        w.truncate(base.len());
+        let b = w.as_bytes();
+        let n = b.len();
+        if w.ends_with("at") || w.ends_with("bl") || w.ends_with("iz") {
+            w.push('e');
+        } else if n >= 2
+            && b[n - 1] == b[n - 2]
+            && matches!(b[n - 1], b'b' | b'd' | b'f' | b'g' | b'm' | b'n' | b'p' | b'r' | b't')
+        {
+            w.pop();
+        } else if en_short(b) && r1 >= n {
+            w.push('e');
+        }
+        break;
+    }
+    w
+}
+
+fn en_r1(b: &[u8]) -> usize {
+    (1..b.len())
+        .find(|&i| !en_vowel(b[i]) && en_vowel(b[i - 1]))
+        .map_or(b.len(), |i| i + 1)
+}
+
+/// Короткий слог в конце: согласная + гласная + согласная (не w, x, y) или
+/// гласная + согласная в начале слова.
+fn en_short(b: &[u8]) -> bool {
+    let n = b.len();
+    match n {
+        2 => en_vowel(b[0]) && !en_vowel(b[1]),
+        n if n >= 3 => {
+            !en_vowel(b[n - 3])
+                && en_vowel(b[n - 2])
+                && !en_vowel(b[n - 1])
+                && !matches!(b[n - 1], b'w' | b'x' | b'y')
+        }
+        _ => false,
+    }
+}
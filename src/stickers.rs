@@ -0,0 +1,87 @@
+//
+// ===================== СТИКЕРЫ =====================
+//
+// У стикера есть "sticker_emoji" и "file" (stickers/<имя>.webp|tgs|webm):
+// один и тот же стикер в экспорте — один и тот же файл, так что по пути
+// видно самые ходовые стикеры. Если медиа не выгружались, вместо пути —
+// "(File not included...)", такие по файлу не считаем.
+//
+
+use ahash::AHashMap;
+
+use std::io::{self, Write};
+
+use crate::model::{MediaType, Message};
+use crate::{Metric, top_by_count};
+
+const TOP_STICKERS: usize = 10;
+const TOP_STICKER_AUTHORS: usize = 10;
+const EMOJI_PER_AUTHOR: usize = 5;
+
+#[derive(Default)]
+pub struct StickerStats {
+    // эмодзи стикера -> сколько
+    pub by_emoji: AHashMap<String, usize>,
+    // автор -> (эмодзи -> сколько)
+    pub per_author: AHashMap<String, AHashMap<String, usize>>,
+    // путь к файлу стикера -> сколько
+    pub by_file: AHashMap<String, usize>,
+}
+
+crate::cache::codec!(StickerStats { by_emoji, per_author, by_file });
+
+impl Metric for StickerStats {
+    fn observe(&mut self, msg: &Message) {
+        if msg.media_type != Some(MediaType::Sticker) {
+            return;
+        }
+        let emoji = msg.sticker_emoji.unwrap_or("?");
+        *self.by_emoji.entry(emoji.to_string()).or_insert(0) += 1;
+        *self
+            .per_author
+            .entry(msg.author().into_owned())
+            .or_default()
+            .entry(emoji.to_string())
+            .or_insert(0) += 1;
+        if let Some(file) = msg.file
+            && !file.starts_with('(')
+        {
+            *self.by_file.entry(file.to_string()).or_insert(0) += 1;
+        }
+    }
+
+    fn report(&self, w: &mut dyn Write) -> io::Result<()> {
+        let total: usize = self.by_emoji.values().sum();
+        writeln!(w, "Стикеры: {}", total)?;
+        if total == 0 {
+            return Ok(());
+        }
+        writeln!(w, "  популярные эмодзи стикеров:")?;
+        for (emoji, count) in top_by_count(&self.by_emoji, TOP_STICKERS) {
+            writeln!(w, "  - {}: {}", emoji, count)?;
+        }
+
+        let mut authors: Vec<(&str, usize)> = self
+            .per_author
+            .iter()
+            .map(|(a, m)| (a.as_str(), m.values().sum::<usize>()))
+            .collect();
+        authors.sort_by_key(|p| std::cmp::Reverse(p.1));
+        writeln!(w, "  по авторам:")?;
+        for (author, count) in authors.into_iter().take(TOP_STICKER_AUTHORS) {
+            let top: Vec<String> = top_by_count(&self.per_author[author], EMOJI_PER_AUTHOR)
+                .into_iter()
+                .map(|(e, c)| format!("{e} ({c})"))
+                .collect();
+            writeln!(w, "  - {}: {} — {}", author, count, top.join(", "))?;
+        }
+
+        if !self.by_file.is_empty() {
+            writeln!(w, "  самые частые стикеры (по файлу):")?;
+            for (file, count) in top_by_count(&self.by_file, TOP_STICKERS) {
+                writeln!(w, "  - {}: {}", file, count)?;
+            }
+        }
+        Ok(())
+    }
+}
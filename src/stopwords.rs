@@ -0,0 +1,52 @@
+//
+// ===================== СТОП-СЛОВА =====================
+//
+// Без них топ слов — это "это", "как" и "the". Встроенные списки
+// (src/stopwords/*.txt) включены по умолчанию; --stopwords добавляет свой
+// файл, --no-stopwords отключает встроенные.
+//
+// Формат файла: по слову на строку, пустые строки и "# ..." пропускаются.
+//
+
+use ahash::AHashSet;
+
+use std::path::Path;
+
+const BUILTIN: [&str; 2] = [include_str!("stopwords/ru.txt"), include_str!("stopwords/en.txt")];
+
+#[derive(Default)]
+pub struct Stopwords {
+    words: AHashSet<String>,
+}
+
+impl Stopwords {
+    pub fn new(builtin: bool, file: Option<&Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut sw = Stopwords::default();
+        if builtin {
+            for list in BUILTIN {
+                sw.add_list(list);
+            }
+        }
+        if let Some(path) = file {
+            let list = std::fs::read_to_string(path).map_err(|e| {
+                format!("Не удалось прочитать стоп-слова из «{}»: {e}", path.display())
+            })?;
+            sw.add_list(&list);
+        }
+        Ok(sw)
+    }
+
+    fn add_list(&mut self, list: &str) {
+        for line in list.lines() {
+            let word = line.trim();
+            if !word.is_empty() && !word.starts_with('#') {
+                self.words.insert(word.to_lowercase());
+            }
+        }
+    }
+
+    /// `word` — уже в нижнем регистре.
+    pub fn contains(&self, word: &str) -> bool {
+        self.words.contains(word)
+    }
+}
@@ -0,0 +1,278 @@
+//
+// ===================== ПОТОКОВЫЙ РАЗБОР =====================
+//
+// Экспорт не читается целиком: идём по байтам через BufRead, вырезаем
+// очередной элемент массива "messages" в переиспользуемый буфер и парсим
+// только его. Память ограничена размером самого большого сообщения.
+//
+
+use simd_json::BorrowedValue;
+
+use std::error::Error;
+use std::io::{self, BufRead};
+
+type Res<T> = Result<T, Box<dyn Error>>;
+
+pub struct JsonStream<R: BufRead> {
+    r: R,
+}
+
+impl<R: BufRead> JsonStream<R> {
+    pub fn new(r: R) -> Self {
+        Self { r }
+    }
+
+    /// Следующий значимый байт (пробелы пропускаются), без потребления.
+    fn peek(&mut self) -> io::Result<Option<u8>> {
+        loop {
+            let buf = self.r.fill_buf()?;
+            if buf.is_empty() {
+                return Ok(None);
+            }
+            match buf.iter().position(|b| !is_json_ws(*b)) {
+                Some(pos) => {
+                    let b = buf[pos];
+                    self.r.consume(pos);
+                    return Ok(Some(b));
+                }
+                None => {
+                    let len = buf.len();
+                    self.r.consume(len);
+                }
+            }
+        }
+    }
+
+    /// Начало непрочитанных данных как есть — для проверки сигнатуры.
+    pub fn peek_raw(&mut self) -> io::Result<&[u8]> {
+        self.r.fill_buf()
+    }
+
+    fn expect(&mut self, want: u8) -> Res<()> {
+        match self.peek()? {
+            Some(b) if b == want => {
+                self.r.consume(1);
+                Ok(())
+            }
+            Some(b) => Err(format!(
+                "Ошибка потокового парсинга: ожидался '{}', встретился '{}'",
+                want as char, b as char
+            )
+            .into()),
+            None => Err("Ошибка потокового парсинга: неожиданный конец файла".into()),
+        }
+    }
+
+    /// Копирует сырые байты одного JSON-значения в `out`.
+    pub fn capture_value(&mut self, out: &mut Vec<u8>) -> Res<()> {
+        self.consume_value(Some(out))
+    }
+
+    /// Пропускает значение целиком, ничего не накапливая.
+    pub fn skip_value(&mut self) -> Res<()> {
+        self.consume_value(None)
+    }
+
+    fn consume_value(&mut self, mut out: Option<&mut Vec<u8>>) -> Res<()> {
+        match self.peek()? {
+            Some(b'"') => {
+                if let Some(o) = out.as_deref_mut() {
+                    o.push(b'"');
+                }
+                self.r.consume(1);
+                self.consume_nested(out, true)
+            }
+            Some(b'{') | Some(b'[') => self.consume_nested(out, false),
+            Some(_) => self.consume_scalar(out),
+            None => Err("Ошибка потокового парсинга: неожиданный конец файла".into()),
+        }
+    }
+
+    // объекты/массивы/строки: считаем глубину, строки с экранированием пропускаем
+    fn consume_nested(
+        &mut self,
+        mut out: Option<&mut Vec<u8>>,
+        mut in_str: bool,
+    ) -> Res<()> {
+        let mut depth = 0usize;
+        let mut esc = false;
+        loop {
+            let buf = self.r.fill_buf()?;
+            if buf.is_empty() {
+                return Err("Ошибка потокового парсинга: неожиданный конец файла".into());
+            }
+
+            let mut done = false;
+            let mut i = 0;
+            while i < buf.len() {
+                let b = buf[i];
+                i += 1;
+                if in_str {
+                    if esc {
+                        esc = false;
+                    } else if b == b'\\' {
+                        esc = true;
+                    } else if b == b'"' {
+                        in_str = false;
+                        if depth == 0 {
+                            done = true;
+                            break;
+                        }
+                    }
+                    continue;
+                }
+                match b {
+                    b'"' => in_str = true,
+                    b'{' | b'[' => depth += 1,
+                    b'}' | b']' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            done = true;
+                            break;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+
+            if let Some(o) = out.as_deref_mut() {
+                o.extend_from_slice(&buf[..i]);
+            }
+            self.r.consume(i);
+            if done {
+                return Ok(());
+            }
+        }
+    }
+
+    // числа, true/false/null: до разделителя
+    fn consume_scalar(&mut self, mut out: Option<&mut Vec<u8>>) -> Res<()> {
+        loop {
+            let buf = self.r.fill_buf()?;
+            if buf.is_empty() {
+                return Ok(());
+            }
+            let end = buf
+                .iter()
+                .position(|b| matches!(b, b',' | b'}' | b']') || is_json_ws(*b));
+            let n = end.unwrap_or(buf.len());
+            if let Some(o) = out.as_deref_mut() {
+                o.extend_from_slice(&buf[..n]);
+            }
+            self.r.consume(n);
+            if end.is_some() {
+                return Ok(());
+            }
+        }
+    }
+
+    fn read_key(&mut self, scratch: &mut Vec<u8>) -> Res<String> {
+        scratch.clear();
+        self.capture_value(scratch)?;
+        match simd_json::to_borrowed_value(scratch) {
+            Ok(BorrowedValue::String(s)) => Ok(s.into_owned()),
+            _ => Err("Ошибка потокового парсинга: ключ объекта не строка".into()),
+        }
+    }
+
+    /// Следующий ключ объекта после `{` (`first`) или после значения;
+    /// None — объект закончился. Значение ключа читает вызывающий.
+    pub fn next_key(&mut self, first: bool, scratch: &mut Vec<u8>) -> Res<Option<String>> {
+        match self.peek()? {
+            Some(b'}') => {
+                self.r.consume(1);
+                return Ok(None);
+            }
+            Some(b',') if !first => self.r.consume(1),
+            _ if !first => return Err("Ошибка потокового парсинга: ожидалась ',' или '}'".into()),
+            _ => {}
+        }
+        let key = self.read_key(scratch)?;
+        self.expect(b':')?;
+        Ok(Some(key))
+    }
+
+    /// Есть ли ещё элемент массива после `[` (`first`) или после элемента.
+    pub fn next_element(&mut self, first: bool) -> Res<bool> {
+        match self.peek()? {
+            Some(b']') => {
+                self.r.consume(1);
+                Ok(false)
+            }
+            Some(b',') if !first => {
+                self.r.consume(1);
+                Ok(true)
+            }
+            _ if !first => Err("Ошибка потокового парсинга: ожидалась ',' или ']'".into()),
+            _ => Ok(true),
+        }
+    }
+
+    pub fn begin_object(&mut self) -> Res<()> {
+        self.expect(b'{')
+    }
+
+    pub fn begin_array(&mut self) -> Res<()> {
+        self.expect(b'[')
+    }
+
+    /// Обходит объект: для каждого ключа зовёт `f`, который обязан
+    /// потребить значение (capture_value / вложенный обход).
+    pub fn walk_object<F>(&mut self, mut f: F) -> Res<()>
+    where
+        F: FnMut(&mut Self, &str) -> Res<()>,
+    {
+        let mut scratch = Vec::new();
+        self.begin_object()?;
+        let mut first = true;
+        while let Some(key) = self.next_key(first, &mut scratch)? {
+            f(self, &key)?;
+            first = false;
+        }
+        Ok(())
+    }
+
+    /// Обходит массив: для каждого элемента зовёт `f`, который обязан
+    /// потребить элемент сам (как в `walk_object`).
+    pub fn walk_array_raw<F>(&mut self, mut f: F) -> Res<()>
+    where
+        F: FnMut(&mut Self) -> Res<()>,
+    {
+        self.begin_array()?;
+        let mut first = true;
+        while self.next_element(first)? {
+            f(self)?;
+            first = false;
+        }
+        Ok(())
+    }
+
+    /// Обходит массив, отдавая каждый элемент уже разобранным.
+    pub fn walk_array<F>(&mut self, mut f: F) -> Res<()>
+    where
+        F: FnMut(&BorrowedValue) -> Res<()>,
+    {
+        let mut buf = Vec::new();
+        self.walk_array_raw(|s| {
+            buf.clear();
+            s.capture_value(&mut buf)?;
+            let val = simd_json::to_borrowed_value(&mut buf)
+                .map_err(|e| format!("Ошибка парсинга JSON: {e}"))?;
+            f(&val)
+        })
+    }
+
+    /// Разбирает одно (небольшое) значение целиком.
+    pub fn read_value(&mut self) -> Res<BorrowedValue<'static>> {
+        let mut buf = Vec::new();
+        self.capture_value(&mut buf)?;
+        let val = simd_json::to_borrowed_value(&mut buf)
+            .map_err(|e| format!("Ошибка парсинга JSON: {e}"))?;
+        Ok(val.into_static())
+    }
+}
+
+#[inline]
+fn is_json_ws(b: u8) -> bool {
+    matches!(b, b' ' | b'\n' | b'\t' | b'\r')
+}
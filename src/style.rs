@@ -0,0 +1,273 @@
+//
+// ===================== МАНЕРА ПИСАТЬ =====================
+//
+// Капс и восклицательные знаки: кто кричит громче всех.
+// Сообщение считается написанным капсом, если в нём не меньше
+// CAPS_MIN_LETTERS букв и из них не меньше CAPS_SHARE — заглавные.
+//
+// Профиль стиля: пунктуация на 100 символов, многоточия, эмодзи и
+// скобки-смайлики ")" на сообщение. ")))" — один смайлик; скобка,
+// закрывающая открытую "(", смайликом не считается.
+//
+
+use ahash::AHashMap;
+
+use std::io::{self, Write};
+
+use crate::model::Message;
+use crate::{Metric, for_each_text_segment, percent_of};
+
+const CAPS_MIN_LETTERS: usize = 5;
+const CAPS_SHARE: f64 = 0.7;
+const TOP_SHOUTERS: usize = 20;
+const TOP_STYLE_AUTHORS: usize = 20;
+
+#[derive(Default, Clone, Copy)]
+pub struct ShoutCounts {
+    pub messages: usize,
+    // сообщений капсом
+    pub caps: usize,
+    pub exclamations: usize,
+    pub questions: usize,
+}
+
+crate::cache::codec!(ShoutCounts { messages, caps, exclamations, questions });
+
+impl ShoutCounts {
+    fn add(&mut self, other: &ShoutCounts) {
+        self.messages += other.messages;
+        self.caps += other.caps;
+        self.exclamations += other.exclamations;
+        self.questions += other.questions;
+    }
+
+    pub fn caps_percent(&self) -> f64 {
+        percent_of(self.caps, self.messages)
+    }
+
+    pub fn per_message(&self, n: usize) -> f64 {
+        if self.messages == 0 { 0.0 } else { n as f64 / self.messages as f64 }
+    }
+}
+
+#[derive(Default)]
+pub struct ShoutStats {
+    pub total: ShoutCounts,
+    pub per_author: AHashMap<String, ShoutCounts>,
+}
+
+crate::cache::codec!(ShoutStats { total, per_author });
+
+impl ShoutStats {
+    /// Сложить счётчики капса и знаков — общие и каждого автора.
+    pub fn merge(&mut self, other: ShoutStats) {
+        self.total.add(&other.total);
+        for (author, c) in other.per_author {
+            self.per_author.entry(author).or_default().add(&c);
+        }
+    }
+
+    /// Авторы по доле капса, при равенстве — по «!» на сообщение.
+    pub fn shouters(&self) -> Vec<(&str, ShoutCounts)> {
+        let mut v: Vec<_> = self.per_author.iter().map(|(a, &c)| (a.as_str(), c)).collect();
+        v.sort_by(|a, b| {
+            b.1.caps_percent().total_cmp(&a.1.caps_percent()).then_with(|| {
+                b.1.per_message(b.1.exclamations).total_cmp(&a.1.per_message(a.1.exclamations))
+            })
+        });
+        v
+    }
+}
+
+impl Metric for ShoutStats {
+    fn observe(&mut self, msg: &Message) {
+        let Some(text_val) = msg.text_value() else {
+            return;
+        };
+        let author = msg.author();
+        let author = author.as_ref();
+        let (mut letters, mut upper, mut excl, mut quest) = (0usize, 0usize, 0usize, 0usize);
+        for_each_text_segment(text_val, |s| {
+            for c in s.chars() {
+                match c {
+                    '!' => excl += 1,
+                    '?' => quest += 1,
+                    c if c.is_alphabetic() => {
+                        letters += 1;
+                        if c.is_uppercase() {
+                            upper += 1;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        });
+        let caps = letters >= CAPS_MIN_LETTERS && upper as f64 >= letters as f64 * CAPS_SHARE;
+
+        let entry = match self.per_author.get_mut(author) {
+            Some(e) => e,
+            None => self.per_author.entry(author.to_string()).or_default(),
+        };
+        let m =
+            ShoutCounts { messages: 1, caps: caps as usize, exclamations: excl, questions: quest };
+        entry.add(&m);
+        self.total.add(&m);
+    }
+
+    fn report(&self, w: &mut dyn Write) -> io::Result<()> {
+        let t = &self.total;
+        writeln!(
+            w,
+            "Кто кричит громче всех: капсом {} сообщений ({:.1}%), «!» {}, «?» {}",
+            t.caps,
+            t.caps_percent(),
+            t.exclamations,
+            t.questions
+        )?;
+        writeln!(w, "  по участникам (капс / «!» на сообщение / «?» на сообщение):")?;
+        for (author, c) in self.shouters().into_iter().take(TOP_SHOUTERS) {
+            writeln!(
+                w,
+                "  - {}: {:.1}% / {:.2} / {:.2}",
+                author,
+                c.caps_percent(),
+                c.per_message(c.exclamations),
+                c.per_message(c.questions)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Основные блоки эмодзи; модификаторы тона кожи не считаются.
+fn is_emoji(c: char) -> bool {
+    matches!(c as u32, 0x1F300..=0x1F3FA | 0x1F400..=0x1FAFF | 0x2600..=0x27BF)
+}
+
+fn is_punctuation(c: char) -> bool {
+    c.is_ascii_punctuation() || matches!(c, '«' | '»' | '—' | '–' | '…' | '„' | '“' | '”')
+}
+
+#[derive(Default, Clone, Copy)]
+pub struct StyleCounts {
+    pub messages: usize,
+    pub chars: usize,
+    pub punctuation: usize,
+    pub ellipses: usize,
+    pub emoji: usize,
+    pub smileys: usize,
+}
+
+crate::cache::codec!(StyleCounts { messages, chars, punctuation, ellipses, emoji, smileys });
+
+impl StyleCounts {
+    fn add(&mut self, other: &StyleCounts) {
+        self.messages += other.messages;
+        self.chars += other.chars;
+        self.punctuation += other.punctuation;
+        self.ellipses += other.ellipses;
+        self.emoji += other.emoji;
+        self.smileys += other.smileys;
+    }
+
+    pub fn punctuation_per_100(&self) -> f64 {
+        percent_of(self.punctuation, self.chars)
+    }
+
+    pub fn per_message(&self, n: usize) -> f64 {
+        if self.messages == 0 { 0.0 } else { n as f64 / self.messages as f64 }
+    }
+}
+
+/// Профиль стиля по авторам.
+#[derive(Default)]
+pub struct StyleStats {
+    pub per_author: AHashMap<String, StyleCounts>,
+}
+
+crate::cache::codec!(StyleStats { per_author });
+
+impl StyleStats {
+    /// Сложить счётчики стиля каждого автора.
+    pub fn merge(&mut self, other: StyleStats) {
+        for (author, c) in other.per_author {
+            self.per_author.entry(author).or_default().add(&c);
+        }
+    }
+
+    /// Авторы по числу сообщений с текстом.
+    pub fn authors(&self) -> Vec<(&str, StyleCounts)> {
+        let mut v: Vec<_> = self.per_author.iter().map(|(a, &c)| (a.as_str(), c)).collect();
+        v.sort_by_key(|p| std::cmp::Reverse(p.1.messages));
+        v
+    }
+}
+
+impl Metric for StyleStats {
+    fn observe(&mut self, msg: &Message) {
+        let Some(text_val) = msg.text_value() else {
+            return;
+        };
+        let author = msg.author();
+        let author = author.as_ref();
+        let mut m = StyleCounts::default();
+        let mut dots = 0usize;
+        let mut open_parens = 0usize;
+        let mut in_smiley = false;
+        for_each_text_segment(text_val, |s| {
+            for c in s.chars() {
+                m.chars += 1;
+                if is_punctuation(c) {
+                    m.punctuation += 1;
+                }
+                if is_emoji(c) {
+                    m.emoji += 1;
+                }
+                // ".." и "..." — одно многоточие
+                dots = if c == '.' { dots + 1 } else { 0 };
+                if c == '…' || dots == 2 {
+                    m.ellipses += 1;
+                }
+                match c {
+                    '(' => open_parens += 1,
+                    ')' if open_parens > 0 => open_parens -= 1,
+                    ')' => {
+                        if !in_smiley {
+                            m.smileys += 1;
+                        }
+                        in_smiley = true;
+                        continue;
+                    }
+                    _ => {}
+                }
+                in_smiley = false;
+            }
+        });
+
+        let entry = match self.per_author.get_mut(author) {
+            Some(e) => e,
+            None => self.per_author.entry(author.to_string()).or_default(),
+        };
+        m.messages = 1;
+        entry.add(&m);
+    }
+
+    fn report(&self, w: &mut dyn Write) -> io::Result<()> {
+        writeln!(
+            w,
+            "Стиль письма (пунктуация на 100 символов / на сообщение: многоточий, эмодзи, «)»):"
+        )?;
+        for (author, c) in self.authors().into_iter().take(TOP_STYLE_AUTHORS) {
+            writeln!(
+                w,
+                "- {}: {:.1} / {:.2}, {:.2}, {:.2}",
+                author,
+                c.punctuation_per_100(),
+                c.per_message(c.ellipses),
+                c.per_message(c.emoji),
+                c.per_message(c.smileys)
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,185 @@
+//
+// ===================== ТАБЛИЦЫ В ТЕРМИНАЛЕ =====================
+//
+// Когда статистика печатается прямо в терминал, участники и топ слов
+// выводятся выровненными таблицами, а гистограммы активности — полосами
+// во всю ширину окна; с цветом, если он не выключен (--no-color или
+// переменная NO_COLOR). В файл и через | — прежние строки "- имя: число"
+// и "час: число": их удобно грепать и сравнивать.
+//
+
+use std::io::{self, Write};
+
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const CYAN: &str = "\x1b[36m";
+const RESET: &str = "\x1b[0m";
+const GREEN: &str = "\x1b[32m";
+
+// доли полного блока: полоса растёт с точностью до 1/8 символа
+const EIGHTHS: [&str; 8] = ["", "▏", "▎", "▍", "▌", "▋", "▊", "▉"];
+const DEFAULT_WIDTH: usize = 80;
+const MIN_BAR: usize = 10;
+
+/// Куда идёт текстовая статистика; по умолчанию — не в терминал.
+#[derive(Clone, Copy, Default)]
+pub struct Term {
+    pub tables: bool,
+    pub color: bool,
+    // ширина окна в символах
+    pub width: usize,
+}
+
+impl Term {
+    /// `tty` — вывод идёт в терминал.
+    pub fn new(tty: bool, no_color: bool) -> Term {
+        let no_color_env = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        Term {
+            tables: tty,
+            color: tty && !no_color && !no_color_env,
+            width: if tty { terminal_width() } else { DEFAULT_WIDTH },
+        }
+    }
+}
+
+/// COLUMNS, если задана, иначе размер окна stdout/stderr, иначе 80.
+fn terminal_width() -> usize {
+    if let Some(n) = std::env::var("COLUMNS").ok().and_then(|c| c.parse().ok())
+        && n > 0
+    {
+        return n;
+    }
+    window_width().unwrap_or(DEFAULT_WIDTH)
+}
+
+#[cfg(unix)]
+fn window_width() -> Option<usize> {
+    for fd in [libc::STDOUT_FILENO, libc::STDERR_FILENO] {
+        let mut ws = libc::winsize { ws_row: 0, ws_col: 0, ws_xpixel: 0, ws_ypixel: 0 };
+        // SAFETY: TIOCGWINSZ только заполняет переданную структуру
+        let ok = unsafe { libc::ioctl(fd, libc::TIOCGWINSZ, &mut ws) } == 0;
+        if ok && ws.ws_col > 0 {
+            return Some(ws.ws_col as usize);
+        }
+    }
+    None
+}
+
+#[cfg(not(unix))]
+fn window_width() -> Option<usize> {
+    None
+}
+
+/// Гистограмма полосами: подпись, полоса в масштабе максимума, число.
+pub fn write_bars<W: Write>(w: &mut W, rows: &[(String, usize)], term: Term) -> io::Result<()> {
+    let max = rows.iter().map(|r| r.1).max().unwrap_or(0);
+    let label_width = rows.iter().map(|r| display_width(&r.0)).max().unwrap_or(0);
+    let count_width = max.to_string().len();
+    // "  подпись  полоса  число"
+    let room = term.width.saturating_sub(label_width + count_width + 6).max(MIN_BAR);
+    for (label, count) in rows {
+        let eighths = (count * room * 8).checked_div(max).unwrap_or(0);
+        let mut bar = "█".repeat(eighths / 8);
+        bar.push_str(EIGHTHS[eighths % 8]);
+        let pad = " ".repeat(room - display_width(&bar));
+        let label_pad = " ".repeat(label_width - display_width(label));
+        let bar = if term.color { format!("{GREEN}{bar}{RESET}") } else { bar };
+        writeln!(w, "  {label}{label_pad}  {bar}{pad}  {count:>count_width$}")?;
+    }
+    Ok(())
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Right,
+}
+
+pub struct Table {
+    columns: Vec<(&'static str, Align)>,
+    rows: Vec<Vec<String>>,
+}
+
+/// Ширина в колонках терминала: эмодзи и CJK — две, диакритика и
+/// селекторы вариантов — ноль.
+fn display_width(s: &str) -> usize {
+    s.chars()
+        .map(|c| match c as u32 {
+            0x0300..=0x036F | 0x200B..=0x200F | 0xFE00..=0xFE0F => 0,
+            0x1100..=0x115F
+            | 0x2E80..=0xA4CF
+            | 0xAC00..=0xD7A3
+            | 0xF900..=0xFAFF
+            | 0xFE30..=0xFE4F
+            | 0xFF00..=0xFF60
+            | 0xFFE0..=0xFFE6
+            | 0x1F300..=0x1FAFF
+            | 0x20000..=0x3FFFD => 2,
+            _ => 1,
+        })
+        .sum()
+}
+
+impl Table {
+    pub fn new(columns: &[(&'static str, Align)]) -> Self {
+        Table { columns: columns.to_vec(), rows: Vec::new() }
+    }
+
+    pub fn row(&mut self, cells: Vec<String>) {
+        self.rows.push(cells);
+    }
+
+    pub fn write<W: Write>(&self, w: &mut W, term: Term) -> io::Result<()> {
+        let widths: Vec<usize> = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(i, (title, _))| {
+                let cells = self.rows.iter().map(|r| display_width(&r[i]));
+                cells.fold(display_width(title), usize::max)
+            })
+            .collect();
+        let paint = |style: &str, text: &str| {
+            if term.color { format!("{style}{text}{RESET}") } else { text.to_string() }
+        };
+
+        let header: Vec<String> = self.columns.iter().map(|(t, _)| t.to_string()).collect();
+        self.write_line(w, &header, &widths, |_, cell| paint(BOLD, cell))?;
+        let rule: Vec<String> = widths.iter().map(|&n| "─".repeat(n)).collect();
+        self.write_line(w, &rule, &widths, |_, cell| paint(DIM, cell))?;
+        for row in &self.rows {
+            self.write_line(w, row, &widths, |i, cell| {
+                if i == 0 { paint(CYAN, cell) } else { cell.to_string() }
+            })?;
+        }
+        Ok(())
+    }
+
+    // цвет накладывается после выравнивания: escape-коды не занимают места
+    fn write_line<W: Write>(
+        &self,
+        w: &mut W,
+        cells: &[String],
+        widths: &[usize],
+        style: impl Fn(usize, &str) -> String,
+    ) -> io::Result<()> {
+        let mut line = String::new();
+        for (i, cell) in cells.iter().enumerate() {
+            let pad = " ".repeat(widths[i].saturating_sub(display_width(cell)));
+            line.push_str("  ");
+            match self.columns[i].1 {
+                Align::Left => {
+                    line += &style(i, cell);
+                    if i + 1 < cells.len() {
+                        line += &pad;
+                    }
+                }
+                Align::Right => {
+                    line += &pad;
+                    line += &style(i, cell);
+                }
+            }
+        }
+        writeln!(w, "{line}")
+    }
+}
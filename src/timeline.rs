@@ -0,0 +1,154 @@
+//
+// ===================== ЛЕНТА АКТИВНОСТИ =====================
+//
+// Сообщения по календарным дням; месяцы и годы собираются из дней при
+// выводе. Дней в истории даже за десять лет — несколько тысяч, так что
+// хранить их дёшево, а по ним же считаются и серии активных дней.
+//
+// Серия — дни подряд, в каждый из которых было хоть одно сообщение.
+// "Текущая" серия — та, что заканчивается последним днём истории.
+// Тишина — сколько полных дней подряд не было ни одного сообщения.
+//
+
+use chrono::{Datelike, NaiveDate};
+
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+const BAR_WIDTH: usize = 40;
+
+/// Полоска из "█" длиной, пропорциональной count / max.
+pub fn text_bar(count: usize, max: usize) -> String {
+    let len = (count * BAR_WIDTH).div_ceil(max.max(1));
+    "█".repeat(len)
+}
+
+/// Отрезок дат [from, to] длиной `days` дней.
+#[derive(Clone, Copy)]
+pub struct DayRange {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub days: i64,
+}
+
+pub struct Streaks {
+    pub longest: DayRange,
+    pub current: DayRange,
+    // None — тишины не было (или всего один активный день)
+    pub silence: Option<DayRange>,
+}
+
+#[derive(Default)]
+pub struct Timeline {
+    pub days: BTreeMap<NaiveDate, usize>,
+}
+
+crate::cache::codec!(Timeline { days });
+
+impl Timeline {
+    pub fn observe(&mut self, day: NaiveDate) {
+        *self.days.entry(day).or_insert(0) += 1;
+    }
+
+    /// "ГГГГ-ММ" -> сообщений, по порядку; месяцы без сообщений — с нулём.
+    pub fn months(&self) -> Vec<(String, usize)> {
+        let mut months: BTreeMap<(i32, u32), usize> = BTreeMap::new();
+        for (d, &n) in &self.days {
+            *months.entry((d.year(), d.month())).or_insert(0) += n;
+        }
+        let (Some(&first), Some(&last)) = (months.keys().next(), months.keys().next_back())
+        else {
+            return Vec::new();
+        };
+        let mut out = Vec::new();
+        let (mut y, mut m) = first;
+        while (y, m) <= last {
+            out.push((format!("{y}-{m:02}"), months.get(&(y, m)).copied().unwrap_or(0)));
+            (y, m) = if m == 12 { (y + 1, 1) } else { (y, m + 1) };
+        }
+        out
+    }
+
+    /// Год -> сообщений, без пропусков между первым и последним годом.
+    pub fn years(&self) -> Vec<(i32, usize)> {
+        let mut years: BTreeMap<i32, usize> = BTreeMap::new();
+        for (d, &n) in &self.days {
+            *years.entry(d.year()).or_insert(0) += n;
+        }
+        let (Some(&first), Some(&last)) = (years.keys().next(), years.keys().next_back()) else {
+            return Vec::new();
+        };
+        (first..=last)
+            .map(|y| (y, years.get(&y).copied().unwrap_or(0)))
+            .collect()
+    }
+
+    pub fn streaks(&self) -> Option<Streaks> {
+        let mut days = self.days.keys().copied();
+        let first = days.next()?;
+        let mut run = DayRange { from: first, to: first, days: 1 };
+        let mut longest = run;
+        let mut silence: Option<DayRange> = None;
+        for day in days {
+            let gap = (day - run.to).num_days() - 1;
+            if gap == 0 {
+                run.to = day;
+                run.days += 1;
+            } else {
+                if silence.is_none_or(|s| gap > s.days) {
+                    silence = Some(DayRange {
+                        from: run.to.succ_opt().unwrap_or(run.to),
+                        to: day.pred_opt().unwrap_or(day),
+                        days: gap,
+                    });
+                }
+                run = DayRange { from: day, to: day, days: 1 };
+            }
+            if run.days > longest.days {
+                longest = run;
+            }
+        }
+        Some(Streaks { longest, current: run, silence })
+    }
+
+    pub fn write_streaks<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let Some(s) = self.streaks() else {
+            return Ok(());
+        };
+        writeln!(w, "Серии активных дней ({} дней с сообщениями):", self.days.len())?;
+        let range = |r: DayRange| format!("{} дн., {} — {}", r.days, r.from, r.to);
+        writeln!(w, "  самая длинная: {}", range(s.longest))?;
+        writeln!(w, "  последняя (к концу истории): {}", range(s.current))?;
+        match s.silence {
+            Some(r) => writeln!(w, "  самая долгая тишина: {}", range(r))?,
+            None => writeln!(w, "  дней без сообщений не было")?,
+        }
+        Ok(())
+    }
+
+    pub fn write_text<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let years = self.years();
+        writeln!(w, "Активность по годам:")?;
+        let max = years.iter().map(|p| p.1).max().unwrap_or(0);
+        let mut prev: Option<usize> = None;
+        for &(year, n) in &years {
+            let change = match prev {
+                Some(p) if p > 0 => {
+                    format!(" ({:+.0}% к прошлому году)", (n as f64 / p as f64 - 1.0) * 100.0)
+                }
+                _ => String::new(),
+            };
+            writeln!(w, "  {} {} {}{}", year, text_bar(n, max), n, change)?;
+            prev = Some(n);
+        }
+
+        writeln!(w)?;
+        writeln!(w, "Активность по месяцам:")?;
+        let months = self.months();
+        let max = months.iter().map(|p| p.1).max().unwrap_or(0);
+        for (month, n) in months {
+            writeln!(w, "  {} {} {}", month, text_bar(n, max), n)?;
+        }
+        Ok(())
+    }
+}
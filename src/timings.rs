@@ -0,0 +1,64 @@
+//
+// ===================== ВРЕМЯ ПО ЭТАПАМ (--timings) =====================
+//
+// Общее время обработки не говорит, куда оно ушло. Этапы меряются там,
+// где они идут целиком: чтение входа, разбор JSON, цикл по сообщениям
+// (вместе с записью лога — она идёт по ходу), сводка статистики и запись
+// остальных выходов. С --streaming чтение и разбор идут вперемешку с
+// обработкой, поэтому там они — одна строка: всё, кроме обработки.
+// С mmap страницы файла подгружаются при разборе, и чтение почти нулевое.
+//
+
+use std::time::{Duration, Instant};
+
+#[derive(Default)]
+pub struct Timings {
+    pub read: Duration,
+    pub parse: Duration,
+    // --streaming: чтение и разбор вместе (read и parse тогда пусты)
+    pub read_parse: Duration,
+    pub process: Duration,
+    pub aggregate: Duration,
+    pub output: Duration,
+    // сообщений прошло через обработку (до фильтров)
+    pub messages: usize,
+}
+
+/// Выполнить `f`, прибавив её время к `phase`.
+pub fn timed<T>(phase: &mut Duration, f: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    let result = f();
+    *phase += start.elapsed();
+    result
+}
+
+fn ms(d: Duration) -> f64 {
+    d.as_secs_f64() * 1000.0
+}
+
+impl Timings {
+    /// Таблица этапов; `total` — всё время обработки.
+    pub fn report(&self, total: Duration) -> String {
+        let mut phases = if self.read_parse.is_zero() {
+            vec![("чтение входа", self.read), ("разбор JSON", self.parse)]
+        } else {
+            vec![("чтение и разбор (поток)", self.read_parse)]
+        };
+        phases.extend([
+            ("обработка сообщений", self.process),
+            ("сводка статистики", self.aggregate),
+            ("запись выходов", self.output),
+        ]);
+        let measured: Duration = phases.iter().map(|p| p.1).sum();
+        let mut out = String::from("Время по этапам:\n");
+        for (name, d) in phases {
+            let share = crate::percent_of(d.as_micros() as usize, total.as_micros() as usize);
+            out += &format!("  {:<24} {:>10.1} мс {:>5.1}%\n", name, ms(d), share);
+        }
+        out += &format!("  {:<24} {:>10.1} мс\n", "прочее", ms(total.saturating_sub(measured)));
+        let secs = total.as_secs_f64();
+        let rate = if secs > 0.0 { self.messages as f64 / secs } else { 0.0 };
+        out += &format!("  сообщений: {}, ~{:.0} в секунду", self.messages, rate);
+        out
+    }
+}
@@ -0,0 +1,576 @@
+//
+// ===================== ПРОСМОТР В ТЕРМИНАЛЕ (tgjsps tui) =====================
+//
+// Переписка на весь экран вместо chat.txt: листать, отбирать по автору и
+// датам, искать; внизу по s — статистика по тому, что сейчас отобрано.
+// Без сторонних библиотек: raw-режим терминала через termios, вывод —
+// escape-последовательностями ANSI. Только для Unix.
+//
+// Клавиши: ↑/↓ или k/j — строка, PgUp/PgDn или b/пробел — страница,
+// g/G — начало/конец, / — поиск, n/N — следующее/предыдущее совпадение,
+// a — автор (часть имени), d — даты («2023-01-01..2023-03-31», любую
+// сторону можно опустить), s — статистика, q — выход.
+//
+
+use ahash::AHashMap;
+use chrono::NaiveDateTime;
+use chrono_tz::Tz;
+use simd_json::BorrowedValue;
+
+use std::error::Error;
+
+use crate::model::{Message, MessageKind};
+use crate::{
+    NO_CHAT_NAME, discover_export, filter, for_each_dom_chat, get_str_field, log_template,
+    read_input, top_by_count,
+};
+
+// строк под статистику внизу экрана (без разделителя)
+const STATS_ROWS: usize = 8;
+const STATS_AUTHORS: usize = STATS_ROWS - 2;
+
+#[derive(clap::Args, Debug)]
+pub struct TuiArgs {
+    /// Экспорт: JSON, архив или папка
+    #[arg(short = 'i', long = "input", value_name = "INPUT", default_value = "result.json")]
+    input: String,
+
+    /// Часовой пояс для дат (например, Europe/Berlin); без него — как в экспорте
+    #[arg(long = "timezone", value_name = "TZ")]
+    timezone: Option<Tz>,
+}
+
+struct Entry {
+    chat: usize,
+    date: Option<NaiveDateTime>,
+    author: String,
+    // текст одной строкой; без текста — вложение, как в логе
+    text: String,
+    // для поиска без учёта регистра
+    lower: String,
+}
+
+fn load(args: &TuiArgs) -> Result<(Vec<String>, Vec<Entry>), Box<dyn Error>> {
+    let path = match discover_export(&args.input)? {
+        Some(dir) => dir.join("result.json").to_string_lossy().into_owned(),
+        None => args.input.clone(),
+    };
+    let mut buf = read_input(&path)?;
+    let root = simd_json::to_borrowed_value(&mut buf)
+        .map_err(|e| format!("Ошибка парсинга JSON в {}: {e}", args.input))?;
+    let BorrowedValue::Object(root_obj) = &root else {
+        return Err(format!("{}: корень JSON не объект", args.input).into());
+    };
+
+    let mut chats = Vec::new();
+    let mut entries = Vec::new();
+    for_each_dom_chat(root_obj, |chat_obj| {
+        let chat = chats.len();
+        chats.push(get_str_field(chat_obj, "name").unwrap_or(NO_CHAT_NAME).to_string());
+        let Some(BorrowedValue::Array(list)) = chat_obj.get("messages") else {
+            return;
+        };
+        for msg_val in list.iter() {
+            let Ok(msg) = Message::from_value(msg_val) else {
+                continue;
+            };
+            if msg.kind != MessageKind::Message {
+                continue;
+            }
+            let mut text = msg.plain_text().replace(['\n', '\r', '\t'], " ");
+            if text.is_empty()
+                && let Some(kind) = msg.media_kind()
+            {
+                text = log_template::media_placeholder(kind);
+            }
+            entries.push(Entry {
+                chat,
+                date: msg.date(args.timezone),
+                author: msg.author().into_owned(),
+                lower: text.to_lowercase(),
+                text,
+            });
+        }
+    });
+    if chats.is_empty() {
+        let input = &args.input;
+        return Err(format!("{input}: в корне нет ни \"messages\", ни \"chats.list\"").into());
+    }
+    Ok((chats, entries))
+}
+
+//
+// ===================== СОСТОЯНИЕ ЭКРАНА =====================
+//
+
+#[derive(Default)]
+struct Filters {
+    // часть имени, в нижнем регистре
+    author: Option<String>,
+    since: Option<NaiveDateTime>,
+    until: Option<NaiveDateTime>,
+}
+
+impl Filters {
+    fn accepts(&self, e: &Entry) -> bool {
+        if let Some(a) = &self.author
+            && !e.author.to_lowercase().contains(a.as_str())
+        {
+            return false;
+        }
+        match e.date {
+            Some(d) => self.since.is_none_or(|s| d >= s) && self.until.is_none_or(|u| d <= u),
+            None => self.since.is_none() && self.until.is_none(),
+        }
+    }
+
+    fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(a) = &self.author {
+            parts.push(format!("автор ~ {a}"));
+        }
+        let fmt = |d: Option<NaiveDateTime>| d.map(|d| d.format("%Y-%m-%d").to_string());
+        if self.since.is_some() || self.until.is_some() {
+            let (s, u) = (fmt(self.since).unwrap_or_default(), fmt(self.until).unwrap_or_default());
+            parts.push(format!("даты {s}..{u}"));
+        }
+        parts.join(", ")
+    }
+}
+
+/// «2023-01-01..2023-03-31», «2023-01-01..», «..2023-03-31» или один день.
+fn parse_range(s: &str) -> Result<(Option<NaiveDateTime>, Option<NaiveDateTime>), String> {
+    let (from, to) = s.split_once("..").unwrap_or((s, s));
+    let bound = |v: &str, parse: fn(&str) -> Result<NaiveDateTime, String>| {
+        let v = v.trim();
+        if v.is_empty() { Ok(None) } else { parse(v).map(Some) }
+    };
+    Ok((bound(from, filter::parse_since)?, bound(to, filter::parse_until)?))
+}
+
+struct View {
+    chats: Vec<String>,
+    entries: Vec<Entry>,
+    filters: Filters,
+    // индексы entries, прошедших фильтры
+    shown: Vec<usize>,
+    // первая видимая строка, индекс в shown
+    top: usize,
+    search: Option<String>,
+    stats: bool,
+    status: String,
+}
+
+impl View {
+    fn new(chats: Vec<String>, entries: Vec<Entry>) -> Self {
+        let shown = (0..entries.len()).collect();
+        View {
+            chats,
+            entries,
+            filters: Filters::default(),
+            shown,
+            top: 0,
+            search: None,
+            stats: false,
+            status: String::new(),
+        }
+    }
+
+    /// Пересчитать отобранное; верхней остаётся та же (или следующая) запись.
+    fn refilter(&mut self) {
+        let anchor = self.shown.get(self.top).copied().unwrap_or(0);
+        self.shown = (0..self.entries.len())
+            .filter(|&i| self.filters.accepts(&self.entries[i]))
+            .collect();
+        self.top = self.shown.partition_point(|&i| i < anchor);
+        if self.shown.is_empty() {
+            self.status = "Ничего не подходит под фильтры".into();
+        }
+    }
+
+    fn scroll(&mut self, delta: isize, page: usize) {
+        let max_top = self.shown.len().saturating_sub(page);
+        self.top = self.top.saturating_add_signed(delta).min(max_top);
+    }
+
+    fn matches(&self, pos: usize) -> bool {
+        let Some(q) = &self.search else {
+            return false;
+        };
+        self.entries[self.shown[pos]].lower.contains(q.as_str())
+    }
+
+    /// Следующее (или предыдущее) совпадение с поиском относительно верха.
+    fn find(&mut self, forward: bool) {
+        if self.search.is_none() {
+            self.status = "Поиск не задан: /".into();
+            return;
+        }
+        let found = if forward {
+            (self.top + 1..self.shown.len()).find(|&p| self.matches(p))
+        } else {
+            (0..self.top).rev().find(|&p| self.matches(p))
+        };
+        match found {
+            Some(p) => self.top = p,
+            None => self.status = "Больше не найдено".into(),
+        }
+    }
+
+    fn stats_lines(&self) -> Vec<String> {
+        let mut per_author: AHashMap<String, usize> = AHashMap::new();
+        let (mut first, mut last) = (None, None);
+        for &i in &self.shown {
+            let e = &self.entries[i];
+            *per_author.entry(e.author.clone()).or_insert(0) += 1;
+            if let Some(d) = e.date {
+                first = Some(first.map_or(d, |f: NaiveDateTime| f.min(d)));
+                last = Some(last.map_or(d, |l: NaiveDateTime| l.max(d)));
+            }
+        }
+        let fmt = |d: Option<NaiveDateTime>| {
+            d.map(|d| d.format("%Y-%m-%d").to_string()).unwrap_or_else(|| "?".into())
+        };
+        let mut lines = vec![format!(
+            "Отобрано {} из {} сообщений, {} — {}, авторов: {}",
+            self.shown.len(),
+            self.entries.len(),
+            fmt(first),
+            fmt(last),
+            per_author.len()
+        )];
+        let top = top_by_count(&per_author, STATS_AUTHORS);
+        let max = top.first().map_or(1, |t| t.1.max(1));
+        for (author, n) in top {
+            let bar = "█".repeat((n * 30).div_ceil(max));
+            let percent = n as f64 * 100.0 / self.shown.len().max(1) as f64;
+            lines.push(format!("  {author:<24.24} {n:>7} {percent:>5.1}% {bar}"));
+        }
+        lines
+    }
+}
+
+pub fn run(args: &TuiArgs) -> Result<(), Box<dyn Error>> {
+    let (chats, entries) = load(args)?;
+    if entries.is_empty() {
+        return Err(format!("{}: сообщений нет", args.input).into());
+    }
+    term::run(View::new(chats, entries))
+}
+
+//
+// ===================== ТЕРМИНАЛ =====================
+//
+
+#[cfg(not(unix))]
+mod term {
+    use super::View;
+
+    pub fn run(_: View) -> Result<(), Box<dyn std::error::Error>> {
+        Err("tgjsps tui работает только в терминале Unix".into())
+    }
+}
+
+#[cfg(unix)]
+mod term {
+    use super::{STATS_ROWS, View, parse_range};
+
+    use std::io::{self, IsTerminal, Write};
+    use std::panic;
+    use std::sync::Mutex;
+
+    /// raw-режим и второй экран; всё возвращается в Drop, даже при ошибке.
+    struct Screen;
+
+    // настройки терминала до enter; None — уже возвращены
+    static SAVED: Mutex<Option<libc::termios>> = Mutex::new(None);
+
+    /// Вернуть терминал как было. Зовётся и из Drop, и из хука паники:
+    /// в релизной сборке panic = "abort", и Drop при панике не выполняется.
+    fn restore() {
+        let Some(saved) = SAVED.lock().unwrap_or_else(|e| e.into_inner()).take() else {
+            return;
+        };
+        // мимо буфера stdout: при панике он может быть недописан
+        let reset = b"\x1b[?7h\x1b[?25h\x1b[?1049l";
+        // SAFETY: пишем свой буфер в открытый дескриптор и возвращаем
+        // настройки, сохранённые в enter
+        unsafe {
+            libc::write(libc::STDOUT_FILENO, reset.as_ptr().cast(), reset.len());
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &saved);
+        }
+    }
+
+    impl Screen {
+        fn enter() -> io::Result<Screen> {
+            // SAFETY: termios — простая структура, tcgetattr её заполняет
+            let mut saved: libc::termios = unsafe { std::mem::zeroed() };
+            if unsafe { libc::tcgetattr(libc::STDIN_FILENO, &mut saved) } != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let mut raw = saved;
+            raw.c_lflag &= !(libc::ICANON | libc::ECHO | libc::ISIG);
+            raw.c_cc[libc::VMIN] = 1;
+            raw.c_cc[libc::VTIME] = 0;
+            // SAFETY: передаём структуру, полученную от tcgetattr
+            if unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &raw) } != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            *SAVED.lock().unwrap_or_else(|e| e.into_inner()) = Some(saved);
+            // сообщение о панике должно попасть на обычный экран, а не во второй
+            let previous = panic::take_hook();
+            panic::set_hook(Box::new(move |info| {
+                restore();
+                previous(info);
+            }));
+            let screen = Screen;
+            // второй экран, без курсора и без переноса длинных строк
+            print!("\x1b[?1049h\x1b[?25l\x1b[?7l");
+            io::stdout().flush()?;
+            Ok(screen)
+        }
+    }
+
+    impl Drop for Screen {
+        fn drop(&mut self) {
+            let _ = io::stdout().flush();
+            restore();
+        }
+    }
+
+    enum Key {
+        Char(char),
+        Up,
+        Down,
+        PageUp,
+        PageDown,
+        Home,
+        End,
+        Enter,
+        Backspace,
+        Esc,
+    }
+
+    fn read_byte(timeout_ms: i32) -> io::Result<Option<u8>> {
+        let mut fd = libc::pollfd { fd: libc::STDIN_FILENO, events: libc::POLLIN, revents: 0 };
+        // SAFETY: один pollfd на стеке
+        let ready = unsafe { libc::poll(&mut fd, 1, timeout_ms) };
+        if ready < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        if ready == 0 {
+            return Ok(None);
+        }
+        let mut b = 0u8;
+        // SAFETY: читаем один байт в переменную на стеке
+        match unsafe { libc::read(libc::STDIN_FILENO, (&mut b as *mut u8).cast(), 1) } {
+            1 => Ok(Some(b)),
+            0 => Err(io::Error::new(io::ErrorKind::UnexpectedEof, "stdin закрыт")),
+            _ => Err(io::Error::last_os_error()),
+        }
+    }
+
+    fn read_key() -> io::Result<Key> {
+        let b = loop {
+            if let Some(b) = read_byte(-1)? {
+                break b;
+            }
+        };
+        match b {
+            b'\r' | b'\n' => return Ok(Key::Enter),
+            0x7f | 0x08 => return Ok(Key::Backspace),
+            // Ctrl-C, как q
+            0x03 => return Ok(Key::Char('q')),
+            0x1b => {}
+            _ => {
+                // UTF-8: сколько ещё байт у символа
+                let len = match b {
+                    0xc0..=0xdf => 1,
+                    0xe0..=0xef => 2,
+                    0xf0..=0xf7 => 3,
+                    _ => 0,
+                };
+                let mut bytes = vec![b];
+                for _ in 0..len {
+                    bytes.extend(read_byte(50)?);
+                }
+                let c = String::from_utf8_lossy(&bytes).chars().next().unwrap_or('?');
+                return Ok(Key::Char(c));
+            }
+        }
+        // ESC [ ... — стрелки и PgUp/PgDn; одиночный ESC — отмена
+        if read_byte(50)? != Some(b'[') {
+            return Ok(Key::Esc);
+        }
+        let mut seq = Vec::new();
+        while let Some(b) = read_byte(50)? {
+            seq.push(b);
+            if (0x40..=0x7e).contains(&b) {
+                break;
+            }
+        }
+        Ok(match seq.as_slice() {
+            b"A" => Key::Up,
+            b"B" => Key::Down,
+            b"5~" => Key::PageUp,
+            b"6~" => Key::PageDown,
+            b"H" | b"1~" => Key::Home,
+            b"F" | b"4~" => Key::End,
+            _ => Key::Esc,
+        })
+    }
+
+    /// (строк, столбцов) окна.
+    fn window_size() -> (usize, usize) {
+        let mut ws = libc::winsize { ws_row: 0, ws_col: 0, ws_xpixel: 0, ws_ypixel: 0 };
+        // SAFETY: TIOCGWINSZ только заполняет переданную структуру
+        let ok = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut ws) } == 0;
+        if ok && ws.ws_row > 0 && ws.ws_col > 0 {
+            (ws.ws_row as usize, ws.ws_col as usize)
+        } else {
+            (24, 80)
+        }
+    }
+
+    fn clip(s: &str, width: usize) -> String {
+        s.chars().take(width).collect()
+    }
+
+    /// Строк под сообщения при текущем размере окна.
+    fn list_rows(view: &View) -> usize {
+        let (rows, _) = window_size();
+        let stats = if view.stats { STATS_ROWS + 1 } else { 0 };
+        rows.saturating_sub(1 + stats).max(1)
+    }
+
+    fn draw(view: &View, prompt: Option<(&str, &str)>) -> io::Result<()> {
+        let (rows, cols) = window_size();
+        let list = list_rows(view);
+        let mut out = String::new();
+        out.push_str("\x1b[H");
+        for row in 0..list {
+            out.push_str(&format!("\x1b[{};1H\x1b[K", row + 1));
+            let Some(&i) = view.shown.get(view.top + row) else {
+                continue;
+            };
+            let e = &view.entries[i];
+            let date = e.date.map(|d| d.format("%Y-%m-%d %H:%M").to_string());
+            let head = format!("{} {}: ", date.as_deref().unwrap_or("????-??-?? ??:??"), e.author);
+            let head = clip(&head, cols);
+            let text = clip(&e.text, cols.saturating_sub(head.chars().count()));
+            // совпадения с поиском — инверсией, автор — жирным
+            if view.matches(view.top + row) {
+                out.push_str(&format!("\x1b[7m{head}\x1b[0m{text}"));
+            } else {
+                out.push_str(&format!("\x1b[1m{head}\x1b[0m{text}"));
+            }
+        }
+        if view.stats {
+            out.push_str(&format!("\x1b[{};1H\x1b[K{}", list + 1, "─".repeat(cols)));
+            let lines = view.stats_lines();
+            for r in 0..STATS_ROWS {
+                let line = lines.get(r).map(|l| clip(l, cols)).unwrap_or_default();
+                out.push_str(&format!("\x1b[{};1H\x1b[K{line}", list + 2 + r));
+            }
+        }
+        let status = match prompt {
+            Some((label, input)) => format!("{label}{input}"),
+            None => {
+                let chat = view
+                    .shown
+                    .get(view.top)
+                    .map(|&i| view.chats[view.entries[i].chat].as_str())
+                    .unwrap_or("");
+                let pos = if view.shown.is_empty() { 0 } else { view.top + 1 };
+                let filters = view.filters.describe();
+                let mut s = format!(" {chat} | {pos}/{}", view.shown.len());
+                if !filters.is_empty() {
+                    s.push_str(&format!(" | {filters}"));
+                }
+                if let Some(q) = &view.search {
+                    s.push_str(&format!(" | поиск: {q}"));
+                }
+                if !view.status.is_empty() {
+                    s.push_str(&format!(" | {}", view.status));
+                }
+                s
+            }
+        };
+        out.push_str(&format!("\x1b[{rows};1H\x1b[K\x1b[7m{}\x1b[0m", clip(&status, cols)));
+        let mut stdout = io::stdout().lock();
+        stdout.write_all(out.as_bytes())?;
+        stdout.flush()
+    }
+
+    /// Строка ввода в строке состояния; None — отменено через Esc.
+    fn prompt(view: &View, label: &str) -> io::Result<Option<String>> {
+        let mut input = String::new();
+        loop {
+            draw(view, Some((label, &input)))?;
+            match read_key()? {
+                Key::Enter => return Ok(Some(input)),
+                Key::Esc => return Ok(None),
+                Key::Backspace => {
+                    input.pop();
+                }
+                Key::Char(c) if !c.is_control() => input.push(c),
+                _ => {}
+            }
+        }
+    }
+
+    pub fn run(mut view: View) -> Result<(), Box<dyn std::error::Error>> {
+        if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+            return Err("tgjsps tui работает в терминале: stdin и stdout не должны быть \
+                        перенаправлены"
+                .into());
+        }
+        let _screen = Screen::enter()?;
+        loop {
+            draw(&view, None)?;
+            let page = list_rows(&view);
+            let key = read_key()?;
+            view.status.clear();
+            match key {
+                Key::Char('q') => return Ok(()),
+                Key::Down | Key::Char('j') | Key::Enter => view.scroll(1, page),
+                Key::Up | Key::Char('k') => view.scroll(-1, page),
+                Key::PageDown | Key::Char(' ') => view.scroll(page as isize, page),
+                Key::PageUp | Key::Char('b') => view.scroll(-(page as isize), page),
+                Key::Home | Key::Char('g') => view.top = 0,
+                Key::End | Key::Char('G') => view.scroll(isize::MAX, page),
+                Key::Char('s') => view.stats = !view.stats,
+                Key::Char('n') => view.find(true),
+                Key::Char('N') => view.find(false),
+                Key::Char('/') => {
+                    if let Some(q) = prompt(&view, "/")? {
+                        view.search = (!q.is_empty()).then(|| q.to_lowercase());
+                        // совпадение может быть и в верхней строке
+                        if view.shown.is_empty() || !view.matches(view.top) {
+                            view.find(true);
+                        }
+                    }
+                }
+                Key::Char('a') => {
+                    if let Some(a) = prompt(&view, "Автор (пусто — все): ")? {
+                        let a = a.trim().to_lowercase();
+                        view.filters.author = (!a.is_empty()).then_some(a);
+                        view.refilter();
+                    }
+                }
+                Key::Char('d') => {
+                    if let Some(range) = prompt(&view, "Даты (С..ПО, пусто — все): ")? {
+                        match parse_range(&range) {
+                            Ok((since, until)) => {
+                                (view.filters.since, view.filters.until) = (since, until);
+                                view.refilter();
+                            }
+                            Err(e) => view.status = e,
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,230 @@
+//
+// ===================== УНИКАЛЬНЫЕ ЗНАЧЕНИЯ (HyperLogLog) =====================
+//
+// Сколько разных слов у каждого участника, разных ссылок и разных
+// сообщений, на которые отвечали. Точный ответ требует держать все
+// значения, а HyperLogLog — 4 КБ на счётчик при ошибке около 1,6%,
+// сколько бы значений ни прошло. Поэтому эти оценки есть и с --streaming,
+// и с --approx, где словари слов обрезаны.
+//
+// Пока значений немного (у большинства участников так и есть), счётчик
+// хранит сами их хеши: это меньше 4 КБ, и ответ точный. Дорос до
+// EXACT_LIMIT — переходит на регистры HyperLogLog.
+//
+// Хеш — SipHash-1-3 с постоянным ключом (cache::KeyHasher) по байтам
+// значения: он не зависит от сборки и процессора, поэтому счётчики из
+// кэша и из --threads складываются с новыми.
+//
+
+use ahash::{AHashMap, AHashSet};
+use simd_json::borrowed::Object;
+
+use std::io::{self, Write};
+
+use crate::cache::{Codec, KeyHasher, Reader};
+use crate::get_i64_field;
+use crate::links::for_each_url;
+
+// 2^12 регистров: стандартная ошибка 1.04 / sqrt(4096) ≈ 1,6%
+const PRECISION: u32 = 12;
+const REGISTERS: usize = 1 << PRECISION;
+// столько хешей (по 8 байт и накладные расходы множества) ещё меньше регистров
+const EXACT_LIMIT: usize = 256;
+const TOP_UNIQUE_AUTHORS: usize = 20;
+
+/// Хеш значения из нескольких частей; длина впереди каждой, чтобы
+/// ("ab", "c") и ("a", "bc") не совпали.
+fn stable_hash(parts: &[&[u8]]) -> u64 {
+    let mut h = KeyHasher::new();
+    for part in parts {
+        h.write(&(part.len() as u64).to_le_bytes());
+        h.write(part);
+    }
+    h.finish()
+}
+
+/// Счётчик различных значений.
+pub enum Hll {
+    // хеши значений, пока их не больше EXACT_LIMIT
+    Exact(AHashSet<u64>),
+    Sketch(Vec<u8>),
+}
+
+impl Default for Hll {
+    fn default() -> Self {
+        Hll::Exact(AHashSet::new())
+    }
+}
+
+impl Codec for Hll {
+    fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            Hll::Exact(hashes) => {
+                0u8.encode(out);
+                hashes.encode(out);
+            }
+            Hll::Sketch(registers) => {
+                1u8.encode(out);
+                registers.encode(out);
+            }
+        }
+    }
+
+    fn decode(r: &mut Reader) -> Result<Self, String> {
+        match u8::decode(r)? {
+            0 => Ok(Hll::Exact(AHashSet::decode(r)?)),
+            1 => {
+                let registers = Vec::<u8>::decode(r)?;
+                if registers.len() != REGISTERS {
+                    return Err("кэш повреждён".into());
+                }
+                Ok(Hll::Sketch(registers))
+            }
+            _ => Err("кэш повреждён".into()),
+        }
+    }
+}
+
+impl Hll {
+    pub fn add(&mut self, value: &str) {
+        self.insert(stable_hash(&[value.as_bytes()]));
+    }
+
+    fn insert(&mut self, h: u64) {
+        match self {
+            Hll::Exact(hashes) => {
+                hashes.insert(h);
+                if hashes.len() > EXACT_LIMIT {
+                    self.switch_to_sketch();
+                }
+            }
+            Hll::Sketch(registers) => sketch_insert(registers, h),
+        }
+    }
+
+    /// Перейти с точного подсчёта на регистры.
+    fn switch_to_sketch(&mut self) {
+        if let Hll::Exact(hashes) = self {
+            let mut registers = vec![0; REGISTERS];
+            for &h in hashes.iter() {
+                sketch_insert(&mut registers, h);
+            }
+            *self = Hll::Sketch(registers);
+        }
+    }
+
+    pub fn merge(&mut self, other: &Hll) {
+        match other {
+            Hll::Exact(hashes) => {
+                for &h in hashes {
+                    self.insert(h);
+                }
+            }
+            Hll::Sketch(theirs) => {
+                self.switch_to_sketch();
+                if let Hll::Sketch(mine) = self {
+                    for (r, &o) in mine.iter_mut().zip(theirs) {
+                        *r = (*r).max(o);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Оценка числа различных значений (до EXACT_LIMIT — точное число).
+    pub fn estimate(&self) -> u64 {
+        let registers = match self {
+            Hll::Exact(hashes) => return hashes.len() as u64,
+            Hll::Sketch(registers) => registers,
+        };
+        let m = REGISTERS as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        let sum: f64 = registers.iter().map(|&r| 2f64.powi(-(r as i32))).sum();
+        let raw = alpha * m * m / sum;
+        let zeros = registers.iter().filter(|&&r| r == 0).count();
+        // на малых числах точнее линейный подсчёт по пустым регистрам
+        let estimate = if raw <= 2.5 * m && zeros > 0 {
+            m * (m / zeros as f64).ln()
+        } else {
+            raw
+        };
+        estimate.round() as u64
+    }
+}
+
+fn sketch_insert(registers: &mut [u8], h: u64) {
+    let index = (h >> (64 - PRECISION)) as usize;
+    // позиция первой единицы в оставшихся битах (сторожевой бит — на случай нулей)
+    let rank = ((h << PRECISION) | (1 << (PRECISION - 1))).leading_zeros() as u8 + 1;
+    let r = &mut registers[index];
+    *r = (*r).max(rank);
+}
+
+#[derive(Default)]
+pub struct UniqueStats {
+    // автор -> разные слова (после стоп-слов и --stem, как в топе слов)
+    pub words_per_author: AHashMap<String, Hll>,
+    pub links: Hll,
+    // (чат, id) сообщений, на которые отвечали
+    pub reply_targets: Hll,
+}
+
+crate::cache::codec!(UniqueStats { words_per_author, links, reply_targets });
+
+impl UniqueStats {
+    /// Счётчик слов автора; имя копируется только для нового автора.
+    pub fn words_of(&mut self, author: &str) -> &mut Hll {
+        if !self.words_per_author.contains_key(author) {
+            self.words_per_author.insert(author.to_string(), Hll::default());
+        }
+        self.words_per_author.get_mut(author).expect("только что добавлен")
+    }
+
+    pub fn observe_links(&mut self, msg_obj: &Object) {
+        for_each_url(msg_obj, |url| self.links.add(url));
+    }
+
+    /// id уникальны только в пределах чата, поэтому считается пара.
+    pub fn observe_reply(&mut self, chat: &str, msg_obj: &Object) {
+        if let Some(reply_to) = get_i64_field(msg_obj, "reply_to_message_id") {
+            self.reply_targets.insert(stable_hash(&[chat.as_bytes(), &reply_to.to_le_bytes()]));
+        }
+    }
+
+    /// Объединить множества: точные — вставкой хешей, HLL — максимумом регистров.
+    pub fn merge(&mut self, other: UniqueStats) {
+        for (author, words) in other.words_per_author {
+            match self.words_per_author.get_mut(&author) {
+                Some(mine) => mine.merge(&words),
+                None => {
+                    self.words_per_author.insert(author, words);
+                }
+            }
+        }
+        self.links.merge(&other.links);
+        self.reply_targets.merge(&other.reply_targets);
+    }
+
+    /// Авторы по оценке числа разных слов.
+    pub fn authors_by_words(&self) -> Vec<(&str, u64)> {
+        let mut v: Vec<_> = self
+            .words_per_author
+            .iter()
+            .map(|(a, h)| (a.as_str(), h.estimate()))
+            .filter(|p| p.1 > 0)
+            .collect();
+        v.sort_by_key(|p| std::cmp::Reverse(p.1));
+        v
+    }
+
+    pub fn write_text<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(w, "Уникальное (оценка HyperLogLog, ±2%):")?;
+        writeln!(w, "  разных ссылок: ~{}", self.links.estimate())?;
+        writeln!(w, "  сообщений, на которые отвечали: ~{}", self.reply_targets.estimate())?;
+        writeln!(w, "  разных слов по участникам:")?;
+        for (author, n) in self.authors_by_words().into_iter().take(TOP_UNIQUE_AUTHORS) {
+            writeln!(w, "  - {}: ~{}", author, n)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,391 @@
+//
+// ===================== СЖАТЫЙ ВХОД (.zip, .gz) =====================
+//
+// Экспорт часто пересылают архивом. Формат узнаётся по первым байтам, а
+// не по расширению: gzip (в том числе склеенный из нескольких членов, как
+// пишет --compress) и zip, из которого берётся result.json — из корня или
+// из самой верхней папки. Распаковывается целиком в память; обычный JSON
+// читается как раньше.
+//
+// inflate свой (RFC 1951), как и кодировщик в gzip.rs. zstd не
+// поддерживается — на него понятная ошибка.
+//
+
+use std::error::Error;
+use std::fs::File;
+use std::io::Read;
+
+use crate::gzip::crc32_update;
+
+const GZIP_MAGIC: [u8; 2] = [0x1F, 0x8B];
+const ZIP_MAGIC: [u8; 4] = *b"PK\x03\x04";
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+const ZIP_EOCD: u32 = 0x0605_4B50;
+const ZIP_CENTRAL: u32 = 0x0201_4B50;
+const ZIP_LOCAL: u32 = 0x0403_4B50;
+
+const MAX_BITS: usize = 15;
+// коды до FAST_BITS бит декодируются одной выборкой из таблицы
+const FAST_BITS: u32 = 10;
+
+const LEN_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115,
+    131, 163, 195, 227, 258,
+];
+const LEN_EXTRA: [u8; 29] =
+    [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+// порядок длин кодов для таблицы длин в динамическом блоке
+const CLEN_ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+const TRUNCATED: &str = "сжатые данные оборваны";
+
+struct Bits<'a> {
+    data: &'a [u8],
+    pos: usize,
+    acc: u64,
+    n: u32,
+}
+
+impl<'a> Bits<'a> {
+    /// Подгружает байты, пока хватает данных; в конце потока битов меньше.
+    fn fill(&mut self) {
+        while self.n <= 56 {
+            let Some(&b) = self.data.get(self.pos) else {
+                return;
+            };
+            self.acc |= (b as u64) << self.n;
+            self.n += 8;
+            self.pos += 1;
+        }
+    }
+
+    fn take(&mut self, k: u32) -> Result<u32, String> {
+        if self.n < k {
+            self.fill();
+            if self.n < k {
+                return Err(TRUNCATED.into());
+            }
+        }
+        let v = (self.acc & ((1u64 << k) - 1)) as u32;
+        self.acc >>= k;
+        self.n -= k;
+        Ok(v)
+    }
+
+    fn align(&mut self) {
+        let drop = self.n % 8;
+        self.acc >>= drop;
+        self.n -= drop;
+    }
+
+    /// Сколько байт входа уже прочитано (без подгруженных про запас).
+    fn consumed(&self) -> usize {
+        self.pos - (self.n / 8) as usize
+    }
+}
+
+struct Huffman {
+    counts: [u16; MAX_BITS + 1],
+    symbols: Vec<u16>,
+    // символ << 4 | длина; 0 — код длиннее FAST_BITS
+    fast: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Self {
+        let mut counts = [0u16; MAX_BITS + 1];
+        for &len in lengths {
+            counts[len as usize] += 1;
+        }
+        counts[0] = 0;
+        let mut offsets = [0u16; MAX_BITS + 2];
+        for len in 1..=MAX_BITS {
+            offsets[len + 1] = offsets[len] + counts[len];
+        }
+        let mut symbols = vec![0u16; offsets[MAX_BITS + 1] as usize];
+        for (sym, &len) in lengths.iter().enumerate() {
+            if len != 0 {
+                symbols[offsets[len as usize] as usize] = sym as u16;
+                offsets[len as usize] += 1;
+            }
+        }
+
+        let mut fast = vec![0u16; 1 << FAST_BITS];
+        let (mut code, mut index) = (0u32, 0usize);
+        for len in 1..=FAST_BITS {
+            for _ in 0..counts[len as usize] {
+                let rev = code.reverse_bits() >> (32 - len);
+                let entry = symbols[index] << 4 | len as u16;
+                for slot in (rev as usize..1 << FAST_BITS).step_by(1 << len) {
+                    fast[slot] = entry;
+                }
+                code += 1;
+                index += 1;
+            }
+            code <<= 1;
+        }
+        Self { counts, symbols, fast }
+    }
+
+    fn decode(&self, bits: &mut Bits) -> Result<u16, String> {
+        if bits.n < MAX_BITS as u32 {
+            bits.fill();
+        }
+        let entry = self.fast[(bits.acc & ((1 << FAST_BITS) - 1)) as usize];
+        let len = (entry & 0xF) as u32;
+        if entry != 0 && len <= bits.n {
+            bits.acc >>= len;
+            bits.n -= len;
+            return Ok(entry >> 4);
+        }
+        // длинный код: по биту, канонический порядок
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for len in 1..=MAX_BITS {
+            code |= bits.take(1)? as i32;
+            let count = self.counts[len] as i32;
+            if code - count < first {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err("битый код Хаффмана".into())
+    }
+}
+
+fn fixed_tables() -> (Huffman, Huffman) {
+    let mut lengths = [0u8; 288];
+    lengths[..144].fill(8);
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+    lengths[280..].fill(8);
+    (Huffman::new(&lengths), Huffman::new(&[5; 30]))
+}
+
+fn dynamic_tables(bits: &mut Bits) -> Result<(Huffman, Huffman), String> {
+    let hlit = bits.take(5)? as usize + 257;
+    let hdist = bits.take(5)? as usize + 1;
+    let hclen = bits.take(4)? as usize + 4;
+    let mut clen = [0u8; 19];
+    for &i in &CLEN_ORDER[..hclen] {
+        clen[i] = bits.take(3)? as u8;
+    }
+    let clen_table = Huffman::new(&clen);
+
+    let mut lengths = vec![0u8; hlit + hdist];
+    let mut i = 0;
+    while i < lengths.len() {
+        let (value, repeat) = match clen_table.decode(bits)? {
+            sym @ 0..=15 => (sym as u8, 1),
+            16 => {
+                let prev = *lengths[..i].last().ok_or("повтор длины без предыдущей")?;
+                (prev, 3 + bits.take(2)? as usize)
+            }
+            17 => (0, 3 + bits.take(3)? as usize),
+            _ => (0, 11 + bits.take(7)? as usize),
+        };
+        if i + repeat > lengths.len() {
+            return Err("лишние длины кодов".into());
+        }
+        lengths[i..i + repeat].fill(value);
+        i += repeat;
+    }
+    Ok((Huffman::new(&lengths[..hlit]), Huffman::new(&lengths[hlit..])))
+}
+
+/// Распаковывает поток deflate в конец `out`; возвращает, сколько байт входа занял.
+fn inflate(data: &[u8], out: &mut Vec<u8>) -> Result<usize, String> {
+    let mut bits = Bits { data, pos: 0, acc: 0, n: 0 };
+    let start = out.len();
+    loop {
+        let last = bits.take(1)? == 1;
+        match bits.take(2)? {
+            0 => {
+                bits.align();
+                let len = bits.take(16)? as usize;
+                let nlen = bits.take(16)? as usize;
+                if len != !nlen & 0xFFFF {
+                    return Err("битый несжатый блок".into());
+                }
+                for _ in 0..len {
+                    out.push(bits.take(8)? as u8);
+                }
+            }
+            kind @ (1 | 2) => {
+                let (lit, dist) =
+                    if kind == 1 { fixed_tables() } else { dynamic_tables(&mut bits)? };
+                loop {
+                    let sym = lit.decode(&mut bits)? as usize;
+                    if sym < 256 {
+                        out.push(sym as u8);
+                        continue;
+                    }
+                    if sym == 256 {
+                        break;
+                    }
+                    let li = sym - 257;
+                    if li >= LEN_BASE.len() {
+                        return Err("битая длина совпадения".into());
+                    }
+                    let len = LEN_BASE[li] as usize + bits.take(LEN_EXTRA[li] as u32)? as usize;
+                    let di = dist.decode(&mut bits)? as usize;
+                    if di >= DIST_BASE.len() {
+                        return Err("битое расстояние совпадения".into());
+                    }
+                    let d = DIST_BASE[di] as usize + bits.take(DIST_EXTRA[di] as u32)? as usize;
+                    if d > out.len() - start {
+                        return Err("совпадение раньше начала данных".into());
+                    }
+                    let from = out.len() - d;
+                    for k in 0..len {
+                        out.push(out[from + k]);
+                    }
+                }
+            }
+            _ => return Err("неизвестный тип блока deflate".into()),
+        }
+        if last {
+            bits.align();
+            return Ok(bits.consumed());
+        }
+    }
+}
+
+fn le16(data: &[u8], at: usize) -> Result<usize, String> {
+    data.get(at..at + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]) as usize)
+        .ok_or_else(|| TRUNCATED.to_string())
+}
+
+fn le32(data: &[u8], at: usize) -> Result<u32, String> {
+    data.get(at..at + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| TRUNCATED.to_string())
+}
+
+/// Все члены gzip подряд.
+fn gunzip(data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut out = Vec::with_capacity(data.len() * 4);
+    let mut at = 0;
+    while data[at..].starts_with(&GZIP_MAGIC) {
+        if data.get(at + 2) != Some(&8) {
+            return Err("gzip: неизвестный метод сжатия".into());
+        }
+        let flags = *data.get(at + 3).ok_or(TRUNCATED)?;
+        let mut p = at + 10;
+        if flags & 4 != 0 {
+            p += 2 + le16(data, p)?;
+        }
+        for flag in [8, 16] {
+            if flags & flag != 0 {
+                let end = data.get(p..).and_then(|d| d.iter().position(|&b| b == 0));
+                p += end.ok_or(TRUNCATED)? + 1;
+            }
+        }
+        if flags & 2 != 0 {
+            p += 2;
+        }
+        let start = out.len();
+        p += inflate(data.get(p..).ok_or(TRUNCATED)?, &mut out)?;
+        let (crc, size) = (le32(data, p)?, le32(data, p + 4)?);
+        if crc32_update(0, &out[start..]) != crc || (out.len() - start) as u32 != size {
+            return Err("gzip: контрольная сумма не сходится".into());
+        }
+        at = p + 8;
+    }
+    Ok(out)
+}
+
+/// result.json из zip: из корня или из самой неглубокой папки.
+fn unzip_result(data: &[u8]) -> Result<Vec<u8>, String> {
+    let tail_from = data.len().saturating_sub(22 + 0xFFFF);
+    let eocd = (tail_from..data.len().saturating_sub(21))
+        .rev()
+        .find(|&i| le32(data, i) == Ok(ZIP_EOCD))
+        .ok_or("zip: не найдено оглавление архива")?;
+    let entries = le16(data, eocd + 10)?;
+    let mut p = le32(data, eocd + 16)? as usize;
+
+    let mut best: Option<(usize, usize, usize, usize)> = None;
+    for _ in 0..entries {
+        if le32(data, p)? != ZIP_CENTRAL {
+            return Err("zip: битое оглавление".into());
+        }
+        let flags = le16(data, p + 8)?;
+        let method = le16(data, p + 10)?;
+        let size = le32(data, p + 20)? as usize;
+        let (name_len, extra_len) = (le16(data, p + 28)?, le16(data, p + 30)?);
+        let comment_len = le16(data, p + 32)?;
+        let local = le32(data, p + 42)? as usize;
+        let name = data.get(p + 46..p + 46 + name_len).ok_or(TRUNCATED)?;
+        let name = String::from_utf8_lossy(name);
+        if name == "result.json" || name.ends_with("/result.json") {
+            if flags & 1 != 0 {
+                return Err("zip: архив зашифрован".into());
+            }
+            if size == u32::MAX as usize || local == u32::MAX as usize {
+                return Err("zip: архивы больше 4 ГБ (ZIP64) не поддерживаются".into());
+            }
+            let depth = name.matches('/').count();
+            if best.is_none_or(|b| depth < b.0) {
+                best = Some((depth, method, size, local));
+            }
+        }
+        p += 46 + name_len + extra_len + comment_len;
+    }
+
+    let (_, method, size, local) = best.ok_or("zip: в архиве нет result.json")?;
+    if le32(data, local)? != ZIP_LOCAL {
+        return Err("zip: битый заголовок файла".into());
+    }
+    let start = local + 30 + le16(data, local + 26)? + le16(data, local + 28)?;
+    let packed = data.get(start..start + size).ok_or(TRUNCATED)?;
+    match method {
+        0 => Ok(packed.to_vec()),
+        8 => {
+            let mut out = Vec::with_capacity(size * 4);
+            inflate(packed, &mut out)?;
+            Ok(out)
+        }
+        m => Err(format!("zip: метод сжатия {m} не поддерживается")),
+    }
+}
+
+/// Распакованный вход, если файл — архив; None — обычный JSON.
+pub fn unpack(path: &str) -> Result<Option<Vec<u8>>, Box<dyn Error>> {
+    let mut magic = [0u8; 4];
+    let n = File::open(path)?.read(&mut magic)?;
+    if !is_packed(&magic[..n]) {
+        return Ok(None);
+    }
+    unpack_data(&std::fs::read(path)?, path).map(Some)
+}
+
+pub(crate) fn is_packed(magic: &[u8]) -> bool {
+    [&GZIP_MAGIC[..], &ZIP_MAGIC, &ZSTD_MAGIC].iter().any(|m| magic.starts_with(m))
+}
+
+/// То же для уже прочитанных данных (stdin): обычный JSON возвращается как есть.
+pub fn unpack_bytes(data: Vec<u8>, what: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    if is_packed(&data) { unpack_data(&data, what) } else { Ok(data) }
+}
+
+fn unpack_data(data: &[u8], what: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    let unpacked = if data.starts_with(&GZIP_MAGIC) {
+        gunzip(data)
+    } else if data.starts_with(&ZIP_MAGIC) {
+        unzip_result(data)
+    } else {
+        Err("сжатие zstd не поддерживается — распакуйте файл или пережмите в .gz".into())
+    };
+    unpacked.map_err(|e| format!("Не удалось распаковать {what}: {e}").into())
+}
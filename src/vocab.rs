@@ -0,0 +1,124 @@
+//
+// ===================== СЛОВАРНЫЙ ЗАПАС =====================
+//
+// Всё считается по уже собранным word_freq / word_freq_per_author:
+// отношение "разных слов к словам всего" (type–token ratio).
+// TTR падает с объёмом текста, поэтому в рейтинг попадают только те,
+// кто написал хотя бы MIN_WORDS_FOR_RICHNESS слов.
+//
+// "Характерные слова" — TF-IDF, где документ — всё, что написал автор:
+// tf = доля слова в его текстах, idf = ln(авторов / авторов, у которых слово
+// есть). Слова, которые пишут все, получают idf = 0 и в подборку не попадают.
+// Разовые слова (меньше MIN_SIGNATURE_COUNT раз) отбрасываем — это шум.
+//
+
+use ahash::AHashMap;
+
+use std::io::{self, Write};
+
+use crate::{Stats, top_n_by};
+
+pub const MIN_WORDS_FOR_RICHNESS: usize = 100;
+const TOP_RICHNESS: usize = 20;
+pub const TOP_SIGNATURE_WORDS: usize = 10;
+const MIN_SIGNATURE_COUNT: usize = 2;
+
+pub struct Richness<'a> {
+    pub author: &'a str,
+    pub unique: usize,
+    pub total: usize,
+    pub ratio: f64,
+}
+
+/// Авторы с достаточным объёмом текста, по убыванию TTR.
+pub fn vocabulary_richness(stats: &Stats) -> Vec<Richness<'_>> {
+    let mut v: Vec<Richness> = stats
+        .word_freq_per_author
+        .iter()
+        .filter_map(|(author, words)| {
+            let total: usize = words.values().sum();
+            if total < MIN_WORDS_FOR_RICHNESS {
+                return None;
+            }
+            Some(Richness {
+                author: author.as_str(),
+                unique: words.len(),
+                total,
+                ratio: words.len() as f64 / total as f64,
+            })
+        })
+        .collect();
+    v.sort_by(|a, b| b.ratio.total_cmp(&a.ratio));
+    v
+}
+
+pub fn write_richness<W: Write>(w: &mut W, stats: &Stats) -> io::Result<()> {
+    writeln!(
+        w,
+        "Богатство словаря (разных слов / всего, от {} слов):",
+        MIN_WORDS_FOR_RICHNESS
+    )?;
+    for r in vocabulary_richness(stats).into_iter().take(TOP_RICHNESS) {
+        writeln!(
+            w,
+            "- {}: {:.3} ({} из {})",
+            r.author, r.ratio, r.unique, r.total
+        )?;
+    }
+    Ok(())
+}
+
+pub struct SignatureWord<'a> {
+    pub word: &'a str,
+    pub count: usize,
+    pub score: f64,
+}
+
+/// Авторы по числу слов и их TOP_SIGNATURE_WORDS самых характерных слов.
+pub fn signature_words(stats: &Stats) -> Vec<(&str, Vec<SignatureWord<'_>>)> {
+    let authors = stats.word_freq_per_author.len();
+    // слово -> у скольких авторов встречается
+    let mut df: AHashMap<&str, usize> = AHashMap::with_capacity(stats.word_freq.len());
+    for words in stats.word_freq_per_author.values() {
+        for word in words.keys() {
+            *df.entry(word.as_str()).or_insert(0) += 1;
+        }
+    }
+
+    let mut out: Vec<(&str, usize, Vec<SignatureWord>)> = stats
+        .word_freq_per_author
+        .iter()
+        .map(|(author, words)| {
+            let total: usize = words.values().sum();
+            let candidates: Vec<SignatureWord> = words
+                .iter()
+                .filter(|&(_, &count)| count >= MIN_SIGNATURE_COUNT)
+                .filter_map(|(word, &count)| {
+                    let idf = (authors as f64 / df[word.as_str()] as f64).ln();
+                    (idf > 0.0).then(|| SignatureWord {
+                        word: word.as_str(),
+                        count,
+                        score: count as f64 / total as f64 * idf,
+                    })
+                })
+                .collect();
+            let top = top_n_by(candidates, TOP_SIGNATURE_WORDS, |a, b| {
+                b.score.total_cmp(&a.score).then_with(|| a.word.cmp(b.word))
+            });
+            (author.as_str(), total, top)
+        })
+        .filter(|(_, _, top)| !top.is_empty())
+        .collect();
+    out.sort_by_key(|p| std::cmp::Reverse(p.1));
+    out.into_iter().map(|(author, _, top)| (author, top)).collect()
+}
+
+pub fn write_signature_words<W: Write>(w: &mut W, stats: &Stats) -> io::Result<()> {
+    writeln!(w, "Характерные слова участников (TF-IDF):")?;
+    for (author, top) in signature_words(stats) {
+        let words: Vec<String> =
+            top.iter().map(|s| format!("{} ({})", s.word, s.count)).collect();
+        writeln!(w, "- {}: {}", author, words.join(", "))?;
+    }
+    Ok(())
+}
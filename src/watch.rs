@@ -0,0 +1,46 @@
+//
+// ===================== НАБЛЮДЕНИЕ ЗА ВХОДОМ (--watch) =====================
+//
+// Без inotify и компании: раз в секунду сверяем время изменения и размер
+// файлов. Экспорт Telegram пишется не мгновенно, поэтому после изменения
+// ждём, пока файлы не перестанут меняться, и только тогда обрабатываем.
+//
+
+use std::path::PathBuf;
+use std::thread::sleep;
+use std::time::{Duration, SystemTime};
+
+const POLL: Duration = Duration::from_secs(1);
+
+/// Снимок файлов: путь и (mtime, размер); None — файла нет.
+pub type Stamp = Vec<(PathBuf, Option<(SystemTime, u64)>)>;
+
+pub fn stamp(files: &[PathBuf]) -> Stamp {
+    files
+        .iter()
+        .map(|path| {
+            let meta = std::fs::metadata(path).ok();
+            let mark = meta.and_then(|m| Some((m.modified().ok()?, m.len())));
+            (path.clone(), mark)
+        })
+        .collect()
+}
+
+/// Ждёт, пока снимок не станет отличаться от `seen` и не устоится.
+pub fn wait_for_change(current: impl Fn() -> Stamp, seen: &Stamp) {
+    loop {
+        sleep(POLL);
+        let mut now = current();
+        if now == *seen {
+            continue;
+        }
+        loop {
+            sleep(POLL);
+            let again = current();
+            if again == now {
+                return;
+            }
+            now = again;
+        }
+    }
+}
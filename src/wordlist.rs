@@ -0,0 +1,132 @@
+//
+// ===================== СЛОВАРИ (--wordlist) =====================
+//
+// Произвольные тематические словари: мат, названия проектов, мемы.
+// Файл — по записи на строку, пустые строки и "# ..." пропускаются:
+//   слово     — целое слово без учёта регистра
+//   корень*   — слова, начинающиеся с "корень"
+//   /regex/   — регулярное выражение как есть (регистр — через (?i))
+// Имя словаря в статистике — имя файла без расширения.
+//
+
+use ahash::AHashMap;
+use regex::Regex;
+
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::cache::Codec;
+use crate::{percent_of, sorted_by_count, top_by_count};
+
+const TOP_WORDLIST_ENTRIES: usize = 10;
+
+pub struct Wordlist {
+    pub name: String,
+    // (запись из файла, во что она скомпилирована)
+    entries: Vec<(String, Regex)>,
+    pub total: usize,
+    pub per_author: AHashMap<String, usize>,
+    pub per_entry: AHashMap<String, usize>,
+}
+
+fn entry_regex(entry: &str) -> Result<Regex, regex::Error> {
+    if let Some(re) = entry.strip_prefix('/').and_then(|e| e.strip_suffix('/')) {
+        Regex::new(re)
+    } else if let Some(prefix) = entry.strip_suffix('*') {
+        Regex::new(&format!(r"(?i)\b{}\w*", regex::escape(prefix)))
+    } else {
+        Regex::new(&format!(r"(?i)\b{}\b", regex::escape(entry)))
+    }
+}
+
+// Regex в кэш не пишется: записи компилируются заново при чтении
+impl Codec for Wordlist {
+    fn encode(&self, out: &mut Vec<u8>) {
+        let entries: Vec<String> = self.entries.iter().map(|(e, _)| e.clone()).collect();
+        self.name.encode(out);
+        entries.encode(out);
+        self.total.encode(out);
+        self.per_author.encode(out);
+        self.per_entry.encode(out);
+    }
+
+    fn decode(r: &mut crate::cache::Reader) -> Result<Self, String> {
+        let name = String::decode(r)?;
+        let entries = Vec::<String>::decode(r)?
+            .into_iter()
+            .map(|e| entry_regex(&e).map(|re| (e, re)).map_err(|e| e.to_string()))
+            .collect::<Result<_, _>>()?;
+        Ok(Wordlist {
+            name,
+            entries,
+            total: usize::decode(r)?,
+            per_author: AHashMap::decode(r)?,
+            per_entry: AHashMap::decode(r)?,
+        })
+    }
+}
+
+impl Wordlist {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let list = std::fs::read_to_string(path)
+            .map_err(|e| format!("Не удалось прочитать словарь «{}»: {e}", path.display()))?;
+        let mut entries = Vec::new();
+        for (i, line) in list.lines().enumerate() {
+            let entry = line.trim();
+            if entry.is_empty() || entry.starts_with('#') {
+                continue;
+            }
+            let re = entry_regex(entry).map_err(|e| {
+                format!("Словарь «{}», строка {}: {e}", path.display(), i + 1)
+            })?;
+            entries.push((entry.to_string(), re));
+        }
+        if entries.is_empty() {
+            return Err(format!("Словарь «{}» пуст", path.display()).into());
+        }
+        Ok(Wordlist {
+            name: path
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            entries,
+            total: 0,
+            per_author: AHashMap::new(),
+            per_entry: AHashMap::new(),
+        })
+    }
+
+    pub fn observe(&mut self, author: &str, text: &str) {
+        for (entry, re) in &self.entries {
+            let n = re.find_iter(text).count();
+            if n == 0 {
+                continue;
+            }
+            self.total += n;
+            *self.per_author.entry(author.to_string()).or_insert(0) += n;
+            *self.per_entry.entry(entry.clone()).or_insert(0) += n;
+        }
+    }
+
+    /// `messages` — сообщения по авторам, для частоты на 100 сообщений.
+    pub fn write_text<W: Write>(
+        &self,
+        w: &mut W,
+        messages: &AHashMap<String, usize>,
+    ) -> io::Result<()> {
+        writeln!(w, "Словарь «{}»: {} совпадений", self.name, self.total)?;
+        if self.total == 0 {
+            return Ok(());
+        }
+        writeln!(w, "  по участникам (на 100 сообщений):")?;
+        for (author, n) in sorted_by_count(&self.per_author) {
+            let rate = percent_of(n, messages.get(author).copied().unwrap_or(0));
+            writeln!(w, "  - {}: {} ({:.1})", author, n, rate)?;
+        }
+        writeln!(w, "  чаще всего:")?;
+        for (entry, n) in top_by_count(&self.per_entry, TOP_WORDLIST_ENTRIES) {
+            writeln!(w, "  - {}: {}", entry, n)?;
+        }
+        Ok(())
+    }
+}